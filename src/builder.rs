@@ -6,7 +6,28 @@
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 
-use crate::{mapping::R8Header, ProguardMapping, ProguardRecord};
+use thiserror::Error;
+
+use crate::{mapping::R8Header, MappingVersion, ProguardMapping, ProguardRecord};
+
+/// Error when merging multiple [`ProguardMapping`]s.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum MergeError<'s> {
+    /// Two mappings map the same obfuscated class to different original classes.
+    #[error("obfuscated class `{obfuscated}` is mapped to both `{first}` and `{second}`")]
+    ConflictingClass {
+        /// The obfuscated class name.
+        obfuscated: &'s str,
+        /// The original class name in the first mapping containing the class.
+        first: &'s str,
+        /// The original class name in the conflicting mapping.
+        second: &'s str,
+    },
+    /// Writing the merged mappings failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
 
 /// Newtype around &str for obfuscated class and method names.
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
@@ -121,6 +142,15 @@ pub(crate) struct MethodInfo {
     pub(crate) is_outline: bool,
 }
 
+/// A field record in a Proguard file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Field<'s> {
+    /// The original name of the field.
+    pub(crate) original: OriginalName<'s>,
+    /// The type of the field.
+    pub(crate) ty: &'s str,
+}
+
 /// Supported rewrite frame actions.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum RewriteAction<'s> {
@@ -145,10 +175,12 @@ pub(crate) struct RewriteRule<'s> {
 }
 
 /// A member record in a Proguard file.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) struct Member<'s> {
     /// The method the member refers to.
     pub(crate) method: MethodKey<'s>,
+    /// The return type of the method.
+    pub(crate) return_type: &'s str,
     /// The obfuscated/minified start line, `None` when no minified range prefix was present.
     pub(crate) startline: Option<usize>,
     /// The obfuscated/minified end line, `None` when no minified range prefix was present.
@@ -161,6 +193,8 @@ pub(crate) struct Member<'s> {
     pub(crate) outline_callsite_positions: Option<HashMap<usize, usize>>,
     /// Optional rewrite rules attached to this member.
     pub(crate) rewrite_rules: Vec<RewriteRule<'s>>,
+    /// The JVM descriptor of the minified method, if the mapping declares it.
+    pub(crate) residual_signature: Option<&'s str>,
 }
 
 fn parse_rewrite_rule<'s>(conditions: &[&'s str], actions: &[&'s str]) -> Option<RewriteRule<'s>> {
@@ -228,11 +262,28 @@ pub(crate) struct ParsedProguardMapping<'s> {
     pub(crate) method_infos: HashMap<MethodKey<'s>, MethodInfo>,
     /// A mapping from obfuscated class and method names to members.
     pub(crate) members: HashMap<(ObfuscatedName<'s>, ObfuscatedName<'s>), Members<'s>>,
+    /// A mapping from obfuscated class and field names to fields.
+    pub(crate) fields: HashMap<(ObfuscatedName<'s>, ObfuscatedName<'s>), Vec<Field<'s>>>,
+    /// The mapping format version declared by the mapping, if it could be parsed.
+    pub(crate) mapping_version: Option<MappingVersion>,
 }
 
 impl<'s> ParsedProguardMapping<'s> {
     pub(crate) fn parse(mapping: ProguardMapping<'s>, initialize_param_mapping: bool) -> Self {
-        let mut slf = Self::default();
+        Self::parse_with_version(mapping, initialize_param_mapping, None)
+    }
+
+    /// Parses a mapping whose format version was already declared by an
+    /// earlier part of the same file.
+    pub(crate) fn parse_with_version(
+        mapping: ProguardMapping<'s>,
+        initialize_param_mapping: bool,
+        mapping_version: Option<MappingVersion>,
+    ) -> Self {
+        let mut slf = Self {
+            mapping_version,
+            ..Self::default()
+        };
         let mut current_class_name = None;
         let mut current_class = ClassInfo::default();
         let mut unique_methods: HashSet<(&str, &str, &str)> = HashSet::new();
@@ -241,8 +292,26 @@ impl<'s> ParsedProguardMapping<'s> {
 
         while let Some(record) = records.next() {
             match record {
-                ProguardRecord::Field { .. } => {}
+                ProguardRecord::Field {
+                    ty,
+                    original,
+                    obfuscated,
+                } => {
+                    // Fields before the first class record have nothing to attach to.
+                    if let Some((current_class_obfuscated, _)) = current_class_name {
+                        slf.fields
+                            .entry((current_class_obfuscated, ObfuscatedName(obfuscated)))
+                            .or_default()
+                            .push(Field {
+                                original: OriginalName(original),
+                                ty,
+                            });
+                    }
+                }
                 ProguardRecord::Header { .. } => {}
+                ProguardRecord::R8Header(R8Header::MappingVersion { version }) => {
+                    slf.mapping_version = MappingVersion::parse(version);
+                }
                 ProguardRecord::R8Header(_) => {
                     // R8 headers can be skipped; they are already
                     // handled in the branches for `Class` and `Method`.
@@ -271,7 +340,9 @@ impl<'s> ParsedProguardMapping<'s> {
                             R8Header::Synthesized => current_class.is_synthesized = true,
                             R8Header::Outline => {}
                             R8Header::OutlineCallsite { .. } => {}
-                            R8Header::Other => {}
+                            R8Header::MappingVersion { .. }
+                            | R8Header::ResidualSignature { .. }
+                            | R8Header::Other(_) => {}
                         }
 
                         records.next();
@@ -279,6 +350,7 @@ impl<'s> ParsedProguardMapping<'s> {
                 }
 
                 ProguardRecord::Method {
+                    ty,
                     original,
                     obfuscated,
                     original_class,
@@ -347,20 +419,30 @@ impl<'s> ParsedProguardMapping<'s> {
 
                     let method_info: &mut MethodInfo = slf.method_infos.entry(method).or_default();
 
+                    // Outlines and rewrite rules only exist from version 2.0 on. Files
+                    // without a (known) version are assumed to use the latest one.
+                    let has_v2_headers = slf
+                        .mapping_version
+                        .is_none_or(|version| version >= MappingVersion::V2_0);
+                    let has_v2_2_headers = slf
+                        .mapping_version
+                        .is_none_or(|version| version >= MappingVersion::V2_2);
+
                     // Collect any OutlineCallsite mapping attached to this member.
                     let mut outline_callsite_positions: Option<HashMap<usize, usize>> = None;
+                    let mut residual_signature = None;
 
                     // Consume R8 headers attached to this method/member.
                     while let Some(ProguardRecord::R8Header(r8_header)) = records.peek() {
                         match r8_header {
                             R8Header::Synthesized => method_info.is_synthesized = true,
-                            R8Header::Outline => {
+                            R8Header::Outline if has_v2_headers => {
                                 method_info.is_outline = true;
                             }
                             R8Header::RewriteFrame {
                                 conditions,
                                 actions,
-                            } => {
+                            } if has_v2_headers => {
                                 if let Some(rule) = parse_rewrite_rule(conditions, actions) {
                                     rewrite_rules.push(rule);
                                 }
@@ -368,7 +450,7 @@ impl<'s> ParsedProguardMapping<'s> {
                             R8Header::OutlineCallsite {
                                 positions,
                                 outline: _,
-                            } => {
+                            } if has_v2_headers => {
                                 // Attach outline callsite mapping to this specific member.
                                 let map: HashMap<usize, usize> = positions
                                     .iter()
@@ -378,7 +460,10 @@ impl<'s> ParsedProguardMapping<'s> {
                                     outline_callsite_positions = Some(map);
                                 }
                             }
-                            R8Header::SourceFile { .. } | R8Header::Other => {}
+                            R8Header::ResidualSignature { signature } if has_v2_2_headers => {
+                                residual_signature = Some(*signature);
+                            }
+                            _ => {}
                         }
 
                         records.next();
@@ -386,12 +471,14 @@ impl<'s> ParsedProguardMapping<'s> {
 
                     let member = Member {
                         method,
+                        return_type: ty,
                         startline,
                         endline,
                         original_startline,
                         original_endline,
                         outline_callsite_positions,
                         rewrite_rules,
+                        residual_signature,
                     };
 
                     members.all.push(member.clone());
@@ -434,6 +521,120 @@ impl<'s> ParsedProguardMapping<'s> {
             slf.class_infos.insert(original, current_class);
         }
 
+        for members in slf.members.values_mut() {
+            propagate_residual_signatures(&mut members.all);
+            for by_params in members.by_params.values_mut() {
+                propagate_residual_signatures(by_params);
+            }
+        }
+
         slf
     }
+
+    /// Parses several mappings and merges them into one.
+    ///
+    /// See [`merge`](Self::merge) for how the mappings are combined.
+    pub(crate) fn parse_merged(
+        mappings: impl IntoIterator<Item = ProguardMapping<'s>>,
+        initialize_param_mapping: bool,
+    ) -> Result<Self, MergeError<'s>> {
+        let mut merged = Self::default();
+        for mapping in mappings {
+            merged.merge(Self::parse(mapping, initialize_param_mapping))?;
+        }
+        Ok(merged)
+    }
+
+    /// Merges `other` into `self`.
+    ///
+    /// Classes contained in both are combined, skipping members and fields that
+    /// are already present. If both map the same obfuscated class to different
+    /// original classes, an error is returned and `self` is left unchanged.
+    pub(crate) fn merge(&mut self, other: Self) -> Result<(), MergeError<'s>> {
+        for (obfuscated, original) in &other.class_names {
+            match self.class_names.get(obfuscated) {
+                Some(existing) if existing != original => {
+                    return Err(MergeError::ConflictingClass {
+                        obfuscated: obfuscated.as_str(),
+                        first: existing.as_str(),
+                        second: original.as_str(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        self.class_names.extend(other.class_names);
+        self.mapping_version = self.mapping_version.max(other.mapping_version);
+
+        for (original, info) in other.class_infos {
+            let existing = self.class_infos.entry(original).or_default();
+            existing.source_file = existing.source_file.or(info.source_file);
+            existing.is_synthesized |= info.is_synthesized;
+        }
+
+        for (method, info) in other.method_infos {
+            let existing = self.method_infos.entry(method).or_default();
+            existing.is_synthesized |= info.is_synthesized;
+            existing.is_outline |= info.is_outline;
+        }
+
+        for (key, members) in other.members {
+            let existing = self.members.entry(key).or_default();
+            extend_unique(&mut existing.all, members.all);
+            for (arguments, by_params) in members.by_params {
+                extend_unique(existing.by_params.entry(arguments).or_default(), by_params);
+            }
+        }
+
+        for (key, fields) in other.fields {
+            extend_unique(self.fields.entry(key).or_default(), fields);
+        }
+
+        Ok(())
+    }
+}
+
+/// Fills in the residual signatures of members that don't declare one themselves.
+///
+/// R8 only attaches the residual signature to one line of a method: the
+/// outermost frame of its first inline group. It applies to the whole group
+/// and to all following groups of the same method.
+fn propagate_residual_signatures(members: &mut [Member<'_>]) {
+    let mut current = None;
+    let mut start = 0;
+    while start < members.len() {
+        let first = &members[start];
+        let len = if first.endline.unwrap_or(0) == 0 {
+            1
+        } else {
+            members[start..]
+                .iter()
+                .take_while(|m| m.startline == first.startline && m.endline == first.endline)
+                .count()
+        };
+        let group = &mut members[start..start + len];
+        start += len;
+
+        let outermost = group[len - 1].method;
+        match group.iter().find_map(|m| m.residual_signature) {
+            Some(signature) => current = Some((outermost, signature)),
+            None if current.is_some_and(|(method, _)| method != outermost) => current = None,
+            None => {}
+        }
+        if let Some((_, signature)) = current {
+            for member in group {
+                member.residual_signature = Some(signature);
+            }
+        }
+    }
+}
+
+/// Appends the items of `other` that are not yet contained in `existing`.
+fn extend_unique<T: PartialEq>(existing: &mut Vec<T>, other: Vec<T>) {
+    for item in other {
+        if !existing.contains(&item) {
+            existing.push(item);
+        }
+    }
 }
@@ -85,6 +85,18 @@ impl MemberDebug<'_, '_> {
             .unwrap_or("<unknown>")
     }
 
+    fn return_type(&self) -> &str {
+        self.cache
+            .read_string(self.raw.return_type_offset)
+            .unwrap_or("<unknown>")
+    }
+
+    fn residual_signature(&self) -> Option<&str> {
+        self.cache
+            .read_string(self.raw.residual_signature_offset)
+            .ok()
+    }
+
     fn original_endline(&self) -> Option<u32> {
         if self.raw.original_endline != u32::MAX {
             Some(self.raw.original_endline)
@@ -106,17 +118,23 @@ impl fmt::Debug for MemberDebug<'_, '_> {
             .field("original_startline", &self.raw.original_startline)
             .field("original_endline", &self.original_endline())
             .field("params", &self.params())
+            .field("return_type", &self.return_type())
             .field("is_synthesized", &self.raw.is_synthesized())
             .field("is_outline", &self.raw.is_outline())
+            .field("residual_signature", &self.residual_signature())
             .finish()
     }
 }
 
 impl fmt::Display for MemberDebug<'_, '_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // XXX: We could print the actual return type here if we saved it in the format.
-        // Wonder if it's worth it, since we'd only use it in this display impl.
-        write!(f, "    {}:{}:<ret> ", self.raw.startline, self.raw.endline)?;
+        write!(
+            f,
+            "    {}:{}:{} ",
+            self.raw.startline,
+            self.raw.endline,
+            self.return_type()
+        )?;
         if let Some(original_class) = self.original_class() {
             write!(f, "{original_class}.")?;
         }
@@ -150,6 +168,16 @@ impl fmt::Display for CacheDebug<'_, '_> {
                     cache: self.cache
                 }
             )?;
+            for field in self.cache.get_class_fields(class).unwrap_or_default() {
+                let read = |offset| self.cache.read_string(offset).unwrap_or("<unknown>");
+                writeln!(
+                    f,
+                    "    {} {} -> {}",
+                    read(field.type_offset),
+                    read(field.original_name_offset),
+                    read(field.obfuscated_name_offset)
+                )?;
+            }
             let Some(members) = self.cache.get_class_members(class) else {
                 continue;
             };
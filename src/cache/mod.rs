@@ -7,12 +7,15 @@
 //!   - the number of class, member, and member-by-params entries,
 //!   - the number of outline mapping pairs,
 //!   - the number of rewrite rule entries and components,
+//!   - the number of field entries,
 //!   - and the length of the string section;
 //! * A [list](ProguardCache::classes) of [`Class`](raw::Class) entries;
 //! * A [list](ProguardCache::members) of [`Member`](raw::Member) entries;
 //! * Another [list](ProguardCache::members_by_params) of `Member` entries, sorted by parameter strings;
 //! * A [list] of outline mapping pairs shared by all members;
 //! * A [list] of rewrite rule entries and their components;
+//! * A [list](ProguardCache::fields) of [`Field`](raw::Field) entries;
+//! * An [index](ProguardCache::classes_by_original) of class entries, sorted by original name;
 //! * A [string section](ProguardCache::string_bytes) in which class names, method names, &c. are collected.
 //!   Whenever a class or member entry references a string, it is by offset into this section.
 //!
@@ -21,9 +24,12 @@
 //! * an obfuscated and an original name,
 //! * optionally a file name,
 //! * an offset and length for the class's associated records in the `members` and `members_by_params` section, respectively,
+//! * an offset and length for the class's associated records in the `fields` section,
 //! * and an `is_synthesized` flag.
 //!
-//! Class entries are sorted by obfuscated name.
+//! Class entries are sorted by obfuscated name. To look up classes by their
+//! original name, the cache additionally contains a list of class entry
+//! indices, sorted by the original names of the classes they point to.
 //!
 //! ## Member entries
 //! A member entry always contains
@@ -38,7 +44,8 @@
 //! It may also contain
 //! * an original class name,
 //! * an original file name,
-//! * and original start and end lines.
+//! * original start and end lines,
+//! * and the residual signature of the minified method.
 //!
 //! Member entries in `members` are sorted by the class they belong to, then by
 //! obfuscated method name, and finally by the order in which they were encountered
@@ -48,6 +55,15 @@
 //! method name, then by params string, and finally by the order in which they were encountered in the
 //! original proguard file.
 //!
+//! ## Field entries
+//! A field entry contains
+//! * an obfuscated and an original field name,
+//! * and the field's type.
+//!
+//! Field entries are sorted by the class they belong to, then by obfuscated
+//! field name, and finally by the order in which they were encountered in the
+//! original proguard file.
+//!
 //! ## Outline pairs section
 //! The outline pairs section is a flat array of pairs mapping an outline-position to a callsite line.
 //! Each [`Member`](raw::Member) that carries outline callsite information references a sub-slice of this
@@ -74,14 +90,28 @@ mod raw;
 
 use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt::Write;
 
 use thiserror::Error;
 
 use crate::builder::{RewriteAction, RewriteCondition, RewriteRule};
-use crate::mapper::{format_cause, format_frames, format_throwable};
-use crate::utils::{class_name_to_descriptor, extract_class_name, synthesize_source_file};
-use crate::{java, stacktrace, DeobfuscatedSignature, StackFrame, StackTrace, Throwable};
+#[cfg(feature = "regex")]
+use crate::frame_regex::{format_captures, remap_type, FrameCaptures};
+use crate::logcat::{self, LogcatReader, PrefixedWriter};
+use crate::mapper::{
+    format_cause, format_frames, format_throwable, prune_synthesized, remapped_provenance,
+    split_caption, split_chains, StreamState,
+};
+use crate::message;
+use crate::stacktrace::{FileSource, FrameProvenance};
+use crate::thread_dump::{self, ThreadDump};
+use crate::utils::{
+    class_name_to_descriptor, extract_class_name, minified_line, synthesize_source_file,
+};
+use crate::{
+    java, stacktrace, DeobfuscatedSignature, RemapOptions, StackFrame, StackTrace, Throwable,
+};
 
 /// Maximum number of frames emitted by span expansion for a single mapping entry.
 ///
@@ -97,7 +127,7 @@ pub use raw::{ProguardCache, PRGCACHE_VERSION};
 /// Result of looking up member mappings for a frame.
 /// Contains: (members, prepared_frame, rewrite_rules, had_mappings, has_line_info, outer_source_file)
 type MemberLookupResult<'data> = (
-    &'data [raw::Member],
+    Cow<'data, [raw::Member]>,
     StackFrame<'data>,
     Vec<RewriteRule<'data>>,
     bool,
@@ -131,6 +161,9 @@ pub enum CacheErrorKind {
     /// Member data could not be parsed from the cache file.
     #[error("could not read members")]
     InvalidMembers,
+    /// Field data could not be parsed from the cache file.
+    #[error("could not read fields")]
+    InvalidFields,
     /// The header claimed an incorrect number of string bytes.
     #[error("expected {expected} string bytes, found {found}")]
     UnexpectedStringBytes {
@@ -169,14 +202,23 @@ impl<'data> ProguardCache<'data> {
         frame: &StackFrame<'data>,
         reference_file: Option<&'data str>,
     ) -> StackFrame<'data> {
-        let file = synthesize_source_file(frame.class, reference_file).map(Cow::Owned);
+        let file = synthesize_source_file(&frame.class, reference_file).map(Cow::Owned);
+        let provenance =
+            remapped_provenance(frame, &file, FileSource::Synthesized).map(|provenance| {
+                FrameProvenance {
+                    class_only: true,
+                    ..provenance
+                }
+            });
         StackFrame {
-            class: frame.class,
-            method: frame.method,
+            class: frame.class.clone(),
+            method: frame.method.clone(),
             file,
             line: Some(frame.line.unwrap_or(0)),
-            parameters: frame.parameters,
+            parameters: frame.parameters.clone(),
+            signature: None,
             method_synthesized: false,
+            provenance,
         }
     }
 
@@ -194,6 +236,22 @@ impl<'data> ProguardCache<'data> {
         self.classes.get(idx)
     }
 
+    fn get_class_by_original(&self, name: &str) -> Option<&raw::Class> {
+        // Classes with the same original name are sorted by obfuscated name,
+        // the first one wins.
+        let idx = self.classes_by_original.partition_point(|idx| {
+            self.classes
+                .get(*idx as usize)
+                .and_then(|class| self.read_string(class.original_name_offset).ok())
+                .is_some_and(|original| original < name)
+        });
+
+        let class = self
+            .classes
+            .get(*self.classes_by_original.get(idx)? as usize)?;
+        (self.read_string(class.original_name_offset).ok()? == name).then_some(class)
+    }
+
     fn get_class_members(&self, class: &raw::Class) -> Option<&'data [raw::Member]> {
         let raw::Class {
             members_offset,
@@ -218,6 +276,18 @@ impl<'data> ProguardCache<'data> {
         self.members_by_params.get(start..end)
     }
 
+    pub(crate) fn get_class_fields(&self, class: &raw::Class) -> Option<&'data [raw::Field]> {
+        let raw::Class {
+            fields_offset,
+            fields_len,
+            ..
+        } = class;
+        let start = *fields_offset as usize;
+        let end = start.checked_add(*fields_len as usize)?;
+
+        self.fields.get(start..end)
+    }
+
     /// Remaps an obfuscated Class.
     ///
     /// This works on the fully-qualified name of the class, with its complete
@@ -277,6 +347,289 @@ impl<'data> ProguardCache<'data> {
         Some((original_class, original_method))
     }
 
+    /// Remaps an obfuscated Class Field.
+    ///
+    /// The `class` argument has to be the fully-qualified obfuscated name of the
+    /// class, with its complete module prefix.
+    ///
+    /// If the `field` can be resolved unambiguously, the remapped `class`,
+    /// the original field name and the field type will be returned,
+    /// otherwise `None` is being returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use proguard::{ProguardMapping, ProguardCache};
+    /// let mapping = ProguardMapping::new(
+    ///     br#"android.arch.core.executor.ArchTaskExecutor -> a.a.a.a.c:
+    ///     android.arch.core.executor.TaskExecutor mDelegate -> d"#,
+    /// );
+    /// let mut cache = Vec::new();
+    /// ProguardCache::write(&mapping, &mut cache).unwrap();
+    /// let cache = ProguardCache::parse(&cache).unwrap();
+    ///
+    /// let mapped = cache.remap_field("a.a.a.a.c", "d");
+    /// assert_eq!(
+    ///     mapped,
+    ///     Some((
+    ///         "android.arch.core.executor.ArchTaskExecutor",
+    ///         "mDelegate",
+    ///         "android.arch.core.executor.TaskExecutor"
+    ///     ))
+    /// );
+    /// ```
+    pub fn remap_field(
+        &self,
+        class: &str,
+        field: &str,
+    ) -> Option<(&'data str, &'data str, &'data str)> {
+        let class = self.get_class(class)?;
+        let fields = self.get_class_fields(class)?;
+
+        let start = fields.partition_point(|f| {
+            self.read_string(f.obfuscated_name_offset)
+                .is_ok_and(|name| name < field)
+        });
+        let mut iter = fields[start..].iter().take_while(|f| {
+            self.read_string(f.obfuscated_name_offset)
+                .is_ok_and(|name| name == field)
+        });
+        let first = iter.next()?;
+
+        // Obfuscated field names are only unique together with their type,
+        // which we don't know here, so all candidates have to agree.
+        let all_matching = iter.all(|f| {
+            f.original_name_offset == first.original_name_offset
+                && f.type_offset == first.type_offset
+        });
+
+        if !all_matching {
+            return None;
+        }
+
+        let original_class = self.read_string(class.original_name_offset).ok()?;
+        let original_field = self.read_string(first.original_name_offset).ok()?;
+        let ty = self.read_string(first.type_offset).ok()?;
+
+        Some((original_class, original_field, ty))
+    }
+
+    /// Remaps the obfuscated class, method and field names in an exception message.
+    ///
+    /// Names are only replaced if they can be remapped unambiguously, see
+    /// [`remap_class`](Self::remap_class), [`remap_method`](Self::remap_method)
+    /// and [`remap_field`](Self::remap_field). If nothing was remapped, the
+    /// `message` is returned as it is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use proguard::{ProguardMapping, ProguardCache};
+    /// let mapping = ProguardMapping::new(
+    ///     br#"com.example.Main -> a.b:
+    ///     int count -> a
+    /// com.example.Other -> a.c:"#,
+    /// );
+    /// let mut cache = Vec::new();
+    /// ProguardCache::write(&mapping, &mut cache).unwrap();
+    /// let cache = ProguardCache::parse(&cache).unwrap();
+    ///
+    /// assert_eq!(
+    ///     cache.remap_message("a.b cannot be cast to a.c"),
+    ///     "com.example.Main cannot be cast to com.example.Other"
+    /// );
+    /// assert_eq!(
+    ///     cache.remap_message("Attempt to read from field 'int a.b.a' on a null object reference"),
+    ///     "Attempt to read from field 'int com.example.Main.count' on a null object reference"
+    /// );
+    /// ```
+    pub fn remap_message<'m>(&self, message: &'m str) -> Cow<'m, str> {
+        message::remap_message(
+            message,
+            |class| self.remap_class(class),
+            |class, member, is_method| {
+                if is_method {
+                    self.remap_method(class, member)
+                } else {
+                    self.remap_field(class, member)
+                        .map(|(class, field, _)| (class, field))
+                }
+            },
+        )
+    }
+
+    /// Obfuscates an original Class.
+    ///
+    /// This is the inverse of [`remap_class`](Self::remap_class) and works on the
+    /// fully-qualified name of the class, with its complete module prefix. If
+    /// several obfuscated classes map to the same original class, the first one
+    /// in lexicographic order is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use proguard::{ProguardMapping, ProguardCache};
+    /// let mapping = ProguardMapping::new(br#"android.arch.core.executor.ArchTaskExecutor -> a.a.a.a.c:"#);
+    /// let mut cache = Vec::new();
+    /// ProguardCache::write(&mapping, &mut cache).unwrap();
+    /// let cache = ProguardCache::parse(&cache).unwrap();
+    ///
+    /// let obfuscated = cache.obfuscate_class("android.arch.core.executor.ArchTaskExecutor");
+    /// assert_eq!(obfuscated, Some("a.a.a.a.c"));
+    /// ```
+    pub fn obfuscate_class(&self, class: &str) -> Option<&'data str> {
+        let class = self.get_class_by_original(class)?;
+        self.read_string(class.obfuscated_name_offset).ok()
+    }
+
+    /// Obfuscates an original Class Method.
+    ///
+    /// The `class` argument has to be the fully-qualified original name of the
+    /// class, and `arguments` the argument string as written in the mapping file,
+    /// for example `"java.lang.Object,java.lang.Object"`.
+    ///
+    /// Mapping entries of methods inlined into other methods are not considered.
+    /// If the method can be resolved unambiguously, the obfuscated class and
+    /// method names are returned, otherwise `None` is being returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use proguard::{ProguardMapping, ProguardCache};
+    /// let mapping = ProguardMapping::new(
+    ///     br#"android.arch.core.executor.ArchTaskExecutor -> a.a.a.a.c:
+    ///     1:1:void executeOnDiskIO(java.lang.Runnable):96:96 -> a
+    ///     2:2:boolean isMainThread():116:116 -> a"#,
+    /// );
+    /// let mut cache = Vec::new();
+    /// ProguardCache::write(&mapping, &mut cache).unwrap();
+    /// let cache = ProguardCache::parse(&cache).unwrap();
+    ///
+    /// let obfuscated = cache.obfuscate_method(
+    ///     "android.arch.core.executor.ArchTaskExecutor",
+    ///     "isMainThread",
+    ///     "",
+    /// );
+    /// assert_eq!(obfuscated, Some(("a.a.a.a.c", "a")));
+    /// ```
+    pub fn obfuscate_method(
+        &self,
+        class: &str,
+        method: &str,
+        arguments: &str,
+    ) -> Option<(&'data str, &'data str)> {
+        let class = self.get_class_by_original(class)?;
+        let members = self.get_class_members(class)?;
+
+        let mut candidates = members.iter().enumerate().filter(|(idx, m)| {
+            m.original_class_offset == u32::MAX
+                && self.read_string(m.original_name_offset).ok() == Some(method)
+                && self.read_string(m.params_offset).unwrap_or_default() == arguments
+                && is_outermost_member(members, *idx)
+        });
+        let (_, first) = candidates.next()?;
+
+        // Different obfuscated names for the same method can't be told apart.
+        let all_matching =
+            candidates.all(|(_, m)| m.obfuscated_name_offset == first.obfuscated_name_offset);
+        if !all_matching {
+            return None;
+        }
+
+        let obfuscated_class = self.read_string(class.obfuscated_name_offset).ok()?;
+        let obfuscated_method = self.read_string(first.obfuscated_name_offset).ok()?;
+
+        Some((obfuscated_class, obfuscated_method))
+    }
+
+    /// Obfuscates a single original Stackframe.
+    ///
+    /// This is the inverse of [`remap_frame`](Self::remap_frame): the frame's
+    /// class and method are looked up by their original names, and its line is
+    /// mapped back into the minified line ranges that produce it. Every
+    /// returned frame remaps to a chain that contains the given `frame`. This
+    /// chain also contains the methods inlined into it or the methods it was
+    /// inlined into, so `frame` is not necessarily its top frame.
+    ///
+    /// If the frame has [parameters](StackFrame::with_parameters), only methods
+    /// with a matching argument string are considered. If the frame has no
+    /// line, one frame without a line is returned per obfuscated method.
+    ///
+    /// The returned frames are sorted by method name and line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use proguard::{ProguardMapping, ProguardCache, StackFrame};
+    /// let mapping = ProguardMapping::new(
+    ///     br#"android.arch.core.internal.SafeIterableMap -> a.a.a.b.c:
+    ///     13:13:java.util.Map$Entry eldest():168:168 -> a"#,
+    /// );
+    /// let mut cache = Vec::new();
+    /// ProguardCache::write(&mapping, &mut cache).unwrap();
+    /// let cache = ProguardCache::parse(&cache).unwrap();
+    ///
+    /// let frame = StackFrame::new("android.arch.core.internal.SafeIterableMap", "eldest", 168);
+    /// assert_eq!(
+    ///     cache.obfuscate_frame(&frame),
+    ///     vec![StackFrame::new("a.a.a.b.c", "a", 13)],
+    /// );
+    /// ```
+    pub fn obfuscate_frame(&self, frame: &StackFrame<'_>) -> Vec<StackFrame<'data>> {
+        let mut frames = Vec::new();
+        let Some(class) = self.get_class_by_original(&frame.class) else {
+            return frames;
+        };
+        let Ok(obfuscated_class) = self.read_string(class.obfuscated_name_offset) else {
+            return frames;
+        };
+
+        for m in self.get_class_members(class).unwrap_or_default() {
+            if m.original_class_offset != u32::MAX
+                || self.read_string(m.original_name_offset).ok() != Some(frame.method())
+            {
+                continue;
+            }
+            if let Some(parameters) = frame.parameters() {
+                if self.read_string(m.params_offset).unwrap_or_default() != parameters {
+                    continue;
+                }
+            }
+            let Ok(method) = self.read_string(m.obfuscated_name_offset) else {
+                continue;
+            };
+
+            let line = match frame.line {
+                Some(line) => match minified_line(
+                    m.startline().map(|v| v as usize),
+                    m.endline().map(|v| v as usize),
+                    m.original_startline().map(|v| v as usize),
+                    (m.original_endline != u32::MAX).then_some(m.original_endline as usize),
+                    line,
+                ) {
+                    Some(line) => Some(line),
+                    None => continue,
+                },
+                None => None,
+            };
+
+            frames.push(StackFrame {
+                class: Cow::Borrowed(obfuscated_class),
+                method: Cow::Borrowed(method),
+                line,
+                file: None,
+                parameters: None,
+                signature: None,
+                method_synthesized: false,
+                provenance: None,
+            });
+        }
+
+        frames.sort_by(|a, b| (&a.method, a.line).cmp(&(&b.method, b.line)));
+        frames.dedup();
+        frames
+    }
+
     fn decode_rewrite_rules(&self, member: &raw::Member) -> Vec<RewriteRule<'data>> {
         let mut rules = Vec::new();
         let start = member.rewrite_rules_offset as usize;
@@ -353,21 +706,23 @@ impl<'data> ProguardCache<'data> {
         &'data self,
         frame: &StackFrame<'data>,
     ) -> Option<MemberLookupResult<'data>> {
-        let class = self.get_class(frame.class)?;
+        let class = self.get_class(&frame.class)?;
         let original_class = self
             .read_string(class.original_name_offset)
-            .unwrap_or(frame.class);
+            .map(Cow::Borrowed)
+            .unwrap_or_else(|_| frame.class.clone());
 
         // Get the outer source file for synthesis
         let outer_source_file = self.read_string(class.file_name_offset).ok();
 
         let mut prepared_frame = frame.clone();
+        prepared_frame.provenance = Some(FrameProvenance::new(frame));
         prepared_frame.class = original_class;
 
-        let method_name = prepared_frame.method;
-        let mapping_entries: &[raw::Member] = if let Some(parameters) = prepared_frame.parameters {
+        let method_name = prepared_frame.method();
+        let mapping_entries = if let Some(parameters) = prepared_frame.parameters() {
             let members = self.get_class_members_by_params(class)?;
-            Self::find_range_by_binary_search(members, |m| {
+            let members = Self::find_range_by_binary_search(members, |m| {
                 let Ok(obfuscated_name) = self.read_string(m.obfuscated_name_offset) else {
                     return Ordering::Greater;
                 };
@@ -377,15 +732,17 @@ impl<'data> ProguardCache<'data> {
                     ""
                 };
                 (obfuscated_name, params).cmp(&(method_name, parameters))
-            })?
+            })?;
+            Cow::Borrowed(members)
         } else {
             let members = self.get_class_members(class)?;
-            Self::find_range_by_binary_search(members, |m| {
+            let members = Self::find_range_by_binary_search(members, |m| {
                 let Ok(obfuscated_name) = self.read_string(m.obfuscated_name_offset) else {
                     return Ordering::Greater;
                 };
                 obfuscated_name.cmp(method_name)
-            })?
+            })?;
+            self.narrow_by_signature(members, prepared_frame.signature())
         };
 
         // Collect rewrite rules and check had_mappings by iterating members
@@ -393,7 +750,7 @@ impl<'data> ProguardCache<'data> {
         let mut had_mappings = false;
 
         if prepared_frame.parameters.is_none() {
-            for member in mapping_entries {
+            for member in mapping_entries.iter() {
                 // Check if this member would produce a frame (line matching)
                 let pf_line = prepared_frame.line.unwrap_or(0);
                 let startline = member.startline().unwrap_or(0) as usize;
@@ -406,7 +763,7 @@ impl<'data> ProguardCache<'data> {
         } else {
             // With parameters, all members match
             had_mappings = !mapping_entries.is_empty();
-            for member in mapping_entries {
+            for member in mapping_entries.iter() {
                 rewrite_rules.extend(self.decode_rewrite_rules(member));
             }
         }
@@ -447,7 +804,7 @@ impl<'data> ProguardCache<'data> {
         RemappedFrameIter::members(
             self,
             prepared_frame,
-            members.iter(),
+            members,
             0,
             had_mappings,
             has_line_info,
@@ -455,6 +812,67 @@ impl<'data> ProguardCache<'data> {
         )
     }
 
+    /// Remaps a single Stackframe into its alternative inline chains.
+    ///
+    /// Unlike [`remap_frame`](Self::remap_frame), which returns all candidates
+    /// as one flat sequence, this keeps apart frames that were inlined into
+    /// each other and frames that are alternatives to each other. Each element
+    /// of the result is one possible original call chain, sorted top to
+    /// bottom. The result contains more than one chain when the mapping is
+    /// ambiguous for the frame.
+    pub fn remap_frame_alternatives<'r: 'data>(
+        &'r self,
+        frame: &StackFrame<'data>,
+    ) -> Vec<Vec<StackFrame<'data>>> {
+        let mut iter = self.remap_frame(frame);
+        split_chains(std::iter::from_fn(|| iter.next_with_outermost()))
+    }
+
+    /// Remaps a single Stackframe like [`remap_frame`](Self::remap_frame),
+    /// using the given `options`.
+    ///
+    /// Of the `options`, only [`RemapOptions::prefer_non_synthesized`] applies
+    /// to single frames.
+    pub fn remap_frame_with_options<'r: 'data>(
+        &'r self,
+        frame: &StackFrame<'data>,
+        options: &RemapOptions,
+    ) -> Vec<StackFrame<'data>> {
+        self.remap_frame_with_outermost(frame, options)
+            .into_iter()
+            .map(|(frame, _)| frame)
+            .collect()
+    }
+
+    /// Remaps a single Stackframe into its alternative inline chains like
+    /// [`remap_frame_alternatives`](Self::remap_frame_alternatives), using the
+    /// given `options`.
+    ///
+    /// Of the `options`, only [`RemapOptions::prefer_non_synthesized`] applies
+    /// to single frames.
+    pub fn remap_frame_alternatives_with_options<'r: 'data>(
+        &'r self,
+        frame: &StackFrame<'data>,
+        options: &RemapOptions,
+    ) -> Vec<Vec<StackFrame<'data>>> {
+        split_chains(self.remap_frame_with_outermost(frame, options))
+    }
+
+    /// Remaps a single Stackframe, returning the frames together with whether
+    /// they are the outermost frame of their inline chain.
+    fn remap_frame_with_outermost<'r: 'data>(
+        &'r self,
+        frame: &StackFrame<'data>,
+        options: &RemapOptions,
+    ) -> Vec<(StackFrame<'data>, bool)> {
+        let mut iter = self.remap_frame(frame);
+        let mut frames: Vec<_> = std::iter::from_fn(|| iter.next_with_outermost()).collect();
+        if options.prefer_non_synthesized {
+            prune_synthesized(&mut frames);
+        }
+        frames
+    }
+
     /// Remaps a single stack frame through the complete processing pipeline.
     ///
     /// This method combines:
@@ -482,16 +900,17 @@ impl<'data> ProguardCache<'data> {
     where
         'r: 'data,
     {
-        if self.is_outline_frame(frame.class, frame.method) {
+        if self.is_outline_frame(&frame.class, &frame.method) {
             *carried_outline_pos = Some(frame.line.unwrap_or(0));
             return None;
         }
 
-        let effective = self.prepare_frame_for_mapping(frame, carried_outline_pos);
+        let (effective, provenance) =
+            self.prepare_frame_with_provenance(frame, carried_outline_pos);
 
         let Some((
             members,
-            prepared_frame,
+            mut prepared_frame,
             rewrite_rules,
             had_mappings,
             has_line_info,
@@ -499,14 +918,16 @@ impl<'data> ProguardCache<'data> {
         )) = self.find_members_and_rules(&effective)
         else {
             // Even if we cannot resolve a member mapping, we may still be able to remap the class.
-            if let Some(class) = self.get_class(effective.class) {
+            if let Some(class) = self.get_class(&effective.class) {
                 let original_class = self
                     .read_string(class.original_name_offset)
-                    .unwrap_or(effective.class);
+                    .map(Cow::Borrowed)
+                    .unwrap_or_else(|_| effective.class.clone());
                 let outer_source_file = self.read_string(class.file_name_offset).ok();
                 return Some(RemappedFrameIter::single(self.remap_class_only(
                     &StackFrame {
                         class: original_class,
+                        provenance: Some(provenance),
                         ..effective
                     },
                     outer_source_file,
@@ -515,6 +936,8 @@ impl<'data> ProguardCache<'data> {
             return Some(RemappedFrameIter::empty());
         };
 
+        prepared_frame.provenance = Some(provenance);
+
         // Compute skip_count from rewrite rules
         let skip_count = if apply_rewrite {
             compute_skip_count(&rewrite_rules, exception_descriptor)
@@ -525,7 +948,7 @@ impl<'data> ProguardCache<'data> {
         Some(RemappedFrameIter::members(
             self,
             prepared_frame,
-            members.iter(),
+            members,
             skip_count,
             had_mappings,
             has_line_info,
@@ -533,6 +956,55 @@ impl<'data> ProguardCache<'data> {
         ))
     }
 
+    /// Narrows `members` down to the ones of the minified method with the given
+    /// signature.
+    ///
+    /// Each inline group belongs to the minified method of its outermost member.
+    /// Its signature is the residual signature if the mapping declares one, and is
+    /// derived from the original types of the outermost method otherwise. If no
+    /// minified method has the signature, all members are returned.
+    fn narrow_by_signature<'m>(
+        &self,
+        members: &'m [raw::Member],
+        signature: Option<&str>,
+    ) -> Cow<'m, [raw::Member]> {
+        let Some(signature) = signature else {
+            return Cow::Borrowed(members);
+        };
+
+        let mut narrowed = Vec::new();
+        let mut start = 0;
+        for (idx, outermost) in members.iter().enumerate() {
+            if !is_outermost_member(members, idx) {
+                continue;
+            }
+            let matches = match self.read_string(outermost.residual_signature_offset) {
+                Ok(residual_signature) => residual_signature == signature,
+                Err(_) => {
+                    let arguments = self
+                        .read_string(outermost.params_offset)
+                        .unwrap_or_default();
+                    let return_type = self
+                        .read_string(outermost.return_type_offset)
+                        .unwrap_or_default();
+                    java::obfuscated_method_descriptor(arguments, return_type, |class| {
+                        self.obfuscate_class(class)
+                    }) == signature
+                }
+            };
+            if matches {
+                narrowed.extend_from_slice(&members[start..=idx]);
+            }
+            start = idx + 1;
+        }
+
+        if narrowed.is_empty() {
+            Cow::Borrowed(members)
+        } else {
+            Cow::Owned(narrowed)
+        }
+    }
+
     /// Finds the range of elements of `members` for which `f(m) == Ordering::Equal`.
     ///
     /// This works by first binary searching for any element fitting the criteria
@@ -586,9 +1058,31 @@ impl<'data> ProguardCache<'data> {
     /// );
     /// ```
     pub fn remap_throwable<'a>(&'a self, throwable: &Throwable<'a>) -> Option<Throwable<'a>> {
-        self.remap_class(throwable.class).map(|class| Throwable {
-            class,
-            message: throwable.message,
+        self.remap_class(&throwable.class).map(|class| Throwable {
+            class: Cow::Borrowed(class),
+            message: throwable.message.clone(),
+        })
+    }
+
+    /// Remaps a [`Throwable`] like [`remap_throwable`](Self::remap_throwable),
+    /// also remapping its message if the `options` ask for it.
+    fn remap_throwable_with_options<'a>(
+        &'a self,
+        throwable: &Throwable<'a>,
+        options: &RemapOptions,
+    ) -> Option<Throwable<'a>> {
+        let remapped = self.remap_throwable(throwable);
+        if !options.remap_messages {
+            return remapped;
+        }
+
+        let message = throwable.message.as_deref().map(|m| self.remap_message(m));
+        let Some(Cow::Owned(message)) = message else {
+            return remapped;
+        };
+        Some(Throwable {
+            class: remapped.map_or_else(|| throwable.class.clone(), |t| t.class),
+            message: Some(Cow::Owned(message)),
         })
     }
 
@@ -679,102 +1173,199 @@ impl<'data> ProguardCache<'data> {
         frame: &StackFrame<'a>,
         carried_outline_pos: &mut Option<usize>,
     ) -> StackFrame<'a> {
+        self.prepare_frame_with_provenance(frame, carried_outline_pos)
+            .0
+    }
+
+    /// Like [`prepare_frame_for_mapping`](Self::prepare_frame_for_mapping), but also returns
+    /// the provenance of the frames remapped from the adjusted frame.
+    fn prepare_frame_with_provenance<'a>(
+        &self,
+        frame: &StackFrame<'a>,
+        carried_outline_pos: &mut Option<usize>,
+    ) -> (StackFrame<'a>, FrameProvenance<'a>) {
         let mut effective = frame.clone();
+        let mut provenance = FrameProvenance::new(frame);
         if let Some(pos) = carried_outline_pos.take() {
             if let Some(mapped) = self.map_outline_position(
-                effective.class,
-                effective.method,
+                &effective.class,
+                &effective.method,
                 effective.line.unwrap_or(0),
                 pos,
-                effective.parameters,
+                effective.parameters(),
             ) {
                 effective.line = Some(mapped);
+                provenance.outline_callsite = true;
             }
         }
 
-        effective
+        (effective, provenance)
     }
 
     /// Remaps a complete Java StackTrace, similar to [`Self::remap_stacktrace_typed`] but instead works on
     /// strings as input and output.
+    ///
+    /// Lines of `adb logcat` output are recognized by their prefix, which is
+    /// kept as it is while the rest of the line is remapped. In the `long`
+    /// format, the lines following a header are attributed to the process and
+    /// thread it names. Lines logged by different processes may be interleaved.
     pub fn remap_stacktrace(&self, input: &str) -> Result<String, std::fmt::Error> {
+        self.remap_stacktrace_with_options(input, &RemapOptions::default())
+    }
+
+    /// Remaps a complete Java StackTrace like [`remap_stacktrace`](Self::remap_stacktrace),
+    /// using the given `options`.
+    pub fn remap_stacktrace_with_options(
+        &self,
+        input: &str,
+        options: &RemapOptions,
+    ) -> Result<String, std::fmt::Error> {
         let mut stacktrace = String::new();
-        let mut carried_outline_pos: Option<usize> = None;
-        let mut current_exception_descriptor: Option<String> = None;
-        let mut next_frame_can_rewrite = false;
+        let mut streams: HashMap<&str, StreamState> = HashMap::new();
+        let mut reader = LogcatReader::default();
 
         for line in input.lines() {
+            let Some(logcat) = reader.read_line(line) else {
+                writeln!(stacktrace, "{line}")?;
+                continue;
+            };
+            let state = streams.entry(logcat.stream).or_default();
+            let line = logcat.message;
+            let mut out = PrefixedWriter::new(&mut stacktrace, logcat.prefix);
+
+            #[cfg(feature = "regex")]
+            if let Some(captures) = options
+                .frame_regex
+                .as_ref()
+                .and_then(|regex| regex.captures(line))
+            {
+                state.current_exception_descriptor = None;
+                state.next_frame_can_rewrite = false;
+                self.format_regex_captures(&mut out, &captures, options)?;
+                continue;
+            }
+
+            if logcat::is_crash_header(line) {
+                state.current_exception_descriptor = None;
+                state.next_frame_can_rewrite = false;
+                writeln!(out, "{line}")?;
+                continue;
+            }
+
+            if let Some((caption, cause)) = split_caption(line) {
+                let remapped_cause = self.remap_throwable_with_options(&cause, options);
+                let descriptor_class = remapped_cause.as_ref().map_or(cause.class(), |t| t.class());
+                state.current_exception_descriptor =
+                    Some(class_name_to_descriptor(descriptor_class));
+                state.next_frame_can_rewrite = true;
+                format_cause(&mut out, line, caption, remapped_cause)?;
+                continue;
+            }
+
             if let Some(throwable) = stacktrace::parse_throwable(line) {
-                let remapped_throwable = self.remap_throwable(&throwable);
+                let remapped_throwable = self.remap_throwable_with_options(&throwable, options);
                 let descriptor_class = remapped_throwable
                     .as_ref()
-                    .map(|t| t.class)
-                    .unwrap_or(throwable.class);
-                current_exception_descriptor = Some(class_name_to_descriptor(descriptor_class));
-                next_frame_can_rewrite = true;
-                format_throwable(&mut stacktrace, line, remapped_throwable)?;
+                    .map_or(throwable.class(), |t| t.class());
+                state.current_exception_descriptor =
+                    Some(class_name_to_descriptor(descriptor_class));
+                state.next_frame_can_rewrite = true;
+                format_throwable(&mut out, line, remapped_throwable)?;
                 continue;
             }
 
             if let Some(frame) = stacktrace::parse_frame(line) {
-                let Some(iter) = self.remap_frame_with_context(
+                let Some(mut iter) = self.remap_frame_with_context(
                     &frame,
-                    current_exception_descriptor.as_deref(),
-                    next_frame_can_rewrite,
-                    &mut carried_outline_pos,
+                    state.current_exception_descriptor.as_deref(),
+                    state.next_frame_can_rewrite,
+                    &mut state.carried_outline_pos,
                 ) else {
-                    // Outline frame, skip (preserve next_frame_can_rewrite for the next real frame)
+                    // Outline frame, skip (preserve state.next_frame_can_rewrite for the next real frame)
                     continue;
                 };
 
-                next_frame_can_rewrite = false;
-                current_exception_descriptor = None;
+                state.next_frame_can_rewrite = false;
+                state.current_exception_descriptor = None;
 
                 let had_mappings = iter.had_mappings();
-                let frames: Vec<_> = iter.collect();
+                let mut frames: Vec<_> =
+                    std::iter::from_fn(|| iter.next_with_outermost()).collect();
 
                 if had_mappings && frames.is_empty() {
                     // Rewrite rules cleared all frames, skip
                     continue;
                 }
+                if options.prefer_non_synthesized {
+                    prune_synthesized(&mut frames);
+                }
 
-                format_frames(&mut stacktrace, line, frames.into_iter())?;
-                continue;
-            }
-
-            if let Some(cause) = line
-                .strip_prefix("Caused by: ")
-                .and_then(stacktrace::parse_throwable)
-            {
-                let remapped_cause = self.remap_throwable(&cause);
-                let descriptor_class = remapped_cause
-                    .as_ref()
-                    .map(|t| t.class)
-                    .unwrap_or(cause.class);
-                current_exception_descriptor = Some(class_name_to_descriptor(descriptor_class));
-                next_frame_can_rewrite = true;
-                format_cause(&mut stacktrace, line, remapped_cause)?;
+                format_frames(&mut out, line, frames.into_iter(), options)?;
                 continue;
             }
 
-            current_exception_descriptor = None;
-            next_frame_can_rewrite = false;
-            writeln!(&mut stacktrace, "{line}")?;
+            state.current_exception_descriptor = None;
+            state.next_frame_can_rewrite = false;
+            writeln!(out, "{line}")?;
         }
         Ok(stacktrace)
     }
 
+    /// Writes a line matched by a [`FrameRegex`](crate::FrameRegex) with its captured parts remapped.
+    #[cfg(feature = "regex")]
+    fn format_regex_captures(
+        &self,
+        out: &mut impl Write,
+        captures: &FrameCaptures<'_>,
+        options: &RemapOptions,
+    ) -> Result<(), std::fmt::Error> {
+        let frames = match captures.frame() {
+            Some(frame) => {
+                let mut iter = self.remap_frame(&frame);
+                let mut frames: Vec<_> =
+                    std::iter::from_fn(|| iter.next_with_outermost()).collect();
+                if options.prefer_non_synthesized {
+                    prune_synthesized(&mut frames);
+                }
+                frames.into_iter().map(|(frame, _)| frame).collect()
+            }
+            None => Vec::new(),
+        };
+        let class = captures.class().and_then(|class| self.remap_class(class));
+        let ty = captures
+            .ty()
+            .and_then(|ty| remap_type(ty, |class| self.remap_class(class)));
+        let field = captures
+            .class()
+            .zip(captures.field())
+            .and_then(|(class, field)| self.remap_field(class, field))
+            .map(|(_, field, _)| field);
+
+        format_captures(out, captures, &frames, class, ty.as_deref(), field)
+    }
+
     /// Remaps a complete Java StackTrace.
+    ///
+    /// The `trace` doesn't need to borrow from the same data as `self`: an
+    /// owned trace created with [`StackTrace::into_owned`] can be remapped, and
+    /// the result can be detached from `self` the same way.
     pub fn remap_stacktrace_typed<'a>(&'a self, trace: &StackTrace<'a>) -> StackTrace<'a> {
+        self.remap_stacktrace_typed_with_options(trace, &RemapOptions::default())
+    }
+
+    /// Remaps a complete Java StackTrace like [`remap_stacktrace_typed`](Self::remap_stacktrace_typed),
+    /// using the given `options`.
+    pub fn remap_stacktrace_typed_with_options<'a>(
+        &'a self,
+        trace: &StackTrace<'a>,
+        options: &RemapOptions,
+    ) -> StackTrace<'a> {
         let exception = trace
             .exception
             .as_ref()
-            .and_then(|t| self.remap_throwable(t));
+            .and_then(|t| self.remap_throwable_with_options(t, options));
         let exception_descriptor = trace.exception.as_ref().map(|original| {
-            let class = exception
-                .as_ref()
-                .map(|t| t.class)
-                .unwrap_or(original.class);
+            let class = exception.as_ref().map_or(original.class(), |t| t.class());
             class_name_to_descriptor(class)
         });
 
@@ -782,7 +1373,7 @@ impl<'data> ProguardCache<'data> {
         let mut frames = Vec::with_capacity(trace.frames.len());
         let mut next_frame_can_rewrite = exception_descriptor.is_some();
         for f in trace.frames.iter() {
-            let Some(iter) = self.remap_frame_with_context(
+            let Some(mut iter) = self.remap_frame_with_context(
                 f,
                 exception_descriptor.as_deref(),
                 next_frame_can_rewrite,
@@ -794,32 +1385,52 @@ impl<'data> ProguardCache<'data> {
             next_frame_can_rewrite = false;
 
             let had_mappings = iter.had_mappings();
-            let mut remapped: Vec<_> = iter.collect();
+            let mut remapped: Vec<_> = std::iter::from_fn(|| iter.next_with_outermost()).collect();
 
             if had_mappings && remapped.is_empty() {
                 // Rewrite rules cleared all frames, skip
                 continue;
             }
+            if options.prefer_non_synthesized {
+                prune_synthesized(&mut remapped);
+            }
 
             if remapped.is_empty() {
                 frames.push(f.clone());
             } else {
-                frames.append(&mut remapped);
+                frames.extend(remapped.into_iter().map(|(frame, _)| frame));
             }
         }
 
         let cause = trace
             .cause
             .as_ref()
-            .map(|c| Box::new(self.remap_stacktrace_typed(c)));
+            .map(|c| Box::new(self.remap_stacktrace_typed_with_options(c, options)));
+        let suppressed = trace
+            .suppressed
+            .iter()
+            .map(|s| self.remap_stacktrace_typed_with_options(s, options))
+            .collect();
 
         StackTrace {
             exception,
             frames,
             cause,
+            suppressed,
+            elided_frames: trace.elided_frames,
         }
     }
 
+    /// Remaps the frames of all threads in a [`ThreadDump`], as well as the
+    /// classes of the monitors they hold or wait for.
+    pub fn remap_thread_dump<'a>(&'a self, dump: &ThreadDump<'a>) -> ThreadDump<'a> {
+        thread_dump::remap_thread_dump(
+            dump,
+            |trace| self.remap_stacktrace_typed(trace),
+            |class| self.remap_class(class),
+        )
+    }
+
     /// returns a tuple where the first element is the list of the function
     /// parameters and the second one is the return type
     pub fn deobfuscate_signature(&self, signature: &str) -> Option<DeobfuscatedSignature> {
@@ -835,12 +1446,14 @@ pub struct RemappedFrameIter<'r, 'data> {
     inner: Option<(
         &'r ProguardCache<'data>,
         StackFrame<'data>,
-        std::slice::Iter<'data, raw::Member>,
+        Cow<'data, [raw::Member]>,
+        usize,
     )>,
     /// A single remapped frame fallback (e.g. class-only remapping).
     fallback: Option<StackFrame<'data>>,
-    /// Buffered frames for multi-frame expansion (e.g. no-line groups).
-    pending_frames: Vec<StackFrame<'data>>,
+    /// Buffered frames for multi-frame expansion (e.g. no-line groups), together
+    /// with whether they are the outermost frame of their inline chain.
+    pending_frames: Vec<(StackFrame<'data>, bool)>,
     /// Number of frames to skip from rewrite rules.
     skip_count: usize,
     /// Whether there were mapping entries (for should_skip determination).
@@ -870,14 +1483,14 @@ impl<'r, 'data> RemappedFrameIter<'r, 'data> {
     fn members(
         cache: &'r ProguardCache<'data>,
         frame: StackFrame<'data>,
-        members: std::slice::Iter<'data, raw::Member>,
+        members: Cow<'data, [raw::Member]>,
         skip_count: usize,
         had_mappings: bool,
         has_line_info: bool,
         outer_source_file: Option<&'data str>,
     ) -> Self {
         Self {
-            inner: Some((cache, frame, members)),
+            inner: Some((cache, frame, members, 0)),
             fallback: None,
             pending_frames: Vec::new(),
             skip_count,
@@ -909,17 +1522,28 @@ impl<'r, 'data> RemappedFrameIter<'r, 'data> {
         self.had_mappings
     }
 
-    fn next_inner(&mut self) -> Option<StackFrame<'data>> {
+    /// Returns the next frame and whether it is the outermost frame of its inline chain.
+    fn next_with_outermost(&mut self) -> Option<(StackFrame<'data>, bool)> {
+        // Lazily skip rewrite-removed frames
+        while self.skip_count > 0 {
+            self.skip_count -= 1;
+            self.next_inner()?;
+        }
+        self.next_inner()
+    }
+
+    fn next_inner(&mut self) -> Option<(StackFrame<'data>, bool)> {
         // Drain any buffered frames from multi-frame expansion first.
         if !self.pending_frames.is_empty() {
             return self.pending_frames.pop();
         }
 
         if let Some(frame) = self.fallback.take() {
-            return Some(frame);
+            return Some((frame, true));
         }
 
-        let (cache, mut frame, mut members) = self.inner.take()?;
+        let (cache, mut frame, members, position) = self.inner.take()?;
+        let mut remaining = members[position..].iter();
 
         let out = if frame.parameters.is_none() {
             // If we have no line number, treat it as unknown. If there are base (no-line) mappings
@@ -928,7 +1552,7 @@ impl<'r, 'data> RemappedFrameIter<'r, 'data> {
                 let mut frames = resolve_no_line_frames(
                     cache,
                     &frame,
-                    members.as_slice(),
+                    remaining.as_slice(),
                     self.outer_source_file,
                 );
                 frames.reverse();
@@ -943,27 +1567,30 @@ impl<'r, 'data> RemappedFrameIter<'r, 'data> {
             let mapped = iterate_with_lines(
                 cache,
                 &mut frame,
-                &mut members,
+                &mut remaining,
                 self.outer_source_file,
                 self.has_line_info,
                 &mut self.pending_frames,
             );
             if mapped.is_some() {
                 self.matched_any = true;
-                self.inner = Some((cache, frame, members));
+                let position = members.len() - remaining.len();
+                self.inner = Some((cache, frame, members, position));
                 mapped
             } else if !self.matched_any && self.has_line_info {
                 // Outside-range fallback: no member matched the frame line.
                 // Remap only the class name, keeping the obfuscated method name
                 // and the original line.
-                Some(cache.remap_class_only(&frame, self.outer_source_file))
+                Some((cache.remap_class_only(&frame, self.outer_source_file), true))
             } else {
                 None
             }
         } else {
             let mapped =
-                iterate_without_lines(cache, &mut frame, &mut members, self.outer_source_file);
-            self.inner = Some((cache, frame, members));
+                iterate_without_lines(cache, &mut frame, &mut remaining, self.outer_source_file)
+                    .map(|frame| (frame, true));
+            let position = members.len() - remaining.len();
+            self.inner = Some((cache, frame, members, position));
             mapped
         };
 
@@ -975,12 +1602,7 @@ impl<'data> Iterator for RemappedFrameIter<'_, 'data> {
     type Item = StackFrame<'data>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // Lazily skip rewrite-removed frames
-        while self.skip_count > 0 {
-            self.skip_count -= 1;
-            self.next_inner()?;
-        }
-        self.next_inner()
+        self.next_with_outermost().map(|(frame, _)| frame)
     }
 }
 
@@ -990,10 +1612,10 @@ fn iterate_with_lines<'a>(
     members: &mut std::slice::Iter<'_, raw::Member>,
     outer_source_file: Option<&str>,
     has_line_info: bool,
-    pending_frames: &mut Vec<StackFrame<'a>>,
-) -> Option<StackFrame<'a>> {
+    pending_frames: &mut Vec<(StackFrame<'a>, bool)>,
+) -> Option<(StackFrame<'a>, bool)> {
     let frame_line = frame.line.unwrap_or(0);
-    for member in members {
+    while let Some(member) = members.next() {
         // If this method has line mappings, skip base (no-line) entries when we have a concrete line.
         if has_line_info && frame_line > 0 && member.endline().unwrap_or(0) == 0 {
             continue;
@@ -1008,7 +1630,8 @@ fn iterate_with_lines<'a>(
                     member,
                     outer_source_file,
                     frame.line,
-                );
+                )
+                .map(|frame| (frame, true));
             }
             // Span expansion: if the original range spans multiple lines,
             // emit one frame per original line.
@@ -1029,9 +1652,9 @@ fn iterate_with_lines<'a>(
                         Some(line),
                     ) {
                         if first_frame.is_none() {
-                            first_frame = Some(f);
+                            first_frame = Some((f, true));
                         } else {
-                            pending_frames.push(f);
+                            pending_frames.push((f, true));
                         }
                     }
                 }
@@ -1044,7 +1667,8 @@ fn iterate_with_lines<'a>(
             } else {
                 None
             };
-            return map_member_without_lines(cache, frame, member, outer_source_file, output_line);
+            return map_member_without_lines(cache, frame, member, outer_source_file, output_line)
+                .map(|frame| (frame, true));
         }
         // skip any members which do not match our frames line
         if member.endline().unwrap_or(0) > 0
@@ -1066,35 +1690,59 @@ fn iterate_with_lines<'a>(
 
         let class = cache
             .read_string(member.original_class_offset)
-            .unwrap_or(frame.class);
+            .map(Cow::Borrowed)
+            .unwrap_or_else(|_| frame.class.clone());
 
-        let file: Option<Cow<'_, str>> = if member.original_file_offset != u32::MAX {
-            let Ok(file_name) = cache.read_string(member.original_file_offset) else {
-                continue;
-            };
+        let (file, file_source): (Option<Cow<'_, str>>, _) =
+            if member.original_file_offset != u32::MAX {
+                let Ok(file_name) = cache.read_string(member.original_file_offset) else {
+                    continue;
+                };
 
-            if file_name == "R8$$SyntheticClass" {
-                extract_class_name(class).map(Cow::Borrowed)
+                if file_name == "R8$$SyntheticClass" {
+                    (
+                        extract_class_name(&class).map(|name| Cow::Owned(name.to_owned())),
+                        FileSource::Synthesized,
+                    )
+                } else {
+                    (Some(Cow::Borrowed(file_name)), FileSource::Mapping)
+                }
             } else {
-                Some(Cow::Borrowed(file_name))
-            }
-        } else {
-            // Synthesize from class name (input filename is not reliable)
-            synthesize_source_file(class, outer_source_file).map(Cow::Owned)
-        };
+                // Synthesize from class name (input filename is not reliable)
+                (
+                    synthesize_source_file(&class, outer_source_file).map(Cow::Owned),
+                    FileSource::Synthesized,
+                )
+            };
 
         let Ok(method) = cache.read_string(member.original_name_offset) else {
             continue;
         };
 
-        return Some(StackFrame {
+        // Inlined methods share the minified range of the method they were
+        // inlined into, which always comes last.
+        let inline_depth = members
+            .as_slice()
+            .iter()
+            .take_while(|next| next.startline == member.startline && next.endline == member.endline)
+            .count();
+        let provenance =
+            remapped_provenance(frame, &file, file_source).map(|provenance| FrameProvenance {
+                inline_depth,
+                ..provenance
+            });
+
+        let frame = StackFrame {
             class,
-            method,
+            method: Cow::Borrowed(method),
             file,
             line: Some(line),
-            parameters: frame.parameters,
+            parameters: frame.parameters.clone(),
+            signature: None,
             method_synthesized: member.is_synthesized(),
-        });
+            provenance,
+        };
+        return Some((frame, inline_depth == 0));
     }
     None
 }
@@ -1108,16 +1756,19 @@ fn map_member_without_lines<'a>(
 ) -> Option<StackFrame<'a>> {
     let class = cache
         .read_string(member.original_class_offset)
-        .unwrap_or(frame.class);
+        .map(Cow::Borrowed)
+        .unwrap_or_else(|_| frame.class.clone());
     let method = cache.read_string(member.original_name_offset).ok()?;
-    let file = synthesize_source_file(class, outer_source_file).map(Cow::Owned);
+    let file = synthesize_source_file(&class, outer_source_file).map(Cow::Owned);
 
     Some(StackFrame {
         class,
-        method,
+        method: Cow::Borrowed(method),
+        provenance: remapped_provenance(frame, &file, FileSource::Synthesized),
         file,
         line: output_line,
-        parameters: frame.parameters,
+        parameters: frame.parameters.clone(),
+        signature: None,
         method_synthesized: member.is_synthesized(),
     })
 }
@@ -1153,14 +1804,18 @@ fn resolve_no_line_frames<'a>(
     frame: &StackFrame<'a>,
     members: &[raw::Member],
     outer_source_file: Option<&str>,
-) -> Vec<StackFrame<'a>> {
+) -> Vec<(StackFrame<'a>, bool)> {
     let base_entries: Vec<&raw::Member> = members
         .iter()
         .filter(|m| m.endline().unwrap_or(0) == 0)
         .collect();
 
     if !base_entries.is_empty() {
-        return resolve_base_entries(cache, frame, &base_entries, outer_source_file);
+        // Base entries are never inlined into anything.
+        return resolve_base_entries(cache, frame, &base_entries, outer_source_file)
+            .into_iter()
+            .map(|frame| (frame, true))
+            .collect();
     }
 
     // No base entries — check if the first range group forms an inline group
@@ -1184,12 +1839,16 @@ fn resolve_no_line_frames<'a>(
         if first_group.len() > 1 {
             // Inline group: multiple entries share the same range.
             // Resolve each with its proper original line.
-            for member in &first_group {
+            for (idx, member) in first_group.iter().enumerate() {
                 let line = compute_member_output_line(member).or(Some(0));
-                if let Some(f) =
+                let inline_depth = first_group.len() - 1 - idx;
+                if let Some(mut f) =
                     map_member_without_lines(cache, frame, member, outer_source_file, line)
                 {
-                    frames.push(f);
+                    if let Some(provenance) = &mut f.provenance {
+                        provenance.inline_depth = inline_depth;
+                    }
+                    frames.push((f, inline_depth == 0));
                 }
             }
         } else {
@@ -1203,14 +1862,14 @@ fn resolve_no_line_frames<'a>(
                 if let Some(f) =
                     map_member_without_lines(cache, frame, first, outer_source_file, Some(0))
                 {
-                    frames.push(f);
+                    frames.push((f, true));
                 }
             } else {
                 for member in members {
                     if let Some(f) =
                         map_member_without_lines(cache, frame, member, outer_source_file, Some(0))
                     {
-                        frames.push(f);
+                        frames.push((f, true));
                     }
                 }
             }
@@ -1305,12 +1964,26 @@ fn resolve_base_entries<'a>(
     // Sort no-range frames by original method name when all have line mappings;
     // bare method entries preserve original mapping file order.
     if !all_no_range_same_name && all_no_range_have_line_mapping {
-        frames.sort_by(|a, b| a.method.cmp(b.method));
+        frames.sort_by(|a, b| a.method.cmp(&b.method));
     }
 
     frames
 }
 
+/// Whether the member at `idx` is the outermost frame of its inline group.
+///
+/// Inlined methods share the obfuscated name and minified range of the method
+/// they were inlined into, which always comes last.
+fn is_outermost_member(members: &[raw::Member], idx: usize) -> bool {
+    let member = &members[idx];
+    member.endline().unwrap_or(0) == 0
+        || members.get(idx + 1).is_none_or(|next| {
+            next.obfuscated_name_offset != member.obfuscated_name_offset
+                || next.startline != member.startline
+                || next.endline != member.endline
+        })
+}
+
 /// Computes the number of frames to skip based on rewrite rules.
 /// Returns the total skip count from all matching RemoveInnerFrames actions.
 fn compute_skip_count(rewrite_rules: &[RewriteRule<'_>], thrown_descriptor: Option<&str>) -> usize {
@@ -1358,42 +2031,52 @@ com.example.MainFragment$onActivityCreated$4 -> com.example.MainFragment$g:
     ";
         let stacktrace = StackTrace {
             exception: Some(Throwable {
-                class: "com.example.MainFragment$e",
-                message: Some("Crash!"),
+                class: "com.example.MainFragment$e".into(),
+                message: Some("Crash!".into()),
             }),
             frames: vec![
                 StackFrame {
-                    class: "com.example.MainFragment$g",
-                    method: "onClick",
+                    class: "com.example.MainFragment$g".into(),
+                    method: "onClick".into(),
                     line: Some(2),
                     file: Some(Cow::Borrowed("SourceFile")),
                     parameters: None,
+                    signature: None,
                     method_synthesized: false,
+                    provenance: None,
                 },
                 StackFrame {
-                    class: "android.view.View",
-                    method: "performClick",
+                    class: "android.view.View".into(),
+                    method: "performClick".into(),
                     line: Some(7393),
                     file: Some(Cow::Borrowed("View.java")),
                     parameters: None,
+                    signature: None,
                     method_synthesized: false,
+                    provenance: None,
                 },
             ],
             cause: Some(Box::new(StackTrace {
                 exception: Some(Throwable {
-                    class: "com.example.MainFragment$d",
-                    message: Some("Engines overheating"),
+                    class: "com.example.MainFragment$d".into(),
+                    message: Some("Engines overheating".into()),
                 }),
                 frames: vec![StackFrame {
-                    class: "com.example.MainFragment$g",
-                    method: "onClick",
+                    class: "com.example.MainFragment$g".into(),
+                    method: "onClick".into(),
                     line: Some(1),
                     file: Some(Cow::Borrowed("SourceFile")),
                     parameters: None,
+                    signature: None,
                     method_synthesized: false,
+                    provenance: None,
                 }],
                 cause: None,
+                suppressed: vec![],
+                elided_frames: 0,
             })),
+            suppressed: vec![],
+            elided_frames: 0,
         };
         let expect = "\
 com.example.MainFragment$RocketException: Crash!
@@ -1572,28 +2255,34 @@ some.Other -> b:
 
         let trace = StackTrace {
             exception: Some(Throwable {
-                class: "java.lang.NullPointerException",
-                message: Some("Boom"),
+                class: "java.lang.NullPointerException".into(),
+                message: Some("Boom".into()),
             }),
             frames: vec![
                 StackFrame {
-                    class: "a",
-                    method: "call",
+                    class: "a".into(),
+                    method: "call".into(),
                     line: Some(4),
                     file: Some(Cow::Borrowed("SourceFile")),
                     parameters: None,
+                    signature: None,
                     method_synthesized: false,
+                    provenance: None,
                 },
                 StackFrame {
-                    class: "b",
-                    method: "run",
+                    class: "b".into(),
+                    method: "run".into(),
                     line: Some(5),
                     file: Some(Cow::Borrowed("SourceFile")),
                     parameters: None,
+                    signature: None,
                     method_synthesized: false,
+                    provenance: None,
                 },
             ],
             cause: None,
+            suppressed: vec![],
+            elided_frames: 0,
         };
 
         let remapped = cache.remap_stacktrace_typed(&trace);
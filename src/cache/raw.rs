@@ -1,10 +1,10 @@
-use std::collections::BTreeMap;
-use std::io::Write;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, Read, Seek, SeekFrom, Write};
 
 use watto::{Pod, StringTable};
 
-use crate::builder::{self, ParsedProguardMapping};
-use crate::ProguardMapping;
+use crate::builder::{self, MergeError, ParsedProguardMapping};
+use crate::{MappingVersion, ProguardMapping, ProguardRecord};
 
 use super::{CacheError, CacheErrorKind};
 
@@ -19,7 +19,7 @@ pub(crate) const PRGCACHE_MAGIC: u32 = u32::from_le_bytes(PRGCACHE_MAGIC_BYTES);
 pub(crate) const PRGCACHE_MAGIC_FLIPPED: u32 = PRGCACHE_MAGIC.swap_bytes();
 
 /// The current version of the ProguardCache format.
-pub const PRGCACHE_VERSION: u32 = 4;
+pub const PRGCACHE_VERSION: u32 = 8;
 
 /// The header of a proguard cache file.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -41,10 +41,29 @@ pub(crate) struct Header {
     pub(crate) num_rewrite_rule_entries: u32,
     /// The total number of rewrite rule components across all members.
     pub(crate) num_rewrite_rule_components: u32,
+    /// The total number of field entries in this cache.
+    pub(crate) num_fields: u32,
     /// The number of string bytes in this cache.
     pub(crate) string_bytes: u32,
 }
 
+impl Default for Header {
+    fn default() -> Self {
+        Self {
+            magic: PRGCACHE_MAGIC,
+            version: PRGCACHE_VERSION,
+            num_classes: 0,
+            num_members: 0,
+            num_members_by_params: 0,
+            num_outline_pairs: 0,
+            num_rewrite_rule_entries: 0,
+            num_rewrite_rule_components: 0,
+            num_fields: 0,
+            string_bytes: 0,
+        }
+    }
+}
+
 /// An entry for a class in a proguard cache file.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[repr(C)]
@@ -63,6 +82,10 @@ pub(crate) struct Class {
     pub(crate) members_by_params_offset: u32,
     /// The number of member-by-params entries for this class.
     pub(crate) members_by_params_len: u32,
+    /// The start of the class's field entries (offset into the field section).
+    pub(crate) fields_offset: u32,
+    /// The number of field entries for this class.
+    pub(crate) fields_len: u32,
     /// Whether this class was synthesized by the compiler.
     ///
     /// `0` means `false`, all other values mean `true`.
@@ -92,6 +115,8 @@ impl Default for Class {
             members_len: 0,
             members_by_params_offset: u32::MAX,
             members_by_params_len: 0,
+            fields_offset: u32::MAX,
+            fields_len: 0,
             is_synthesized: 0,
             _reserved: [0; 3],
         }
@@ -131,6 +156,11 @@ pub(crate) struct Member {
     pub(crate) rewrite_rules_offset: u32,
     /// Number of rewrite rule entries for this member.
     pub(crate) rewrite_rules_len: u32,
+    /// The residual signature of the minified method (offset into the string section),
+    /// `u32::MAX` if absent.
+    pub(crate) residual_signature_offset: u32,
+    /// The return type of the method (offset into the string section).
+    pub(crate) return_type_offset: u32,
     /// Whether this member was synthesized by the compiler.
     ///
     /// `0` means `false`, all other values mean `true`.
@@ -179,9 +209,22 @@ impl Member {
     }
 }
 
+/// An entry corresponding to a field line in a proguard cache file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub(crate) struct Field {
+    /// The obfuscated field name (offset into the string section).
+    pub(crate) obfuscated_name_offset: u32,
+    /// The original field name (offset into the string section).
+    pub(crate) original_name_offset: u32,
+    /// The field type (offset into the string section).
+    pub(crate) type_offset: u32,
+}
+
 unsafe impl Pod for Header {}
 unsafe impl Pod for Class {}
 unsafe impl Pod for Member {}
+unsafe impl Pod for Field {}
 
 /// A single outline mapping pair: outline position -> callsite line.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -244,6 +287,16 @@ pub struct ProguardCache<'data> {
     pub(crate) rewrite_rule_entries: &'data [RewriteRuleEntry],
     /// A flat list of rewrite rule components.
     pub(crate) rewrite_rule_components: &'data [RewriteComponent],
+    /// A list of field entries.
+    ///
+    /// Field entries are sorted by class, then
+    /// obfuscated field name, and finally by the
+    /// order in which they occurred in the original proguard file.
+    pub(crate) fields: &'data [Field],
+    /// A list of indices into `classes`.
+    ///
+    /// The indices are sorted by the original names of the classes they point to.
+    pub(crate) classes_by_original: &'data [u32],
     /// The collection of all strings in the cache file.
     pub(crate) string_bytes: &'data [u8],
 }
@@ -255,6 +308,7 @@ impl std::fmt::Debug for ProguardCache<'_> {
             .field("classes", &self.header.num_classes)
             .field("members", &self.header.num_members)
             .field("members_by_params", &self.header.num_members_by_params)
+            .field("fields", &self.header.num_fields)
             .field("string_bytes", &self.header.string_bytes)
             .finish()
     }
@@ -302,6 +356,14 @@ impl<'data> ProguardCache<'data> {
             RewriteComponent::slice_from_prefix(rest, header.num_rewrite_rule_components as usize)
                 .ok_or(CacheErrorKind::InvalidMembers)?;
 
+        let (_, rest) = watto::align_to(rest, 8).ok_or(CacheErrorKind::InvalidFields)?;
+        let (fields, rest) = Field::slice_from_prefix(rest, header.num_fields as usize)
+            .ok_or(CacheErrorKind::InvalidFields)?;
+
+        let (_, rest) = watto::align_to(rest, 8).ok_or(CacheErrorKind::InvalidClasses)?;
+        let (classes_by_original, rest) = u32::slice_from_prefix(rest, header.num_classes as usize)
+            .ok_or(CacheErrorKind::InvalidClasses)?;
+
         let (_, string_bytes) =
             watto::align_to(rest, 8).ok_or(CacheErrorKind::UnexpectedStringBytes {
                 expected: header.string_bytes as usize,
@@ -324,28 +386,116 @@ impl<'data> ProguardCache<'data> {
             outline_pairs,
             rewrite_rule_entries,
             rewrite_rule_components,
+            fields,
+            classes_by_original,
             string_bytes,
         })
     }
 
     /// Writes a [`ProguardMapping`] into a writer in the proguard cache format.
     pub fn write<W: Write>(mapping: &ProguardMapping, writer: &mut W) -> std::io::Result<()> {
-        let mut string_table = StringTable::new();
+        Self::write_parsed(ParsedProguardMapping::parse(*mapping, true), writer)
+    }
+
+    /// Writes several [`ProguardMapping`]s into a writer as a single proguard cache.
+    ///
+    /// The mappings are merged as described in [`ProguardMapper::new_merged`](crate::ProguardMapper::new_merged).
+    pub fn write_merged<'m, W: Write>(
+        mappings: &[ProguardMapping<'m>],
+        writer: &mut W,
+    ) -> Result<(), MergeError<'m>> {
+        let parsed = ParsedProguardMapping::parse_merged(mappings.iter().copied(), true)?;
+        Ok(Self::write_parsed(parsed, writer)?)
+    }
+
+    /// Writes a proguard mapping read from `reader` into a writer in the proguard cache format.
+    ///
+    /// Unlike [`write`](Self::write), this holds neither the mapping nor the cache in memory.
+    /// The text of each class is copied to `scratch` while the mapping is read. It is then read
+    /// back one class at a time to fill in the sections of the cache, which is why `writer` needs
+    /// to be seekable. Memory use grows with the number of classes, whose names and source files
+    /// are kept, and with the size of the largest class, but not with the number of members.
+    ///
+    /// The resulting cache remaps exactly like one created with [`write`](Self::write), but it
+    /// is not necessarily byte-for-byte identical. If a class appears more than once in the
+    /// mapping, its members are combined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    ///
+    /// use proguard::ProguardCache;
+    ///
+    /// let mapping = b"com.example.Foo -> a:\n    1:1:void bar():10:10 -> a\n";
+    ///
+    /// let mut buf = Cursor::new(Vec::new());
+    /// let mut scratch = Cursor::new(Vec::new());
+    /// ProguardCache::write_from_reader(&mapping[..], &mut buf, &mut scratch).unwrap();
+    ///
+    /// let cache = ProguardCache::parse(buf.get_ref()).unwrap();
+    /// assert_eq!(cache.remap_class("a"), Some("com.example.Foo"));
+    /// ```
+    pub fn write_from_reader<R, W, S>(
+        mut reader: R,
+        writer: &mut W,
+        scratch: &mut S,
+    ) -> std::io::Result<()>
+    where
+        R: BufRead,
+        W: Write + Seek,
+        S: Read + Write + Seek,
+    {
+        let mut streamed = StreamedCache::new(scratch)?;
+
+        // The lines of the current class, starting with its class record.
+        let mut block = Vec::new();
+        loop {
+            let line_start = block.len();
+            let eof = reader.read_until(b'\n', &mut block)? == 0;
+            if eof || (line_start > 0 && is_class_line(&block[line_start..])) {
+                streamed.push_block(&block[..line_start])?;
+                block.drain(..line_start);
+            }
+            if eof {
+                break;
+            }
+        }
 
-        let parsed = ParsedProguardMapping::parse(*mapping, true);
+        streamed.finish(writer)
+    }
 
+    fn write_parsed<W: Write>(
+        parsed: ParsedProguardMapping<'_>,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        let mut string_table = StringSection::default();
+        let mut cache = CacheInProgress::default();
+
+        for class in Self::collect_classes(&parsed, &mut string_table).into_values() {
+            cache.push_class(class);
+        }
+
+        cache.write(string_table.table.into_bytes(), writer)
+    }
+
+    /// Collects the classes of a parsed mapping, keyed and sorted by obfuscated name.
+    fn collect_classes<'s>(
+        parsed: &ParsedProguardMapping<'s>,
+        string_table: &mut StringSection,
+    ) -> BTreeMap<&'s str, ClassInProgress<'s>> {
         // Initialize class mappings with obfuscated -> original name data. The mappings will be filled in afterwards.
         let mut classes: BTreeMap<&str, ClassInProgress> = parsed
             .class_names
             .iter()
             .map(|(obfuscated, original)| {
-                let obfuscated_name_offset = string_table.insert(obfuscated.as_str()) as u32;
-                let original_name_offset = string_table.insert(original.as_str()) as u32;
+                let obfuscated_name_offset = string_table.insert(obfuscated.as_str());
+                let original_name_offset = string_table.insert(original.as_str());
                 let class_info = parsed.class_infos.get(original);
                 let is_synthesized = class_info.map(|ci| ci.is_synthesized).unwrap_or_default();
                 let file_name_offset = class_info
                     .and_then(|ci| ci.source_file)
-                    .map_or(u32::MAX, |s| string_table.insert(s) as u32);
+                    .map_or(u32::MAX, |s| string_table.insert(s));
                 let class = ClassInProgress {
                     class: Class {
                         original_name_offset,
@@ -364,7 +514,7 @@ impl<'data> ProguardCache<'data> {
         for ((obfuscated_class, obfuscated_method), members) in &parsed.members {
             let current_class = classes.entry(obfuscated_class.as_str()).or_default();
 
-            let obfuscated_method_offset = string_table.insert(obfuscated_method.as_str()) as u32;
+            let obfuscated_method_offset = string_table.insert(obfuscated_method.as_str());
 
             let method_mappings = current_class
                 .members
@@ -373,12 +523,11 @@ impl<'data> ProguardCache<'data> {
 
             for member in members.all.iter() {
                 method_mappings.push(Self::resolve_mapping(
-                    &mut string_table,
-                    &parsed,
+                    string_table,
+                    parsed,
                     obfuscated_method_offset,
                     member,
                 ));
-                current_class.class.members_len += 1;
             }
 
             for (args, param_members) in members.by_params.iter() {
@@ -389,168 +538,39 @@ impl<'data> ProguardCache<'data> {
 
                 for member in param_members.iter() {
                     param_mappings.push(Self::resolve_mapping(
-                        &mut string_table,
-                        &parsed,
+                        string_table,
+                        parsed,
                         obfuscated_method_offset,
                         member,
                     ));
-                    current_class.class.members_by_params_len += 1;
                 }
             }
         }
 
-        // At this point, we know how many members/members-by-params each class has because we kept count,
-        // but we don't know where each class's entries start. We'll rectify that below.
-
-        let string_bytes = string_table.into_bytes();
-
-        let num_members = classes.values().map(|c| c.class.members_len).sum::<u32>();
-        let num_members_by_params = classes
-            .values()
-            .map(|c| c.class.members_by_params_len)
-            .sum::<u32>();
-
-        // Build output vectors first to know outline pair count.
-        let mut out_classes: Vec<Class> = Vec::with_capacity(classes.len());
-        let mut members: Vec<Member> = Vec::with_capacity(num_members as usize);
-        let mut members_by_params: Vec<Member> = Vec::with_capacity(num_members_by_params as usize);
-        let mut outline_pairs: Vec<OutlinePair> = Vec::new();
-        let mut rewrite_rule_entries: Vec<RewriteRuleEntry> = Vec::new();
-        let mut rewrite_rule_components: Vec<RewriteComponent> = Vec::new();
-
-        for mut c in classes.into_values() {
-            // Set offsets relative to current vector sizes
-            c.class.members_offset = members.len() as u32;
-            c.class.members_by_params_offset = members_by_params.len() as u32;
-
-            // Serialize members without params
-            for (_method, ms) in c.members {
-                for mut mp in ms {
-                    let start = outline_pairs.len() as u32;
-                    if !mp.outline_pairs.is_empty() {
-                        mp.member.outline_pairs_offset = start;
-                        mp.member.outline_pairs_len = mp.outline_pairs.len() as u32;
-                        outline_pairs.extend(mp.outline_pairs);
-                    } else {
-                        mp.member.outline_pairs_offset = start;
-                        mp.member.outline_pairs_len = 0;
-                    }
-
-                    let rule_start = rewrite_rule_entries.len() as u32;
-                    let mut rule_count = 0;
-                    for rule in mp.rewrite_rules {
-                        let cond_start = rewrite_rule_components.len() as u32;
-                        rewrite_rule_components.extend(rule.conditions);
-                        let cond_len = rewrite_rule_components.len() as u32 - cond_start;
-                        let action_start = rewrite_rule_components.len() as u32;
-                        rewrite_rule_components.extend(rule.actions);
-                        let action_len = rewrite_rule_components.len() as u32 - action_start;
-                        rewrite_rule_entries.push(RewriteRuleEntry {
-                            conditions_offset: cond_start,
-                            conditions_len: cond_len,
-                            actions_offset: action_start,
-                            actions_len: action_len,
-                        });
-                        rule_count += 1;
-                    }
-                    mp.member.rewrite_rules_offset = rule_start;
-                    mp.member.rewrite_rules_len = rule_count;
-
-                    members.push(mp.member);
-                }
-            }
+        for ((obfuscated_class, obfuscated_field), fields) in &parsed.fields {
+            let current_class = classes.entry(obfuscated_class.as_str()).or_default();
 
-            // Serialize members by params
-            for (_key, ms) in c.members_by_params {
-                for mut mp in ms {
-                    let start = outline_pairs.len() as u32;
-                    if !mp.outline_pairs.is_empty() {
-                        mp.member.outline_pairs_offset = start;
-                        mp.member.outline_pairs_len = mp.outline_pairs.len() as u32;
-                        outline_pairs.extend(mp.outline_pairs);
-                    } else {
-                        mp.member.outline_pairs_offset = start;
-                        mp.member.outline_pairs_len = 0;
-                    }
+            let obfuscated_name_offset = string_table.insert(obfuscated_field.as_str());
 
-                    let rule_start = rewrite_rule_entries.len() as u32;
-                    let mut rule_count = 0;
-                    for rule in mp.rewrite_rules {
-                        let cond_start = rewrite_rule_components.len() as u32;
-                        rewrite_rule_components.extend(rule.conditions);
-                        let cond_len = rewrite_rule_components.len() as u32 - cond_start;
-                        let action_start = rewrite_rule_components.len() as u32;
-                        rewrite_rule_components.extend(rule.actions);
-                        let action_len = rewrite_rule_components.len() as u32 - action_start;
-                        rewrite_rule_entries.push(RewriteRuleEntry {
-                            conditions_offset: cond_start,
-                            conditions_len: cond_len,
-                            actions_offset: action_start,
-                            actions_len: action_len,
-                        });
-                        rule_count += 1;
-                    }
-                    mp.member.rewrite_rules_offset = rule_start;
-                    mp.member.rewrite_rules_len = rule_count;
+            let field_mappings = current_class
+                .fields
+                .entry(obfuscated_field.as_str())
+                .or_default();
 
-                    members_by_params.push(mp.member);
-                }
+            for field in fields {
+                field_mappings.push(Field {
+                    obfuscated_name_offset,
+                    original_name_offset: string_table.insert(field.original.as_str()),
+                    type_offset: string_table.insert(field.ty),
+                });
             }
-
-            out_classes.push(c.class);
         }
 
-        let num_outline_pairs = outline_pairs.len() as u32;
-        let num_rewrite_rule_entries = rewrite_rule_entries.len() as u32;
-        let num_rewrite_rule_components = rewrite_rule_components.len() as u32;
-
-        let header = Header {
-            magic: PRGCACHE_MAGIC,
-            version: PRGCACHE_VERSION,
-            num_classes: out_classes.len() as u32,
-            num_members,
-            num_members_by_params,
-            num_outline_pairs,
-            num_rewrite_rule_entries,
-            num_rewrite_rule_components,
-            string_bytes: string_bytes.len() as u32,
-        };
-
-        let mut writer = watto::Writer::new(writer);
-        writer.write_all(header.as_bytes())?;
-        writer.align_to(8)?;
-
-        // Write classes
-        for c in out_classes.iter() {
-            writer.write_all(c.as_bytes())?;
-        }
-        writer.align_to(8)?;
-
-        // Write member sections
-        writer.write_all(members.as_bytes())?;
-        writer.align_to(8)?;
-
-        writer.write_all(members_by_params.as_bytes())?;
-        writer.align_to(8)?;
-
-        // Write outline pairs
-        writer.write_all(outline_pairs.as_bytes())?;
-        writer.align_to(8)?;
-
-        writer.write_all(rewrite_rule_entries.as_bytes())?;
-        writer.align_to(8)?;
-
-        writer.write_all(rewrite_rule_components.as_bytes())?;
-        writer.align_to(8)?;
-
-        // Write strings
-        writer.write_all(&string_bytes)?;
-
-        Ok(())
+        classes
     }
 
     fn resolve_mapping(
-        string_table: &mut StringTable,
+        string_table: &mut StringSection,
         parsed: &ParsedProguardMapping<'_>,
         obfuscated_name_offset: u32,
         member: &builder::Member,
@@ -560,17 +580,20 @@ impl<'data> ProguardCache<'data> {
             .get(&member.method.receiver.name())
             .and_then(|class| class.source_file);
 
-        let original_file_offset =
-            original_file.map_or(u32::MAX, |s| string_table.insert(s) as u32);
-        let original_name_offset = string_table.insert(member.method.name.as_str()) as u32;
+        let original_file_offset = original_file.map_or(u32::MAX, |s| string_table.insert(s));
+        let original_name_offset = string_table.insert(member.method.name.as_str());
 
         // Only fill in `original_class` if it is _not_ the current class
         let original_class_offset = match member.method.receiver {
             builder::MethodReceiver::ThisClass(_) => u32::MAX,
-            builder::MethodReceiver::OtherClass(name) => string_table.insert(name.as_str()) as u32,
+            builder::MethodReceiver::OtherClass(name) => string_table.insert(name.as_str()),
         };
 
-        let params_offset = string_table.insert(member.method.arguments) as u32;
+        let params_offset = string_table.insert(member.method.arguments);
+        let residual_signature_offset = member
+            .residual_signature
+            .map_or(u32::MAX, |s| string_table.insert(s));
+        let return_type_offset = string_table.insert(member.return_type);
 
         let method_info = parsed
             .method_infos
@@ -605,14 +628,14 @@ impl<'data> ProguardCache<'data> {
                 for condition in &rule.conditions {
                     match condition {
                         builder::RewriteCondition::Throws(descriptor) => {
-                            let offset = string_table.insert(descriptor) as u32;
+                            let offset = string_table.insert(descriptor);
                             conditions.push(RewriteComponent {
                                 kind: REWRITE_CONDITION_THROWS,
                                 value: offset,
                             });
                         }
                         builder::RewriteCondition::Unknown(value) => {
-                            let offset = string_table.insert(value) as u32;
+                            let offset = string_table.insert(value);
                             conditions.push(RewriteComponent {
                                 kind: REWRITE_CONDITION_UNKNOWN,
                                 value: offset,
@@ -631,7 +654,7 @@ impl<'data> ProguardCache<'data> {
                             });
                         }
                         builder::RewriteAction::Unknown(value) => {
-                            let offset = string_table.insert(value) as u32;
+                            let offset = string_table.insert(value);
                             actions.push(RewriteComponent {
                                 kind: REWRITE_ACTION_UNKNOWN,
                                 value: offset,
@@ -664,6 +687,8 @@ impl<'data> ProguardCache<'data> {
             outline_pairs_len: 0,
             rewrite_rules_offset: 0,
             rewrite_rules_len: 0,
+            residual_signature_offset,
+            return_type_offset,
         };
 
         MemberInProgress {
@@ -677,10 +702,21 @@ impl<'data> ProguardCache<'data> {
     ///
     /// Specifically it checks the following:
     /// * All string offsets in class and member entries are either `u32::MAX` or defined.
-    /// * Member entries are ordered by the class they belong to.
+    /// * Member and field entries are ordered by the class they belong to.
+    /// * The original-name index points at every class, in order of original names.
     /// * All `is_synthesized` fields on classes and members are either `0` or `1`.
     pub fn test(&self) {
+        assert_eq!(self.classes_by_original.len(), self.classes.len());
+        let mut prev_original = None;
+        for idx in self.classes_by_original {
+            let class = &self.classes[*idx as usize];
+            let original = self.read_string(class.original_name_offset).ok();
+            assert!(prev_original <= original);
+            prev_original = original;
+        }
+
         let mut prev_end = 0;
+        let mut prev_fields_end = 0;
         for class in self.classes {
             assert!(self.read_string(class.obfuscated_name_offset).is_ok());
             assert!(self.read_string(class.original_name_offset).is_ok());
@@ -690,6 +726,17 @@ impl<'data> ProguardCache<'data> {
                 assert!(self.read_string(class.file_name_offset).is_ok());
             }
 
+            assert_eq!(class.fields_offset, prev_fields_end);
+            prev_fields_end += class.fields_len;
+            assert!(prev_fields_end as usize <= self.fields.len());
+            if let Some(fields) = self.get_class_fields(class) {
+                for field in fields {
+                    assert!(self.read_string(field.obfuscated_name_offset).is_ok());
+                    assert!(self.read_string(field.original_name_offset).is_ok());
+                    assert!(self.read_string(field.type_offset).is_ok());
+                }
+            }
+
             assert_eq!(class.members_offset, prev_end);
             prev_end += class.members_len;
             assert!(prev_end as usize <= self.members.len());
@@ -736,6 +783,12 @@ impl<'data> ProguardCache<'data> {
                 if member.original_file_offset != u32::MAX {
                     assert!(self.read_string(member.original_file_offset).is_ok());
                 }
+
+                if member.residual_signature_offset != u32::MAX {
+                    assert!(self.read_string(member.residual_signature_offset).is_ok());
+                }
+
+                assert!(self.read_string(member.return_type_offset).is_ok());
             }
         }
     }
@@ -754,6 +807,8 @@ struct ClassInProgress<'data> {
     members: BTreeMap<&'data str, Vec<MemberInProgress>>,
     /// The member records for the class, grouped by method name and parameter string.
     members_by_params: BTreeMap<(&'data str, &'data str), Vec<MemberInProgress>>,
+    /// The field records for the class, grouped by field name.
+    fields: BTreeMap<&'data str, Vec<Field>>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -768,3 +823,525 @@ struct RewriteRuleInProgress {
     conditions: Vec<RewriteComponent>,
     actions: Vec<RewriteComponent>,
 }
+
+/// The entries of a single class of a [`ProguardCache`] that is currently being written.
+#[derive(Debug, Clone, Default)]
+struct ClassEntries {
+    /// The class record. Its offsets and lengths are only filled in when writing.
+    class: Class,
+    /// The member records for the class, sorted by method name.
+    members: Vec<Member>,
+    /// The member records for the class, sorted by method name and parameter string.
+    members_by_params: Vec<Member>,
+    /// The field records for the class, sorted by field name.
+    fields: Vec<Field>,
+}
+
+/// A [`ProguardCache`] that is currently being written.
+#[derive(Debug, Clone, Default)]
+struct CacheInProgress {
+    /// The classes in the order in which they are written.
+    classes: Vec<ClassEntries>,
+    /// The outline pairs of all members.
+    outline_pairs: Vec<OutlinePair>,
+    /// The rewrite rule entries of all members.
+    rewrite_rule_entries: Vec<RewriteRuleEntry>,
+    /// The rewrite rule components of all rewrite rule entries.
+    rewrite_rule_components: Vec<RewriteComponent>,
+}
+
+impl CacheInProgress {
+    /// Adds a class, flattening its members and fields.
+    fn push_class(&mut self, class: ClassInProgress<'_>) {
+        let mut entries = ClassEntries {
+            class: class.class,
+            fields: class.fields.into_values().flatten().collect(),
+            ..Default::default()
+        };
+
+        for member in class.members.into_values().flatten() {
+            let member = self.push_member(member);
+            entries.members.push(member);
+        }
+        for member in class.members_by_params.into_values().flatten() {
+            let member = self.push_member(member);
+            entries.members_by_params.push(member);
+        }
+
+        self.classes.push(entries);
+    }
+
+    /// Moves the outline pairs and rewrite rules of a member into their
+    /// sections and returns the member record pointing at them.
+    fn push_member(&mut self, mut mp: MemberInProgress) -> Member {
+        mp.member.outline_pairs_offset = self.outline_pairs.len() as u32;
+        mp.member.outline_pairs_len = mp.outline_pairs.len() as u32;
+        self.outline_pairs.extend(mp.outline_pairs);
+
+        mp.member.rewrite_rules_offset = self.rewrite_rule_entries.len() as u32;
+        mp.member.rewrite_rules_len = mp.rewrite_rules.len() as u32;
+        for rule in mp.rewrite_rules {
+            let cond_start = self.rewrite_rule_components.len() as u32;
+            self.rewrite_rule_components.extend(rule.conditions);
+            let cond_len = self.rewrite_rule_components.len() as u32 - cond_start;
+            let action_start = self.rewrite_rule_components.len() as u32;
+            self.rewrite_rule_components.extend(rule.actions);
+            let action_len = self.rewrite_rule_components.len() as u32 - action_start;
+            self.rewrite_rule_entries.push(RewriteRuleEntry {
+                conditions_offset: cond_start,
+                conditions_len: cond_len,
+                actions_offset: action_start,
+                actions_len: action_len,
+            });
+        }
+
+        mp.member
+    }
+
+    /// Writes the cache. The classes must already be sorted by obfuscated name.
+    fn write<W: Write>(mut self, string_bytes: Vec<u8>, writer: &mut W) -> std::io::Result<()> {
+        // Now that the classes are in their final order, we know where each class's entries start.
+        let mut num_members = 0;
+        let mut num_members_by_params = 0;
+        let mut num_fields = 0;
+        for c in &mut self.classes {
+            c.class.members_offset = num_members;
+            c.class.members_len = c.members.len() as u32;
+            num_members += c.class.members_len;
+
+            c.class.members_by_params_offset = num_members_by_params;
+            c.class.members_by_params_len = c.members_by_params.len() as u32;
+            num_members_by_params += c.class.members_by_params_len;
+
+            c.class.fields_offset = num_fields;
+            c.class.fields_len = c.fields.len() as u32;
+            num_fields += c.class.fields_len;
+        }
+
+        let mut classes_by_original: Vec<u32> = (0..self.classes.len() as u32).collect();
+        classes_by_original.sort_by_key(|idx| {
+            let class = &self.classes[*idx as usize].class;
+            (
+                StringTable::read(&string_bytes, class.original_name_offset as usize).ok(),
+                StringTable::read(&string_bytes, class.obfuscated_name_offset as usize).ok(),
+            )
+        });
+
+        let header = Header {
+            magic: PRGCACHE_MAGIC,
+            version: PRGCACHE_VERSION,
+            num_classes: self.classes.len() as u32,
+            num_members,
+            num_members_by_params,
+            num_outline_pairs: self.outline_pairs.len() as u32,
+            num_rewrite_rule_entries: self.rewrite_rule_entries.len() as u32,
+            num_rewrite_rule_components: self.rewrite_rule_components.len() as u32,
+            num_fields,
+            string_bytes: string_bytes.len() as u32,
+        };
+
+        let mut writer = watto::Writer::new(writer);
+        writer.write_all(header.as_bytes())?;
+        writer.align_to(8)?;
+
+        // Write classes
+        for c in &self.classes {
+            writer.write_all(c.class.as_bytes())?;
+        }
+        writer.align_to(8)?;
+
+        // Write member sections
+        for c in &self.classes {
+            writer.write_all(c.members.as_bytes())?;
+        }
+        writer.align_to(8)?;
+
+        for c in &self.classes {
+            writer.write_all(c.members_by_params.as_bytes())?;
+        }
+        writer.align_to(8)?;
+
+        // Write outline pairs
+        writer.write_all(self.outline_pairs.as_bytes())?;
+        writer.align_to(8)?;
+
+        writer.write_all(self.rewrite_rule_entries.as_bytes())?;
+        writer.align_to(8)?;
+
+        writer.write_all(self.rewrite_rule_components.as_bytes())?;
+        writer.align_to(8)?;
+
+        for c in &self.classes {
+            writer.write_all(c.fields.as_bytes())?;
+        }
+        writer.align_to(8)?;
+
+        writer.write_all(classes_by_original.as_bytes())?;
+        writer.align_to(8)?;
+
+        // Write strings
+        writer.write_all(&string_bytes)?;
+
+        Ok(())
+    }
+}
+
+/// A string table whose offsets start at `base`.
+///
+/// This allows writing the strings of a cache in several parts.
+#[derive(Debug, Default)]
+struct StringSection {
+    /// The offset of the first string of this table in the whole string section.
+    base: u32,
+    table: StringTable,
+}
+
+impl StringSection {
+    /// Inserts a string and returns its offset in the whole string section.
+    fn insert(&mut self, s: &str) -> u32 {
+        self.base + self.table.insert(s) as u32
+    }
+}
+
+/// A class of a [`StreamedCache`].
+#[derive(Debug, Default)]
+struct StreamedClass {
+    /// The original name of the class.
+    original: String,
+    /// The position and length of each part of the mapping containing the class,
+    /// in the scratch storage.
+    blocks: Vec<(u64, usize)>,
+}
+
+/// A part of a [`ProguardCache`] that is written in several steps.
+#[derive(Debug)]
+struct Section {
+    /// The position of the section in the writer.
+    start: u64,
+    /// The position up to which the section has been written.
+    end: u64,
+}
+
+impl Section {
+    /// Appends `bytes` to the section.
+    fn write<W: Write + Seek>(&mut self, writer: &mut W, bytes: &[u8]) -> std::io::Result<()> {
+        writer.seek(SeekFrom::Start(self.end))?;
+        writer.write_all(bytes)?;
+        self.end += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Fills the space up to the `next` section with zeros.
+    fn pad<W: Write + Seek>(&mut self, writer: &mut W, next: &Section) -> std::io::Result<()> {
+        let padding = [0; 8];
+        self.write(writer, &padding[..(next.start - self.end) as usize])
+    }
+}
+
+/// A [`ProguardCache`] that is written one class at a time.
+///
+/// See [`ProguardCache::write_from_reader`].
+#[derive(Debug)]
+struct StreamedCache<'a, S> {
+    /// Holds the text of the classes until the cache is written.
+    scratch: &'a mut S,
+    /// The position in `scratch` at which the next class is stored.
+    scratch_position: u64,
+    /// The classes, by obfuscated name.
+    classes: BTreeMap<String, StreamedClass>,
+    /// The number of entries in each section of the cache.
+    counts: Header,
+    /// The source file and synthesized flag of each class, by original name.
+    class_infos: HashMap<String, (Option<String>, bool)>,
+    /// The name, params and information of each synthesized or outline method,
+    /// by receiver.
+    method_infos: HashMap<String, Vec<(String, String, builder::MethodInfo)>>,
+    /// The mapping format version declared at the top of the mapping.
+    mapping_version: Option<MappingVersion>,
+}
+
+impl<'a, S: Read + Write + Seek> StreamedCache<'a, S> {
+    fn new(scratch: &'a mut S) -> std::io::Result<Self> {
+        Ok(Self {
+            scratch_position: scratch.stream_position()?,
+            scratch,
+            classes: Default::default(),
+            counts: Header::default(),
+            class_infos: Default::default(),
+            method_infos: Default::default(),
+            mapping_version: None,
+        })
+    }
+
+    /// Stores a part of a mapping that contains at most one class, and collects
+    /// the information needed by other classes.
+    fn push_block(&mut self, block: &[u8]) -> std::io::Result<()> {
+        let parsed = ParsedProguardMapping::parse_with_version(
+            ProguardMapping::new(block),
+            true,
+            self.mapping_version,
+        );
+        self.mapping_version = parsed.mapping_version;
+
+        let Some((obfuscated, original)) = parsed.class_names.iter().next() else {
+            return Ok(());
+        };
+
+        for (original, info) in &parsed.class_infos {
+            let source_file = info.source_file.map(str::to_owned);
+            self.class_infos.insert(
+                original.as_str().to_owned(),
+                (source_file, info.is_synthesized),
+            );
+        }
+
+        for (method, info) in &parsed.method_infos {
+            if !info.is_synthesized && !info.is_outline {
+                continue;
+            }
+            let methods = self
+                .method_infos
+                .entry(method.receiver.name().as_str().to_owned())
+                .or_default();
+            let existing = methods.iter_mut().find(|(name, arguments, _)| {
+                name == method.name.as_str() && arguments == method.arguments
+            });
+            match existing {
+                Some((_, _, existing)) => {
+                    existing.is_synthesized |= info.is_synthesized;
+                    existing.is_outline |= info.is_outline;
+                }
+                None => methods.push((
+                    method.name.as_str().to_owned(),
+                    method.arguments.to_owned(),
+                    *info,
+                )),
+            }
+        }
+
+        // The entries are only counted here, so that the sections can be laid out
+        // before the classes are converted again in `finish`.
+        let mut cache = CacheInProgress::default();
+        let mut string_table = StringSection::default();
+        for class in ProguardCache::collect_classes(&parsed, &mut string_table).into_values() {
+            cache.push_class(class);
+        }
+        for c in &cache.classes {
+            self.counts.num_members += c.members.len() as u32;
+            self.counts.num_members_by_params += c.members_by_params.len() as u32;
+            self.counts.num_fields += c.fields.len() as u32;
+        }
+        self.counts.num_outline_pairs += cache.outline_pairs.len() as u32;
+        self.counts.num_rewrite_rule_entries += cache.rewrite_rule_entries.len() as u32;
+        self.counts.num_rewrite_rule_components += cache.rewrite_rule_components.len() as u32;
+
+        let class = self
+            .classes
+            .entry(obfuscated.as_str().to_owned())
+            .or_default();
+        // Like the other ways of writing a cache, the later class record wins.
+        class.original = original.as_str().to_owned();
+        class.blocks.push((self.scratch_position, block.len()));
+
+        self.scratch.write_all(block)?;
+        self.scratch_position += block.len() as u64;
+
+        Ok(())
+    }
+
+    /// Converts the classes in order of their obfuscated names and writes the cache.
+    fn finish<W: Write + Seek>(self, writer: &mut W) -> std::io::Result<()> {
+        let num_classes = self.classes.len() as u32;
+        let counts = &self.counts;
+
+        // Lay out the sections in the same order as `CacheInProgress::write`.
+        let base = writer.stream_position()?;
+        let mut offset = std::mem::size_of::<Header>() as u64;
+        let mut section = |count: u32, size: usize| {
+            let start = base + offset.next_multiple_of(8);
+            offset = start - base + count as u64 * size as u64;
+            Section { start, end: start }
+        };
+        let mut header_section = Section {
+            start: base,
+            end: base,
+        };
+        let mut classes_section = section(num_classes, std::mem::size_of::<Class>());
+        let mut members = section(counts.num_members, std::mem::size_of::<Member>());
+        let mut members_by_params =
+            section(counts.num_members_by_params, std::mem::size_of::<Member>());
+        let mut outline_pairs =
+            section(counts.num_outline_pairs, std::mem::size_of::<OutlinePair>());
+        let mut rewrite_rule_entries = section(
+            counts.num_rewrite_rule_entries,
+            std::mem::size_of::<RewriteRuleEntry>(),
+        );
+        let mut rewrite_rule_components = section(
+            counts.num_rewrite_rule_components,
+            std::mem::size_of::<RewriteComponent>(),
+        );
+        let mut fields = section(counts.num_fields, std::mem::size_of::<Field>());
+        let mut classes_by_original = section(num_classes, std::mem::size_of::<u32>());
+        let mut strings = section(0, 0);
+
+        let mut header = Header::default();
+        let mut class_records = Vec::with_capacity(self.classes.len());
+        let mut text = Vec::new();
+        for class in self.classes.values() {
+            text.clear();
+            for &(position, len) in &class.blocks {
+                self.scratch.seek(SeekFrom::Start(position))?;
+                let start = text.len();
+                text.resize(start + len, 0);
+                self.scratch.read_exact(&mut text[start..])?;
+            }
+
+            let mut parsed = ParsedProguardMapping::parse_with_version(
+                ProguardMapping::new(&text),
+                true,
+                self.mapping_version,
+            );
+            resolve_infos(&mut parsed, &self.class_infos, &self.method_infos);
+
+            let mut string_table = StringSection {
+                base: header.string_bytes,
+                table: StringTable::new(),
+            };
+            let mut cache = CacheInProgress::default();
+            for class in ProguardCache::collect_classes(&parsed, &mut string_table).into_values() {
+                cache.push_class(class);
+            }
+
+            // The entries of the class start after the ones already written.
+            for mut c in cache.classes {
+                c.class.members_offset = header.num_members;
+                c.class.members_len = c.members.len() as u32;
+                header.num_members += c.class.members_len;
+
+                c.class.members_by_params_offset = header.num_members_by_params;
+                c.class.members_by_params_len = c.members_by_params.len() as u32;
+                header.num_members_by_params += c.class.members_by_params_len;
+
+                c.class.fields_offset = header.num_fields;
+                c.class.fields_len = c.fields.len() as u32;
+                header.num_fields += c.class.fields_len;
+
+                for member in c.members.iter_mut().chain(&mut c.members_by_params) {
+                    member.outline_pairs_offset += header.num_outline_pairs;
+                    member.rewrite_rules_offset += header.num_rewrite_rule_entries;
+                }
+
+                members.write(writer, c.members.as_bytes())?;
+                members_by_params.write(writer, c.members_by_params.as_bytes())?;
+                fields.write(writer, c.fields.as_bytes())?;
+                class_records.push(c.class);
+            }
+
+            for entry in &mut cache.rewrite_rule_entries {
+                entry.conditions_offset += header.num_rewrite_rule_components;
+                entry.actions_offset += header.num_rewrite_rule_components;
+            }
+            header.num_outline_pairs += cache.outline_pairs.len() as u32;
+            header.num_rewrite_rule_entries += cache.rewrite_rule_entries.len() as u32;
+            header.num_rewrite_rule_components += cache.rewrite_rule_components.len() as u32;
+            outline_pairs.write(writer, cache.outline_pairs.as_bytes())?;
+            rewrite_rule_entries.write(writer, cache.rewrite_rule_entries.as_bytes())?;
+            rewrite_rule_components.write(writer, cache.rewrite_rule_components.as_bytes())?;
+
+            let string_bytes = string_table.table.into_bytes();
+            header.string_bytes += string_bytes.len() as u32;
+            strings.write(writer, &string_bytes)?;
+        }
+        header.num_classes = class_records.len() as u32;
+
+        let names: Vec<_> = self
+            .classes
+            .iter()
+            .map(|(obfuscated, class)| (class.original.as_str(), obfuscated.as_str()))
+            .collect();
+        let mut by_original: Vec<u32> = (0..names.len() as u32).collect();
+        by_original.sort_by_key(|idx| names[*idx as usize]);
+
+        classes_section.write(writer, class_records.as_bytes())?;
+        classes_by_original.write(writer, by_original.as_bytes())?;
+        header_section.write(writer, header.as_bytes())?;
+
+        // Classes are converted the same way when counting and when writing.
+        debug_assert_eq!(
+            Header {
+                num_classes,
+                string_bytes: header.string_bytes,
+                ..self.counts.clone()
+            },
+            header
+        );
+
+        header_section.pad(writer, &classes_section)?;
+        classes_section.pad(writer, &members)?;
+        members.pad(writer, &members_by_params)?;
+        members_by_params.pad(writer, &outline_pairs)?;
+        outline_pairs.pad(writer, &rewrite_rule_entries)?;
+        rewrite_rule_entries.pad(writer, &rewrite_rule_components)?;
+        rewrite_rule_components.pad(writer, &fields)?;
+        fields.pad(writer, &classes_by_original)?;
+        classes_by_original.pad(writer, &strings)?;
+
+        // Leave the writer at the end of the cache.
+        writer.seek(SeekFrom::Start(strings.end))?;
+
+        Ok(())
+    }
+}
+
+/// Fills in the information about the classes and methods that the members of
+/// `parsed` refer to, which may be defined in other parts of the mapping.
+fn resolve_infos<'s>(
+    parsed: &mut ParsedProguardMapping<'s>,
+    class_infos: &'s HashMap<String, (Option<String>, bool)>,
+    method_infos: &'s HashMap<String, Vec<(String, String, builder::MethodInfo)>>,
+) {
+    let methods: Vec<_> = parsed
+        .members
+        .values()
+        .flat_map(|members| members.all.iter().map(|member| member.method))
+        .collect();
+
+    let receivers = parsed.class_names.values().copied();
+    let receivers: Vec<_> = receivers
+        .chain(methods.iter().map(|method| method.receiver.name()))
+        .collect();
+    for receiver in receivers {
+        if let Some((source_file, is_synthesized)) = class_infos.get(receiver.as_str()) {
+            let info = builder::ClassInfo {
+                source_file: source_file.as_deref(),
+                is_synthesized: *is_synthesized,
+            };
+            parsed.class_infos.insert(receiver, info);
+        }
+    }
+
+    for method in methods {
+        let info = method_infos
+            .get(method.receiver.name().as_str())
+            .and_then(|methods| {
+                methods.iter().find(|(name, arguments, _)| {
+                    name == method.name.as_str() && arguments == method.arguments
+                })
+            })
+            .map(|(_, _, info)| *info)
+            .unwrap_or_default();
+        parsed.method_infos.insert(method, info);
+    }
+}
+
+/// Returns whether a line of a mapping is a class record.
+fn is_class_line(line: &[u8]) -> bool {
+    // Only class records start without indentation, apart from headers.
+    !matches!(
+        line.first(),
+        None | Some(b' ' | b'\t' | b'#' | b'\r' | b'\n')
+    ) && matches!(
+        ProguardRecord::try_parse(line.trim_ascii_end()),
+        Ok(ProguardRecord::Class { .. })
+    )
+}
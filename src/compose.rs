@@ -0,0 +1,1075 @@
+//! Composition of two ProGuard mappings into one.
+//!
+//! When the output of one obfuscation run is obfuscated again (for example a
+//! library shrunk by its own R8 run and then again as part of an app), stack
+//! traces have to be remapped through both mappings. Composing the mappings
+//! up front produces a single mapping from the final names and lines straight
+//! to the original ones, in the same way R8's own `compose` feature does.
+//!
+//! The outer mapping determines which classes and members exist in the
+//! result. Every frame of an outer member is looked up in the inner mapping and
+//! replaced by the (possibly inlined) frames it maps to there, splitting the
+//! minified line range wherever the inner mapping changes.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+use thiserror::Error;
+
+use crate::map_hash::MAP_HASH_KEY;
+use crate::mapping::R8Header;
+use crate::utils::minified_line;
+use crate::{
+    LineMapping, MappingVersion, MappingWriter, ParseError, ProguardMapping, ProguardRecord,
+};
+
+/// Error when composing two [`ProguardMapping`]s.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ComposeError<'s, 'o> {
+    /// A line of the inner mapping could not be parsed.
+    #[error("invalid line {line} in inner mapping: {0}", line = .0.position().line())]
+    InvalidInner(ParseError<'s>),
+    /// A line of the outer mapping could not be parsed.
+    #[error("invalid line {line} in outer mapping: {0}", line = .0.position().line())]
+    InvalidOuter(ParseError<'o>),
+    /// Writing the composed mapping failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// How the line of a frame is derived from a minified line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Line {
+    /// Every minified line maps to the same line.
+    Const(usize),
+    /// Minified lines map to lines at a fixed offset.
+    Offset(i64),
+}
+
+impl Line {
+    /// Interprets a line mapping the same way [`ProguardMapper`](crate::ProguardMapper) does.
+    fn from_mapping(line_mapping: Option<&LineMapping>) -> Self {
+        let Some(line_mapping) = line_mapping else {
+            return Line::Offset(0);
+        };
+        let Some(original_startline) = line_mapping.original_startline else {
+            return Line::Offset(0);
+        };
+        match (line_mapping.startline, line_mapping.original_endline) {
+            (Some(startline), Some(original_endline))
+                if line_mapping.endline.unwrap_or(0) > 0
+                    && original_endline != original_startline =>
+            {
+                Line::Offset(original_startline as i64 - startline as i64)
+            }
+            _ => Line::Const(original_startline),
+        }
+    }
+
+    fn at(self, line: usize) -> usize {
+        match self {
+            Line::Const(line) => line,
+            Line::Offset(offset) => shift(line, offset),
+        }
+    }
+
+    /// Chains `self` with `inner`, which maps the lines produced by `self` further.
+    fn then(self, inner: Line) -> Line {
+        match (self, inner) {
+            (_, Line::Const(line)) => Line::Const(line),
+            (Line::Const(line), Line::Offset(offset)) => Line::Const(shift(line, offset)),
+            (Line::Offset(outer), Line::Offset(inner)) => Line::Offset(outer + inner),
+        }
+    }
+}
+
+fn shift(line: usize, offset: i64) -> usize {
+    (line as i64 + offset).max(0) as usize
+}
+
+/// A single method record together with the R8 headers attached to it.
+#[derive(Clone, Debug)]
+struct Frame<'s> {
+    ty: &'s str,
+    original: &'s str,
+    arguments: &'s str,
+    original_class: Option<&'s str>,
+    line_mapping: Option<LineMapping>,
+    headers: Vec<R8Header<'s>>,
+}
+
+impl Frame<'_> {
+    fn line(&self) -> Line {
+        Line::from_mapping(self.line_mapping.as_ref())
+    }
+}
+
+/// Consecutive method records sharing an obfuscated name and minified range.
+///
+/// All but the last frame are inlined into the next one.
+#[derive(Clone, Debug)]
+struct Group<'s> {
+    obfuscated: &'s str,
+    range: Option<(usize, usize)>,
+    frames: Vec<Frame<'s>>,
+}
+
+#[derive(Clone, Debug)]
+struct Field<'s> {
+    ty: &'s str,
+    original: &'s str,
+    obfuscated: &'s str,
+    headers: Vec<R8Header<'s>>,
+}
+
+#[derive(Clone, Debug)]
+struct Class<'s> {
+    original: &'s str,
+    obfuscated: &'s str,
+    headers: Vec<R8Header<'s>>,
+    fields: Vec<Field<'s>>,
+    groups: Vec<Group<'s>>,
+}
+
+/// A mapping file grouped into classes and inline groups.
+#[derive(Clone, Debug, Default)]
+struct Parsed<'s> {
+    headers: Vec<(&'s str, Option<&'s str>)>,
+    /// The mapping format version declared by the mapping.
+    version: Option<&'s str>,
+    /// Other R8 headers before the first class.
+    r8_headers: Vec<R8Header<'s>>,
+    classes: Vec<Class<'s>>,
+}
+
+impl<'s> Parsed<'s> {
+    fn parse(mapping: &ProguardMapping<'s>) -> Result<Self, ParseError<'s>> {
+        #[derive(PartialEq)]
+        enum Last {
+            Class,
+            Field,
+            Method,
+        }
+
+        let mut parsed = Self::default();
+        let mut last = None;
+
+        for record in mapping.iter() {
+            match record? {
+                ProguardRecord::Header { key, value } => parsed.headers.push((key, value)),
+                ProguardRecord::R8Header(R8Header::MappingVersion { version }) => {
+                    parsed.version = Some(version);
+                }
+                ProguardRecord::R8Header(header) => {
+                    let Some(class) = parsed.classes.last_mut() else {
+                        parsed.r8_headers.push(header);
+                        continue;
+                    };
+                    match last {
+                        Some(Last::Class) => class.headers.push(header),
+                        Some(Last::Field) => {
+                            if let Some(field) = class.fields.last_mut() {
+                                field.headers.push(header);
+                            }
+                        }
+                        Some(Last::Method) => {
+                            if let Some(frame) = class
+                                .groups
+                                .last_mut()
+                                .and_then(|group| group.frames.last_mut())
+                            {
+                                frame.headers.push(header);
+                            }
+                        }
+                        None => {}
+                    }
+                }
+                ProguardRecord::Class {
+                    original,
+                    obfuscated,
+                } => {
+                    parsed.classes.push(Class {
+                        original,
+                        obfuscated,
+                        headers: Vec::new(),
+                        fields: Vec::new(),
+                        groups: Vec::new(),
+                    });
+                    last = Some(Last::Class);
+                }
+                ProguardRecord::Field {
+                    ty,
+                    original,
+                    obfuscated,
+                } => {
+                    // Fields before the first class record have nothing to attach to.
+                    if let Some(class) = parsed.classes.last_mut() {
+                        class.fields.push(Field {
+                            ty,
+                            original,
+                            obfuscated,
+                            headers: Vec::new(),
+                        });
+                        last = Some(Last::Field);
+                    }
+                }
+                ProguardRecord::Method {
+                    ty,
+                    original,
+                    obfuscated,
+                    arguments,
+                    original_class,
+                    line_mapping,
+                } => {
+                    let Some(class) = parsed.classes.last_mut() else {
+                        continue;
+                    };
+                    let range = line_mapping
+                        .and_then(|lm| Some((lm.startline?, lm.endline?)))
+                        .filter(|(_, endline)| *endline > 0);
+                    let frame = Frame {
+                        ty,
+                        original,
+                        arguments,
+                        original_class,
+                        line_mapping,
+                        headers: Vec::new(),
+                    };
+
+                    match class.groups.last_mut() {
+                        Some(group)
+                            if last == Some(Last::Method)
+                                && range.is_some()
+                                && group.range == range
+                                && group.obfuscated == obfuscated =>
+                        {
+                            group.frames.push(frame)
+                        }
+                        _ => class.groups.push(Group {
+                            obfuscated,
+                            range,
+                            frames: vec![frame],
+                        }),
+                    }
+                    last = Some(Last::Method);
+                }
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// An R8 header of a composed frame.
+#[derive(Clone, Debug)]
+enum Header<'s> {
+    Verbatim(R8Header<'s>),
+    OutlineCallsite {
+        positions: Vec<(usize, usize)>,
+        outline: Option<Cow<'s, str>>,
+    },
+    RewriteFrame {
+        conditions: Vec<&'s str>,
+        actions: Vec<Cow<'s, str>>,
+    },
+}
+
+/// A frame of the composed mapping.
+#[derive(Clone, Debug)]
+struct ComposedFrame<'s> {
+    ty: Cow<'s, str>,
+    class: &'s str,
+    original: &'s str,
+    arguments: Cow<'s, str>,
+    line: Line,
+    headers: Vec<Header<'s>>,
+}
+
+/// A minified range of the composed mapping with its (inlined) frames.
+#[derive(Clone, Debug)]
+struct Piece<'s> {
+    range: Option<(usize, usize)>,
+    frames: Vec<ComposedFrame<'s>>,
+}
+
+/// The inner mapping's view of a frame of the outer mapping.
+struct Resolved<'a, 's> {
+    /// The class of the frame as named by the inner mapping.
+    frame_class: &'s str,
+    /// The class of the frame with inner obfuscation removed.
+    class: &'s str,
+    /// The inline groups of the inner mapping the frame may refer to.
+    candidates: Vec<&'a Group<'s>>,
+}
+
+struct Composer<'a, 's> {
+    inner: &'a Parsed<'s>,
+    outer: &'a Parsed<'s>,
+    /// Inner classes by obfuscated name.
+    inner_classes: HashMap<&'s str, &'a Class<'s>>,
+    /// Outer classes by original name.
+    outer_classes: HashMap<&'s str, &'a Class<'s>>,
+    /// Names of all classes in the composed mapping.
+    outer_obfuscated: HashSet<&'s str>,
+    /// Original class and field names referenced anywhere in the outer mapping.
+    referenced_fields: HashSet<(&'s str, &'s str)>,
+    /// Original class and method names referenced anywhere in the outer mapping.
+    referenced_methods: HashSet<(&'s str, &'s str)>,
+}
+
+impl<'a, 's> Composer<'a, 's> {
+    fn new(inner: &'a Parsed<'s>, outer: &'a Parsed<'s>) -> Self {
+        let mut referenced_fields = HashSet::new();
+        let mut referenced_methods = HashSet::new();
+        for class in &outer.classes {
+            for field in &class.fields {
+                referenced_fields.insert((class.original, field.original));
+            }
+            for frame in class.groups.iter().flat_map(|group| &group.frames) {
+                let frame_class = frame.original_class.unwrap_or(class.original);
+                referenced_methods.insert((frame_class, frame.original));
+            }
+        }
+
+        Self {
+            inner,
+            outer,
+            inner_classes: inner.classes.iter().map(|c| (c.obfuscated, c)).collect(),
+            outer_classes: outer.classes.iter().map(|c| (c.original, c)).collect(),
+            outer_obfuscated: outer.classes.iter().map(|c| c.obfuscated).collect(),
+            referenced_fields,
+            referenced_methods,
+        }
+    }
+
+    fn write<W: Write>(&self, writer: &mut MappingWriter<W>) -> io::Result<()> {
+        // Headers of the outer mapping take precedence over inner headers with
+        // the same key. The map hash of neither mapping matches the composed one.
+        let inner_headers = self.inner.headers.iter().filter(|(key, _)| {
+            !self
+                .outer
+                .headers
+                .iter()
+                .any(|(outer_key, _)| outer_key == key)
+        });
+        for &(key, value) in self.outer.headers.iter().chain(inner_headers) {
+            if key != MAP_HASH_KEY {
+                writer.write_record(&ProguardRecord::Header { key, value })?;
+            }
+        }
+
+        // The composed mapping can contain headers of both mappings.
+        let version = [self.inner.version, self.outer.version]
+            .into_iter()
+            .flatten()
+            .max_by_key(|version| MappingVersion::parse(version));
+        if let Some(version) = version {
+            writer.write_record(&ProguardRecord::R8Header(R8Header::MappingVersion {
+                version,
+            }))?;
+        }
+
+        let inner_r8_headers = self
+            .inner
+            .r8_headers
+            .iter()
+            .filter(|header| !self.outer.r8_headers.contains(header));
+        for header in self.outer.r8_headers.iter().chain(inner_r8_headers) {
+            writer.write_record(&ProguardRecord::R8Header(header.clone()))?;
+        }
+
+        for class in &self.outer.classes {
+            self.write_class(writer, class)?;
+        }
+
+        // Classes the outer mapping doesn't mention were kept as they are,
+        // unless their name is now taken by another class.
+        for class in &self.inner.classes {
+            if !self.outer_classes.contains_key(class.obfuscated)
+                && !self.outer_obfuscated.contains(class.obfuscated)
+            {
+                write_class_verbatim(writer, class)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_class<W: Write>(
+        &self,
+        writer: &mut MappingWriter<W>,
+        class: &'a Class<'s>,
+    ) -> io::Result<()> {
+        let inner = self.inner_classes.get(class.original).copied();
+        let original = inner.map_or(class.original, |inner| inner.original);
+
+        writer.write_record(&ProguardRecord::Class {
+            original,
+            obfuscated: class.obfuscated,
+        })?;
+        // Class headers describe the original class, which only the inner mapping knows about.
+        for header in original_headers(inner.map(|inner| &inner.headers[..]), &class.headers) {
+            writer.write_record(&ProguardRecord::R8Header(header.clone()))?;
+        }
+
+        // Members the outer mapping doesn't mention were kept as they are,
+        // unless their name is now taken by another member.
+        let kept_fields = inner.into_iter().flat_map(|inner| {
+            inner.fields.iter().filter(|field| {
+                !class
+                    .fields
+                    .iter()
+                    .any(|f| f.obfuscated == field.obfuscated)
+                    && !self
+                        .referenced_fields
+                        .contains(&(inner.obfuscated, field.obfuscated))
+            })
+        });
+        let kept_groups = inner.into_iter().flat_map(|inner| {
+            inner.groups.iter().filter(|group| {
+                !class
+                    .groups
+                    .iter()
+                    .any(|g| g.obfuscated == group.obfuscated)
+                    && !self
+                        .referenced_methods
+                        .contains(&(inner.obfuscated, group.obfuscated))
+            })
+        });
+
+        for field in &class.fields {
+            let inner_field = inner.and_then(|inner| {
+                inner
+                    .fields
+                    .iter()
+                    .find(|inner_field| inner_field.obfuscated == field.original)
+            });
+            let (ty, field_original) = match inner_field {
+                Some(inner_field) => (Cow::Borrowed(inner_field.ty), inner_field.original),
+                None => (self.deobfuscate_type(field.ty), field.original),
+            };
+            writer.write_record(&ProguardRecord::Field {
+                ty: &ty,
+                original: field_original,
+                obfuscated: field.obfuscated,
+            })?;
+            let inner_headers = inner_field.map(|inner_field| &inner_field.headers[..]);
+            for header in original_headers(inner_headers, &field.headers) {
+                writer.write_record(&ProguardRecord::R8Header(header.clone()))?;
+            }
+        }
+
+        for field in kept_fields {
+            write_field_verbatim(writer, field)?;
+        }
+
+        for group in &class.groups {
+            for piece in self.compose_group(class, group) {
+                write_piece(writer, original, group.obfuscated, &piece)?;
+            }
+        }
+        for group in kept_groups {
+            write_group_verbatim(writer, group)?;
+        }
+
+        Ok(())
+    }
+
+    /// Looks up the inner groups an outer frame of `class` refers to.
+    fn resolve(&self, class: &Class<'s>, frame: &Frame<'s>) -> Resolved<'a, 's> {
+        let frame_class = frame.original_class.unwrap_or(class.original);
+        let Some(inner) = self.inner_classes.get(frame_class) else {
+            return Resolved {
+                frame_class,
+                class: frame_class,
+                candidates: Vec::new(),
+            };
+        };
+
+        let candidates: Vec<_> = inner
+            .groups
+            .iter()
+            .filter(|group| group.obfuscated == frame.original)
+            .collect();
+        // Overloads are told apart by the arguments of the outermost frame.
+        let arguments = self.deobfuscate_arguments(frame.arguments);
+        let by_arguments: Vec<_> = candidates
+            .iter()
+            .copied()
+            .filter(|group| {
+                group
+                    .frames
+                    .last()
+                    .is_some_and(|outermost| outermost.arguments == arguments)
+            })
+            .collect();
+
+        Resolved {
+            frame_class,
+            class: inner.original,
+            candidates: if by_arguments.is_empty() {
+                candidates
+            } else {
+                by_arguments
+            },
+        }
+    }
+
+    fn compose_group(&self, class: &Class<'s>, group: &Group<'s>) -> Vec<Piece<'s>> {
+        let resolved: Vec<_> = group
+            .frames
+            .iter()
+            .map(|frame| self.resolve(class, frame))
+            .collect();
+
+        let Some((startline, endline)) = group.range else {
+            // Groups without a minified range consist of a single frame.
+            let (Some(frame), Some(resolved)) = (group.frames.first(), resolved.first()) else {
+                return Vec::new();
+            };
+            let line = frame.line();
+            let pieces = match line {
+                Line::Const(intermediate) => {
+                    let inner = select_group(&resolved.candidates, intermediate);
+                    vec![(None, self.expand(frame, resolved, inner, line, true))]
+                }
+                // The outer mapping didn't touch the lines, so the inner
+                // ranges are carried over as they are.
+                Line::Offset(_) if !resolved.candidates.is_empty() => resolved
+                    .candidates
+                    .iter()
+                    .map(|inner| {
+                        let frames = self.expand(frame, resolved, Some(inner), line, true);
+                        (inner.range, frames)
+                    })
+                    .collect(),
+                Line::Offset(_) => {
+                    vec![(None, self.expand(frame, resolved, None, line, true))]
+                }
+            };
+            return pieces
+                .into_iter()
+                .map(|(range, expansion)| finish_piece(group, range, vec![expansion]))
+                .collect();
+        };
+
+        // Split the minified range wherever one of the frames crosses into
+        // another inner range.
+        let mut bounds = vec![startline, endline + 1];
+        for (frame, resolved) in group.frames.iter().zip(&resolved) {
+            let Line::Offset(offset) = frame.line() else {
+                continue;
+            };
+            for (start, end) in resolved.candidates.iter().filter_map(|inner| inner.range) {
+                for bound in [start as i64 - offset, end as i64 + 1 - offset] {
+                    if bound > startline as i64 && bound <= endline as i64 {
+                        bounds.push(bound as usize);
+                    }
+                }
+            }
+        }
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        bounds
+            .windows(2)
+            .map(|window| {
+                let range = (window[0], window[1] - 1);
+                let expansions = group
+                    .frames
+                    .iter()
+                    .zip(&resolved)
+                    .enumerate()
+                    .map(|(idx, (frame, resolved))| {
+                        let line = frame.line();
+                        let inner = select_group(&resolved.candidates, line.at(range.0));
+                        self.expand(frame, resolved, inner, line, idx == 0)
+                    })
+                    .collect();
+                finish_piece(group, Some(range), expansions)
+            })
+            .collect()
+    }
+
+    /// Replaces an outer frame by the frames of the `inner` group it refers to.
+    ///
+    /// Falls back to the outer frame itself, with inner obfuscation removed
+    /// from its types, if there is no such group.
+    fn expand(
+        &self,
+        frame: &Frame<'s>,
+        resolved: &Resolved<'a, 's>,
+        inner: Option<&Group<'s>>,
+        line: Line,
+        innermost: bool,
+    ) -> Vec<ComposedFrame<'s>> {
+        let Some(inner) = inner else {
+            return vec![ComposedFrame {
+                ty: self.deobfuscate_type(frame.ty),
+                class: resolved.class,
+                original: frame.original,
+                arguments: self.deobfuscate_arguments(frame.arguments),
+                line,
+                headers: Vec::new(),
+            }];
+        };
+
+        inner
+            .frames
+            .iter()
+            .map(|inner_frame| {
+                let headers = inner_frame
+                    .headers
+                    .iter()
+                    .filter_map(|header| match header {
+                        R8Header::OutlineCallsite { positions, outline } => self
+                            .compose_outline_callsite(
+                                positions,
+                                outline.filter(|_| self.inner_has_outline_keys()),
+                                resolved.frame_class,
+                                inner.obfuscated,
+                            ),
+                        // Rewrites remove frames from the top of the stack, so they
+                        // only still apply if nothing was inlined into this frame.
+                        R8Header::RewriteFrame {
+                            conditions,
+                            actions,
+                        } => innermost.then(|| Header::RewriteFrame {
+                            conditions: conditions.clone(),
+                            actions: actions.iter().copied().map(Cow::Borrowed).collect(),
+                        }),
+                        // The signature of the intermediate minified method is
+                        // meaningless after composing, only the outer one is kept.
+                        R8Header::ResidualSignature { .. } => None,
+                        header => Some(Header::Verbatim(header.clone())),
+                    })
+                    .collect();
+
+                ComposedFrame {
+                    ty: Cow::Borrowed(inner_frame.ty),
+                    class: inner_frame.original_class.unwrap_or(resolved.class),
+                    original: inner_frame.original,
+                    arguments: Cow::Borrowed(inner_frame.arguments),
+                    line: line.then(inner_frame.line()),
+                    headers,
+                }
+            })
+            .collect()
+    }
+
+    /// Whether outline callsite headers of the inner mapping may name the
+    /// outline method, which they only do from version 2.2 on.
+    fn inner_has_outline_keys(&self) -> bool {
+        self.inner
+            .version
+            .and_then(MappingVersion::parse)
+            .is_none_or(|version| version >= MappingVersion::V2_2)
+    }
+
+    /// Moves an outline callsite header of the inner mapping to the lines of the
+    /// outer mapping.
+    ///
+    /// The callsite positions are lines of `method` in `class`, as named by the
+    /// inner mapping. The outline positions can only be moved if the header
+    /// names the outline method; otherwise they are kept as they are.
+    fn compose_outline_callsite(
+        &self,
+        positions: &HashMap<&'s str, usize>,
+        outline: Option<&'s str>,
+        class: &str,
+        method: &str,
+    ) -> Option<Header<'s>> {
+        let outline_method = outline.and_then(|outline| self.outer_outline(outline));
+
+        let mut composed: Vec<_> = positions
+            .iter()
+            .filter_map(|(position, callsite)| {
+                let position = position.parse().ok()?;
+                let position = match &outline_method {
+                    Some((_, outline_class, outline_name)) => {
+                        self.outer_line(outline_class, outline_name, position)?
+                    }
+                    None => position,
+                };
+                Some((position, self.outer_line(class, method, *callsite)?))
+            })
+            .collect();
+        composed.sort_unstable();
+
+        (!composed.is_empty()).then(|| Header::OutlineCallsite {
+            positions: composed,
+            outline: match outline_method {
+                Some((descriptor, _, _)) => Some(Cow::Owned(descriptor)),
+                None => outline.map(Cow::Borrowed),
+            },
+        })
+    }
+
+    /// Maps a line of a method, as named by the inner mapping, to the line it
+    /// ends up at after the outer obfuscation.
+    ///
+    /// Methods the outer mapping doesn't mention keep their lines.
+    fn outer_line(&self, class: &str, method: &str, line: usize) -> Option<usize> {
+        let Some(class) = self.outer_classes.get(class) else {
+            return Some(line);
+        };
+        let mut groups = outer_method_groups(class, method).peekable();
+        if groups.peek().is_none() {
+            return Some(line);
+        }
+        groups.find_map(|group| match group.frames.last()?.line_mapping {
+            Some(lm) => minified_line(
+                lm.startline,
+                lm.endline,
+                lm.original_startline,
+                lm.original_endline,
+                line,
+            ),
+            None => Some(line),
+        })
+    }
+
+    /// Looks up an outline method, given as residual descriptor like `La;a()I`,
+    /// in the outer mapping.
+    ///
+    /// Returns the descriptor of the outline after the outer obfuscation and the
+    /// class and method name of the outline as named by the inner mapping.
+    fn outer_outline(&self, outline: &'s str) -> Option<(String, &'s str, &'s str)> {
+        let (class, method) = outline.strip_prefix('L')?.split_once(';')?;
+        let (name, signature) = method.split_at(method.find('(')?);
+        let class = self.outer_classes.get(class.replace('/', ".").as_str())?;
+        let obfuscated = outer_method_groups(class, name).next()?.obfuscated;
+
+        let mut descriptor = format!("L{};{obfuscated}", class.obfuscated.replace('.', "/"));
+        let mut rest = signature;
+        while let Some(start) = rest.find('L') {
+            let end = rest[start..].find(';')? + start;
+            descriptor.push_str(&rest[..=start]);
+            let ty = rest[start + 1..end].replace('/', ".");
+            let ty = self
+                .outer_classes
+                .get(ty.as_str())
+                .map_or(ty.as_str(), |class| class.obfuscated);
+            descriptor.push_str(&ty.replace('.', "/"));
+            rest = &rest[end..];
+        }
+        descriptor.push_str(rest);
+
+        Some((descriptor, class.original, name))
+    }
+
+    /// Removes the inner obfuscation from a type name.
+    fn deobfuscate_type(&self, ty: &'s str) -> Cow<'s, str> {
+        let base = ty.trim_end_matches("[]");
+        match self.inner_classes.get(base) {
+            Some(class) if base.len() == ty.len() => Cow::Borrowed(class.original),
+            Some(class) => Cow::Owned(format!("{}{}", class.original, &ty[base.len()..])),
+            None => Cow::Borrowed(ty),
+        }
+    }
+
+    /// Removes the inner obfuscation from a comma-separated argument list.
+    fn deobfuscate_arguments(&self, arguments: &'s str) -> Cow<'s, str> {
+        let types: Vec<_> = arguments
+            .split(',')
+            .map(|ty| self.deobfuscate_type(ty))
+            .collect();
+        if types
+            .iter()
+            .zip(arguments.split(','))
+            .all(|(ty, old)| ty == old)
+        {
+            Cow::Borrowed(arguments)
+        } else {
+            Cow::Owned(types.join(","))
+        }
+    }
+}
+
+/// Returns the headers of a class or field that both mappings mention.
+///
+/// Known headers describe the original, so only the inner ones are kept if
+/// there are any. Headers this crate doesn't know are kept from both.
+fn original_headers<'h, 's>(
+    inner: Option<&'h [R8Header<'s>]>,
+    outer: &'h [R8Header<'s>],
+) -> Vec<&'h R8Header<'s>> {
+    match inner {
+        Some(inner) => inner
+            .iter()
+            .chain(
+                outer
+                    .iter()
+                    .filter(|header| matches!(header, R8Header::Other(_))),
+            )
+            .collect(),
+        None => outer.iter().collect(),
+    }
+}
+
+/// Returns the groups of `class` whose outermost frame is the method `name`.
+fn outer_method_groups<'a, 's>(
+    class: &'a Class<'s>,
+    name: &'a str,
+) -> impl Iterator<Item = &'a Group<'s>> {
+    class.groups.iter().filter(move |group| {
+        group
+            .frames
+            .last()
+            .is_some_and(|frame| frame.original == name && frame.original_class.is_none())
+    })
+}
+
+/// Picks the inner group covering `line`, following the same rules as remapping.
+fn select_group<'a, 's>(candidates: &[&'a Group<'s>], line: usize) -> Option<&'a Group<'s>> {
+    if candidates.iter().any(|group| group.range.is_some()) {
+        candidates
+            .iter()
+            .find(|group| {
+                group
+                    .range
+                    .is_some_and(|(start, end)| (start..=end).contains(&line))
+            })
+            .copied()
+    } else {
+        candidates.first().copied()
+    }
+}
+
+/// Assembles the expansions of all frames of an outer `group` into a piece and
+/// attaches the outer group's headers.
+fn finish_piece<'s>(
+    group: &Group<'s>,
+    range: Option<(usize, usize)>,
+    mut expansions: Vec<Vec<ComposedFrame<'s>>>,
+) -> Piece<'s> {
+    let counts: Vec<_> = expansions.iter().map(Vec::len).collect();
+    let mut rewrites = Vec::new();
+
+    for (frame, expansion) in group.frames.iter().zip(&mut expansions) {
+        let Some(outermost) = expansion.last_mut() else {
+            continue;
+        };
+        for header in &frame.headers {
+            match header {
+                R8Header::RewriteFrame {
+                    conditions,
+                    actions,
+                } => {
+                    // The number of frames to remove changes with the number of
+                    // frames each outer frame expanded to.
+                    let actions = actions
+                        .iter()
+                        .map(|action| {
+                            let count = action
+                                .strip_prefix("removeInnerFrames(")
+                                .and_then(|rest| rest.strip_suffix(')'))
+                                .and_then(|count| count.parse::<usize>().ok());
+                            match count {
+                                Some(count) => {
+                                    let count: usize = counts.iter().take(count).sum();
+                                    Cow::Owned(format!("removeInnerFrames({count})"))
+                                }
+                                None => Cow::Borrowed(*action),
+                            }
+                        })
+                        .collect();
+                    rewrites.push(Header::RewriteFrame {
+                        conditions: conditions.clone(),
+                        actions,
+                    });
+                }
+                // Outline callsite positions are lines of the outer mapping already.
+                header => outermost.headers.push(Header::Verbatim(header.clone())),
+            }
+        }
+    }
+
+    let mut frames: Vec<_> = expansions.into_iter().flatten().collect();
+    if let Some(innermost) = frames.first_mut() {
+        innermost.headers.extend(rewrites);
+    }
+
+    Piece { range, frames }
+}
+
+fn write_piece<W: Write>(
+    writer: &mut MappingWriter<W>,
+    class: &str,
+    obfuscated: &str,
+    piece: &Piece<'_>,
+) -> io::Result<()> {
+    for frame in &piece.frames {
+        let line_mapping = match (piece.range, frame.line) {
+            (Some((startline, endline)), Line::Const(line)) => Some(LineMapping {
+                startline: Some(startline),
+                endline: Some(endline),
+                original_startline: Some(line),
+                original_endline: None,
+            }),
+            (Some((startline, endline)), Line::Offset(offset)) => Some(LineMapping {
+                startline: Some(startline),
+                endline: Some(endline),
+                original_startline: Some(shift(startline, offset)),
+                original_endline: Some(shift(endline, offset)),
+            }),
+            (None, Line::Const(line)) => Some(LineMapping {
+                startline: None,
+                endline: None,
+                original_startline: Some(line),
+                original_endline: None,
+            }),
+            (None, Line::Offset(_)) => None,
+        };
+
+        writer.write_record(&ProguardRecord::Method {
+            ty: &frame.ty,
+            original: frame.original,
+            obfuscated,
+            arguments: &frame.arguments,
+            original_class: (frame.class != class).then_some(frame.class),
+            line_mapping,
+        })?;
+
+        for header in &frame.headers {
+            match header {
+                Header::Verbatim(header) => {
+                    writer.write_record(&ProguardRecord::R8Header(header.clone()))?
+                }
+                Header::OutlineCallsite { positions, outline } => {
+                    let keys: Vec<_> = positions.iter().map(|(key, _)| key.to_string()).collect();
+                    let header = R8Header::OutlineCallsite {
+                        positions: keys
+                            .iter()
+                            .map(String::as_str)
+                            .zip(positions.iter().map(|(_, value)| *value))
+                            .collect(),
+                        outline: outline.as_deref(),
+                    };
+                    writer.write_record(&ProguardRecord::R8Header(header))?;
+                }
+                Header::RewriteFrame {
+                    conditions,
+                    actions,
+                } => {
+                    let header = R8Header::RewriteFrame {
+                        conditions: conditions.clone(),
+                        actions: actions.iter().map(AsRef::as_ref).collect(),
+                    };
+                    writer.write_record(&ProguardRecord::R8Header(header))?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_field_verbatim<W: Write>(
+    writer: &mut MappingWriter<W>,
+    field: &Field<'_>,
+) -> io::Result<()> {
+    writer.write_record(&ProguardRecord::Field {
+        ty: field.ty,
+        original: field.original,
+        obfuscated: field.obfuscated,
+    })?;
+    for header in &field.headers {
+        writer.write_record(&ProguardRecord::R8Header(header.clone()))?;
+    }
+    Ok(())
+}
+
+fn write_group_verbatim<W: Write>(
+    writer: &mut MappingWriter<W>,
+    group: &Group<'_>,
+) -> io::Result<()> {
+    for frame in &group.frames {
+        writer.write_record(&ProguardRecord::Method {
+            ty: frame.ty,
+            original: frame.original,
+            obfuscated: group.obfuscated,
+            arguments: frame.arguments,
+            original_class: frame.original_class,
+            line_mapping: frame.line_mapping,
+        })?;
+        for header in &frame.headers {
+            writer.write_record(&ProguardRecord::R8Header(header.clone()))?;
+        }
+    }
+    Ok(())
+}
+
+fn write_class_verbatim<W: Write>(
+    writer: &mut MappingWriter<W>,
+    class: &Class<'_>,
+) -> io::Result<()> {
+    writer.write_record(&ProguardRecord::Class {
+        original: class.original,
+        obfuscated: class.obfuscated,
+    })?;
+    for header in &class.headers {
+        writer.write_record(&ProguardRecord::R8Header(header.clone()))?;
+    }
+    for field in &class.fields {
+        write_field_verbatim(writer, field)?;
+    }
+    for group in &class.groups {
+        write_group_verbatim(writer, group)?;
+    }
+    Ok(())
+}
+
+impl<'s> ProguardMapping<'s> {
+    /// Composes this mapping with an `outer` mapping and writes the result to `writer`.
+    ///
+    /// `outer` has to be the mapping of a second obfuscation run on the output
+    /// of the run that produced this mapping. The composed mapping maps the
+    /// names and lines of the final output directly to the original ones,
+    /// including inlined frames of both runs and the R8 outline and rewrite
+    /// frame metadata.
+    ///
+    /// Classes and members that `outer` does not mention are assumed to have
+    /// been kept as they are by the second run. The file headers of both
+    /// mappings are kept, except for the map hash, which doesn't match the
+    /// composed mapping.
+    ///
+    /// Fails on the first line of either mapping that can't be parsed, since
+    /// the composed mapping would silently be incomplete otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use proguard::{ProguardMapper, ProguardMapping, StackFrame};
+    ///
+    /// let inner = ProguardMapping::new(
+    ///     b"com.example.Foo -> a:
+    ///     1:2:void bar():10:11 -> a",
+    /// );
+    /// let outer = ProguardMapping::new(
+    ///     b"a -> b:
+    ///     5:6:void a():1:2 -> c",
+    /// );
+    ///
+    /// let mut composed = Vec::new();
+    /// inner.compose(&outer, &mut composed).unwrap();
+    ///
+    /// let mapper = ProguardMapper::new(ProguardMapping::new(&composed));
+    /// let frame = mapper.remap_frame(&StackFrame::new("b", "c", 6)).next().unwrap();
+    /// assert_eq!(frame.class(), "com.example.Foo");
+    /// assert_eq!(frame.method(), "bar");
+    /// assert_eq!(frame.line(), Some(11));
+    /// ```
+    pub fn compose<'o, W: Write>(
+        &self,
+        outer: &ProguardMapping<'o>,
+        writer: W,
+    ) -> Result<(), ComposeError<'s, 'o>> {
+        let inner = Parsed::parse(self).map_err(ComposeError::InvalidInner)?;
+        let outer = Parsed::parse(outer).map_err(ComposeError::InvalidOuter)?;
+        Composer::new(&inner, &outer).write(&mut MappingWriter::new(writer))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+//! Matching stack trace lines with user-provided patterns.
+
+use std::fmt::{Result as FmtResult, Write};
+use std::ops::Range;
+
+use regex::{Captures, Regex};
+use thiserror::Error;
+
+use crate::StackFrame;
+
+/// A Java identifier, such as a simple class or method name.
+const IDENTIFIER: &str = r"[\p{L}\p{N}_$]+";
+
+/// The placeholders of a [`FrameRegex`], with the names of their capture groups.
+const PLACEHOLDERS: [(char, &str); 6] = [
+    ('c', "class"),
+    ('m', "method"),
+    ('s', "file"),
+    ('l', "line"),
+    ('t', "type"),
+    ('f', "field"),
+];
+
+/// Error when compiling a [`FrameRegex`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum FrameRegexError {
+    /// A placeholder appears more than once in the pattern.
+    #[error("placeholder `%{0}` appears more than once")]
+    DuplicatePlaceholder(char),
+    /// The pattern is not a valid regular expression.
+    #[error(transparent)]
+    Regex(#[from] regex::Error),
+}
+
+/// A pattern for stack trace lines in a custom format.
+///
+/// This is the equivalent of the `--regex` option of R8's `retrace` tool. The
+/// pattern is a regular expression that may contain the following
+/// placeholders, each of which captures part of a line:
+///
+/// - `%c`: a fully qualified class name,
+/// - `%m`: a method name,
+/// - `%s`: a source file name,
+/// - `%l`: a line number,
+/// - `%t`: a type, for example the type of a field,
+/// - `%f`: a field name.
+///
+/// When remapping a line that matches the pattern, only the captured parts
+/// are replaced, and the rest of the line is kept as it is. A class and method
+/// are remapped as a frame, which may result in multiple lines for inlined
+/// or ambiguous methods.
+///
+/// # Examples
+///
+/// ```
+/// use proguard::{FrameRegex, ProguardMapper, RemapOptions};
+///
+/// let mapper = ProguardMapper::from(
+///     "com.example.Main -> a:
+///     1:1:void main():10:10 -> a",
+/// );
+///
+/// let regex = FrameRegex::new(r"^\[%c#%m:%l\]").unwrap();
+/// let options = RemapOptions::new().frame_regex(regex);
+/// let remapped = mapper
+///     .remap_stacktrace_with_options("[a#a:1] main thread", &options)
+///     .unwrap();
+/// assert_eq!(remapped, "[com.example.Main#main:10] main thread\n");
+/// ```
+#[derive(Clone, Debug)]
+pub struct FrameRegex {
+    regex: Regex,
+}
+
+impl FrameRegex {
+    /// Compiles a pattern with placeholders into a `FrameRegex`.
+    pub fn new(pattern: &str) -> Result<Self, FrameRegexError> {
+        let class = format!("{IDENTIFIER}(?:\\.{IDENTIFIER})*");
+        let mut seen = [false; PLACEHOLDERS.len()];
+        let mut regex = String::with_capacity(pattern.len());
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                regex.push(c);
+                continue;
+            }
+
+            let next = chars.clone().next();
+            let Some(idx) = PLACEHOLDERS
+                .iter()
+                .position(|(placeholder, _)| Some(*placeholder) == next)
+            else {
+                regex.push(c);
+                continue;
+            };
+            chars.next();
+
+            let (placeholder, name) = PLACEHOLDERS[idx];
+            if std::mem::replace(&mut seen[idx], true) {
+                return Err(FrameRegexError::DuplicatePlaceholder(placeholder));
+            }
+            let group = match placeholder {
+                'c' => class.clone(),
+                'm' => format!("{IDENTIFIER}|<init>|<clinit>"),
+                's' => "[^:()]*".to_owned(),
+                'l' => r"\d*".to_owned(),
+                't' => format!("{class}(?:\\[\\])*"),
+                _ => IDENTIFIER.to_owned(),
+            };
+            regex.push_str(&format!("(?P<{name}>{group})"));
+        }
+
+        Ok(Self {
+            regex: Regex::new(&regex)?,
+        })
+    }
+
+    /// Matches `line` against the pattern.
+    pub(crate) fn captures<'a>(&self, line: &'a str) -> Option<FrameCaptures<'a>> {
+        self.regex
+            .captures(line)
+            .map(|captures| FrameCaptures { line, captures })
+    }
+}
+
+/// The parts of a line captured by a [`FrameRegex`].
+pub(crate) struct FrameCaptures<'a> {
+    line: &'a str,
+    captures: Captures<'a>,
+}
+
+impl<'a> FrameCaptures<'a> {
+    fn get(&self, name: &str) -> Option<&'a str> {
+        self.captures.name(name).map(|m| m.as_str())
+    }
+
+    /// The captured class name.
+    pub(crate) fn class(&self) -> Option<&'a str> {
+        self.get("class")
+    }
+
+    /// The captured field name.
+    pub(crate) fn field(&self) -> Option<&'a str> {
+        self.get("field")
+    }
+
+    /// The captured type.
+    pub(crate) fn ty(&self) -> Option<&'a str> {
+        self.get("type")
+    }
+
+    /// The frame described by the line, if it captures a class and a method.
+    pub(crate) fn frame(&self) -> Option<StackFrame<'a>> {
+        let class = self.class()?;
+        let method = self.get("method")?;
+        let line = self.get("line").and_then(|line| line.parse().ok());
+        let file = self.get("file").filter(|file| !file.is_empty());
+
+        let mut frame = match file {
+            Some(file) => StackFrame::with_file(class, method, 0, file),
+            None => StackFrame::new(class, method, 0),
+        };
+        frame.line = line;
+        Some(frame)
+    }
+
+    /// Writes the line with the captured parts replaced by `replacements`.
+    fn write_replaced(&self, out: &mut impl Write, replacements: &[(&str, &str)]) -> FmtResult {
+        let mut ranges: Vec<(Range<usize>, &str)> = replacements
+            .iter()
+            .filter_map(|(name, replacement)| {
+                let range = self.captures.name(name)?.range();
+                Some((range, *replacement))
+            })
+            .collect();
+        ranges.sort_by_key(|(range, _)| range.start);
+
+        let mut last = 0;
+        for (range, replacement) in ranges {
+            out.write_str(&self.line[last..range.start])?;
+            out.write_str(replacement)?;
+            last = range.end;
+        }
+        writeln!(out, "{}", &self.line[last..])
+    }
+}
+
+/// Writes a line matched by a [`FrameRegex`] with its captured parts remapped.
+///
+/// `frames` are the remapped frames of the [captured frame](FrameCaptures::frame),
+/// one line is written for each of them. `class` is the remapped captured class,
+/// `ty` the remapped captured type and `field` the remapped captured field.
+pub(crate) fn format_captures(
+    out: &mut impl Write,
+    captures: &FrameCaptures<'_>,
+    frames: &[StackFrame<'_>],
+    class: Option<&str>,
+    ty: Option<&str>,
+    field: Option<&str>,
+) -> FmtResult {
+    let mut replacements = Vec::new();
+    if let Some(ty) = ty {
+        replacements.push(("type", ty));
+    }
+    if let Some(field) = field {
+        replacements.push(("field", field));
+    }
+
+    if frames.is_empty() {
+        if let Some(class) = class {
+            replacements.push(("class", class));
+        }
+        return captures.write_replaced(out, &replacements);
+    }
+
+    for frame in frames {
+        let line = frame.line.map(|line| line.to_string());
+        let mut replacements = replacements.clone();
+        replacements.push(("class", frame.class()));
+        replacements.push(("method", frame.method()));
+        if let Some(file) = frame.file() {
+            replacements.push(("file", file));
+        }
+        if let Some(line) = &line {
+            replacements.push(("line", line));
+        }
+        captures.write_replaced(out, &replacements)?;
+    }
+    Ok(())
+}
+
+/// Remaps the class of a captured type, keeping any array dimensions.
+pub(crate) fn remap_type<'m>(
+    ty: &str,
+    remap_class: impl FnOnce(&str) -> Option<&'m str>,
+) -> Option<String> {
+    let class = ty.trim_end_matches("[]");
+    let dimensions = &ty[class.len()..];
+    remap_class(class).map(|class| format!("{class}{dimensions}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders() {
+        let regex = FrameRegex::new(r"%t %c\.%f @ %c").unwrap_err();
+        assert!(matches!(regex, FrameRegexError::DuplicatePlaceholder('c')));
+
+        let regex = FrameRegex::new(r"^%c\.%m\(%s:%l\) 100%").unwrap();
+        let captures = regex.captures("a.b.<init>(SourceFile:12) 100%").unwrap();
+        assert_eq!(
+            captures.frame(),
+            Some(StackFrame::with_file("a.b", "<init>", 12, "SourceFile"))
+        );
+
+        let captures = regex.captures("a.b.c() 100%");
+        assert!(captures.is_none());
+    }
+
+    #[test]
+    fn replace() {
+        let regex = FrameRegex::new(r"%t %c\.%f").unwrap();
+        let captures = regex.captures("field: a[][] b.c = null").unwrap();
+        assert_eq!(captures.ty(), Some("a[][]"));
+
+        let mut out = String::new();
+        let ty = remap_type("a[][]", |_| Some("com.example.A"));
+        format_captures(
+            &mut out,
+            &captures,
+            &[],
+            Some("com.example.B"),
+            ty.as_deref(),
+            Some("field"),
+        )
+        .unwrap();
+        assert_eq!(out, "field: com.example.A[][] com.example.B.field = null\n");
+    }
+}
@@ -71,6 +71,53 @@ fn byte_code_type_to_java_type_cache(
     None
 }
 
+/// Appends the JVM descriptor of a Java type as written in a mapping, for example
+/// `[Ljava/lang/String;` for `java.lang.String[]`.
+///
+/// Class names are replaced by their obfuscated names if `obfuscate_class` knows them.
+fn java_type_to_byte_code_type<'a>(
+    java_type: &str,
+    obfuscate_class: &impl Fn(&str) -> Option<&'a str>,
+    descriptor: &mut String,
+) {
+    let mut ty = java_type.trim();
+    while let Some(element) = ty.strip_suffix("[]") {
+        descriptor.push('[');
+        ty = element;
+    }
+
+    match "ZBCSIJFDV"
+        .chars()
+        .find(|&c| java_base_types(c) == Some(ty))
+    {
+        Some(base_type) => descriptor.push(base_type),
+        None => {
+            let class = obfuscate_class(ty).unwrap_or(ty);
+            descriptor.push('L');
+            descriptor.push_str(&class.replace('.', "/"));
+            descriptor.push(';');
+        }
+    }
+}
+
+/// Returns the obfuscated JVM descriptor of a method, for example `(I)V`, from its
+/// original argument and return types as written in a mapping.
+///
+/// Class names are replaced by their obfuscated names if `obfuscate_class` knows them.
+pub(crate) fn obfuscated_method_descriptor<'a>(
+    arguments: &str,
+    return_type: &str,
+    obfuscate_class: impl Fn(&str) -> Option<&'a str>,
+) -> String {
+    let mut descriptor = String::from("(");
+    for argument in arguments.split(',').filter(|a| !a.trim().is_empty()) {
+        java_type_to_byte_code_type(argument, &obfuscate_class, &mut descriptor);
+    }
+    descriptor.push(')');
+    java_type_to_byte_code_type(return_type, &obfuscate_class, &mut descriptor);
+    descriptor
+}
+
 // parse_obfuscated_bytecode_signature will parse an obfuscated signatures into parameter
 // and return types that can be then deobfuscated
 fn parse_obfuscated_bytecode_signature(signature: &str) -> Option<(Vec<&str>, &str)> {
@@ -149,7 +196,10 @@ pub fn deobfuscate_bytecode_signature_cache(
 
 #[cfg(test)]
 mod tests {
-    use crate::{java::byte_code_type_to_java_type, ProguardMapper, ProguardMapping};
+    use crate::{
+        java::{byte_code_type_to_java_type, obfuscated_method_descriptor},
+        ProguardMapper, ProguardMapping,
+    };
     use std::collections::HashMap;
 
     #[test]
@@ -230,4 +280,38 @@ mod tests {
             assert!(signature.is_none());
         }
     }
+
+    #[test]
+    fn test_obfuscated_method_descriptor() {
+        let proguard_source = b"org.slf4j.helpers.Util$ClassContextSecurityManager -> org.a.b.g$a:
+    65:65:void <init>() -> <init>";
+
+        let mapping = ProguardMapping::new(proguard_source);
+        let mapper = ProguardMapper::new(mapping);
+
+        let tests = [
+            ("", "void", "()V"),
+            ("int", "void", "(I)V"),
+            ("int,int", "int", "(II)I"),
+            ("int[],long[][]", "byte[]", "([I[[J)[B"),
+            (
+                "java.lang.String,java.lang.Object[]",
+                "java.lang.String",
+                "(Ljava/lang/String;[Ljava/lang/Object;)Ljava/lang/String;",
+            ),
+            (
+                // Obfuscated class type
+                "org.slf4j.helpers.Util$ClassContextSecurityManager",
+                "void",
+                "(Lorg/a/b/g$a;)V",
+            ),
+        ];
+
+        for (arguments, return_type, expected) in tests {
+            let descriptor = obfuscated_method_descriptor(arguments, return_type, |class| {
+                mapper.obfuscate_class(class)
+            });
+            assert_eq!(descriptor, expected);
+        }
+    }
 }
@@ -5,6 +5,16 @@
 //!
 //! The `uuid` feature also allows getting the UUID of the proguard file.
 //!
+//! The `regex` feature allows remapping stack traces in custom formats, see
+//! `FrameRegex`.
+//!
+//! The `serde` feature implements `Serialize` and `Deserialize` for
+//! `StackTrace`, `StackFrame` and `Throwable`.
+//!
+//! A `ProguardMapper` borrows from the mapping text. `OwnedProguardMapper`
+//! owns it instead and can be shared across threads. `CacheRegistry` holds
+//! the caches of many mappings and remaps with the right one.
+//!
 //! # Examples
 //!
 //! ```
@@ -38,17 +48,40 @@
 
 mod builder;
 mod cache;
+mod compose;
+#[cfg(feature = "regex")]
+mod frame_regex;
 mod java;
+mod logcat;
+mod map_hash;
 mod mapper;
 mod mapping;
+mod message;
+mod options;
+mod owned;
+mod registry;
 mod stacktrace;
+mod thread_dump;
 mod utils;
+mod validate;
+mod writer;
 
+pub use builder::MergeError;
 pub use cache::{CacheError, CacheErrorKind, ProguardCache, PRGCACHE_VERSION};
+pub use compose::ComposeError;
+#[cfg(feature = "regex")]
+pub use frame_regex::{FrameRegex, FrameRegexError};
+pub use map_hash::MapHashError;
 pub use mapper::{DeobfuscatedSignature, ProguardMapper, RemappedFrameIter};
 pub use mapping::{
-    LineMapping, MappingSummary, ParseError, ParseErrorKind, ProguardMapping, ProguardRecord,
-    ProguardRecordIter,
+    LineMapping, MappingSummary, MappingVersion, ParseError, ParseErrorKind, ProguardMapping,
+    ProguardRecord, ProguardRecordIter, R8Header, SourcePosition,
 };
-pub use stacktrace::{StackFrame, StackTrace, Throwable};
+pub use options::RemapOptions;
+pub use owned::OwnedProguardMapper;
+pub use registry::{CacheRegistry, MappingKey, RegistryError};
+pub use stacktrace::{FileSource, FrameProvenance, StackFrame, StackTrace, Throwable};
+pub use thread_dump::{Monitor, Thread, ThreadDump, ThreadLine};
 pub use utils::class_name_to_descriptor;
+pub use validate::{Diagnostic, DiagnosticKind, Severity};
+pub use writer::MappingWriter;
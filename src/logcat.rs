@@ -0,0 +1,285 @@
+//! Detection of the prefixes `adb logcat` adds to every line.
+
+use std::fmt::{Result as FmtResult, Write};
+
+/// A line of `adb logcat` output.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct LogcatLine<'a> {
+    /// Everything before the logged message, including the separator.
+    pub(crate) prefix: &'a str,
+    /// The process (and thread) or the tag that logged the line.
+    ///
+    /// Lines of different streams may be interleaved.
+    pub(crate) stream: &'a str,
+    /// The logged message.
+    pub(crate) message: &'a str,
+}
+
+/// Splits a line of `adb logcat` output into its prefix and message.
+///
+/// This recognizes the `threadtime` (the default), `time`, `brief` and `tag`
+/// formats:
+///
+/// ```text
+/// 10-16 12:00:01.123  1234  1234 E AndroidRuntime: message
+/// 10-16 12:00:01.123 E/AndroidRuntime( 1234): message
+/// E/AndroidRuntime( 1234): message
+/// E/AndroidRuntime: message
+/// ```
+///
+/// The `long` format puts the metadata on a header line of its own, see
+/// [`LogcatReader`].
+pub(crate) fn parse_line(line: &str) -> Option<LogcatLine<'_>> {
+    let (stream, message) = match strip_date_time(line) {
+        Some(rest) => {
+            let rest = rest.trim_start();
+            parse_threadtime(rest).or_else(|| parse_brief(rest))?
+        }
+        None => parse_brief(line)?,
+    };
+
+    Some(LogcatLine {
+        prefix: &line[..line.len() - message.len()],
+        stream,
+        message,
+    })
+}
+
+/// Splits lines of `adb logcat` output, keeping track of the headers of the
+/// `long` format:
+///
+/// ```text
+/// [ 10-16 12:00:01.123  1234: 1234 E/AndroidRuntime ]
+/// message
+/// ```
+///
+/// The lines following such a header belong to the process and thread it
+/// names, until the next header.
+#[derive(Debug, Default)]
+pub(crate) struct LogcatReader<'a> {
+    long_stream: Option<&'a str>,
+}
+
+impl<'a> LogcatReader<'a> {
+    /// Splits `line` into its prefix and message.
+    ///
+    /// Returns `None` for lines that don't carry a message: the headers of the
+    /// `long` format and blank lines.
+    pub(crate) fn read_line(&mut self, line: &'a str) -> Option<LogcatLine<'a>> {
+        if let Some(stream) = parse_long_header(line) {
+            self.long_stream = Some(stream);
+            return None;
+        }
+        if line.trim().is_empty() {
+            return None;
+        }
+
+        Some(parse_line(line).unwrap_or(LogcatLine {
+            prefix: "",
+            stream: self.long_stream.unwrap_or(""),
+            message: line,
+        }))
+    }
+}
+
+/// Parses `[ 10-16 12:00:01.123  1234: 1234 E/AndroidRuntime ]` and returns
+/// the process and thread.
+fn parse_long_header(line: &str) -> Option<&str> {
+    let rest = line.trim_end().strip_prefix("[ ")?.strip_suffix(']')?;
+    let rest = strip_date_time(rest)?.trim_start();
+
+    let after_pid = strip_digits(rest)?.strip_prefix(':')?.trim_start();
+    let after_tid = strip_digits(after_pid)?;
+    let stream = &rest[..rest.len() - after_tid.len()];
+
+    let tag = strip_level(after_tid.strip_prefix(' ')?)?.strip_prefix('/')?;
+    (!tag.trim().is_empty()).then_some(stream)
+}
+
+/// Returns whether `message` is one of the lines Android logs before the
+/// stack trace of an uncaught exception.
+pub(crate) fn is_crash_header(message: &str) -> bool {
+    message.starts_with("FATAL EXCEPTION: ") || message.starts_with("Process: ")
+}
+
+fn strip_digits(s: &str) -> Option<&str> {
+    let rest = s.trim_start_matches(|c: char| c.is_ascii_digit());
+    (rest.len() < s.len()).then_some(rest)
+}
+
+/// Strips a date and time such as `10-16 12:00:01.123`, optionally with a year.
+fn strip_date_time(s: &str) -> Option<&str> {
+    let mut rest = strip_digits(s)?.strip_prefix('-')?;
+    rest = strip_digits(rest)?;
+    if let Some(day) = rest.strip_prefix('-') {
+        rest = strip_digits(day)?;
+    }
+    rest = rest.strip_prefix(' ')?;
+    rest = strip_digits(rest)?.strip_prefix(':')?;
+    rest = strip_digits(rest)?.strip_prefix(':')?;
+    rest = strip_digits(rest)?;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        rest = strip_digits(fraction)?;
+    }
+    rest.starts_with(' ').then_some(rest)
+}
+
+fn strip_level(s: &str) -> Option<&str> {
+    s.strip_prefix(['V', 'D', 'I', 'W', 'E', 'F', 'A', 'S'])
+}
+
+/// Splits the message from the tag and separator.
+fn strip_tag(s: &str) -> Option<(&str, &str)> {
+    let (tag, message) = s.split_once(':')?;
+    Some((tag, message.strip_prefix(' ').unwrap_or(message)))
+}
+
+/// Parses `1234  1234 E AndroidRuntime: message`.
+fn parse_threadtime(s: &str) -> Option<(&str, &str)> {
+    let rest = strip_digits(s)?.trim_start();
+    let rest = strip_digits(rest)?;
+    let stream = &s[..s.len() - rest.len()];
+
+    let rest = strip_level(rest.strip_prefix(' ')?.trim_start())?.strip_prefix(' ')?;
+    let (_, message) = strip_tag(rest)?;
+    Some((stream, message))
+}
+
+/// Parses `E/AndroidRuntime( 1234): message` and `E/AndroidRuntime: message`.
+fn parse_brief(s: &str) -> Option<(&str, &str)> {
+    let rest = strip_level(s)?.strip_prefix('/')?;
+    let (tag, message) = strip_tag(rest)?;
+
+    let (name, stream) = match tag.strip_suffix(')').and_then(|tag| tag.rsplit_once('(')) {
+        Some((name, pid)) => (name, pid.trim()),
+        None => (tag, tag.trim_end()),
+    };
+    let name = name.trim_end();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+
+    Some((stream, message))
+}
+
+/// A writer that starts every line with a `prefix`.
+pub(crate) struct PrefixedWriter<'a, W> {
+    inner: &'a mut W,
+    prefix: &'a str,
+    at_line_start: bool,
+}
+
+impl<'a, W: Write> PrefixedWriter<'a, W> {
+    pub(crate) fn new(inner: &'a mut W, prefix: &'a str) -> Self {
+        Self {
+            inner,
+            prefix,
+            at_line_start: true,
+        }
+    }
+}
+
+impl<W: Write> Write for PrefixedWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> FmtResult {
+        for line in s.split_inclusive('\n') {
+            if self.at_line_start {
+                self.inner.write_str(self.prefix)?;
+            }
+            self.inner.write_str(line)?;
+            self.at_line_start = line.ends_with('\n');
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats() {
+        let line = "10-16 12:00:01.123  1234  1235 E AndroidRuntime:     at a.b(SourceFile:1)";
+        assert_eq!(
+            parse_line(line),
+            Some(LogcatLine {
+                prefix: "10-16 12:00:01.123  1234  1235 E AndroidRuntime: ",
+                stream: "1234  1235",
+                message: "    at a.b(SourceFile:1)",
+            })
+        );
+
+        let line = "2024-10-16 12:00:01.123 E/AndroidRuntime( 1234): FATAL EXCEPTION: main";
+        assert_eq!(
+            parse_line(line),
+            Some(LogcatLine {
+                prefix: "2024-10-16 12:00:01.123 E/AndroidRuntime( 1234): ",
+                stream: "1234",
+                message: "FATAL EXCEPTION: main",
+            })
+        );
+
+        let line = "E/AndroidRuntime( 1234): Caused by: a: boom";
+        assert_eq!(parse_line(line).map(|line| line.stream), Some("1234"));
+        assert_eq!(
+            parse_line(line).map(|line| line.message),
+            Some("Caused by: a: boom")
+        );
+
+        let line = "W/System.err: java.lang.RuntimeException";
+        assert_eq!(
+            parse_line(line),
+            Some(LogcatLine {
+                prefix: "W/System.err: ",
+                stream: "System.err",
+                message: "java.lang.RuntimeException",
+            })
+        );
+    }
+
+    #[test]
+    fn not_logcat() {
+        for line in [
+            "java.lang.RuntimeException: boom",
+            "    at a.b(SourceFile:1)",
+            "I/O error: disk full",
+            "Caused by: a: boom",
+            "[ 10-16 12:00:01.123  1234: 1234 E/AndroidRuntime ]",
+        ] {
+            assert_eq!(parse_line(line), None, "{line}");
+        }
+    }
+
+    #[test]
+    fn long_format() {
+        let mut reader = LogcatReader::default();
+        assert_eq!(
+            reader.read_line("[ 10-16 12:00:01.123  1234: 1235 E/AndroidRuntime ]"),
+            None
+        );
+        assert_eq!(
+            reader.read_line("java.lang.RuntimeException"),
+            Some(LogcatLine {
+                prefix: "",
+                stream: "1234: 1235",
+                message: "java.lang.RuntimeException",
+            })
+        );
+        assert_eq!(reader.read_line(""), None);
+
+        assert_eq!(
+            parse_long_header("[ 2024-10-16 12:00:01.123  5678:0 W/System.err ]"),
+            Some("5678:0")
+        );
+        assert_eq!(parse_long_header("[ not a header ]"), None);
+    }
+
+    #[test]
+    fn prefixed_writer() {
+        let mut out = String::new();
+        let mut writer = PrefixedWriter::new(&mut out, "> ");
+        write!(writer, "a\nb").unwrap();
+        writeln!(writer, "c").unwrap();
+        writeln!(writer, "d").unwrap();
+        assert_eq!(out, "> a\n> bc\n> d\n");
+    }
+}
@@ -0,0 +1,150 @@
+//! Verification of the `pg_map_hash` header written by R8.
+
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::ProguardMapping;
+
+/// The header key of the map id.
+pub(crate) const MAP_ID_KEY: &str = "pg_map_id";
+/// The header key of the map hash.
+pub(crate) const MAP_HASH_KEY: &str = "pg_map_hash";
+/// The only hash algorithm R8 uses for the map hash.
+const SHA_256: &str = "SHA-256";
+
+/// Error when verifying the map hash of a [`ProguardMapping`].
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MapHashError<'s> {
+    /// The leading comments of the mapping don't contain a `pg_map_hash` header.
+    #[error("mapping does not contain a map hash")]
+    Missing,
+    /// The map hash uses an algorithm other than SHA-256.
+    #[error("unsupported map hash algorithm `{0}`")]
+    UnsupportedAlgorithm(&'s str),
+    /// The map hash does not match the contents of the mapping.
+    #[error("map hash `{expected}` does not match the computed hash `{computed}`")]
+    Mismatch {
+        /// The hash declared in the mapping.
+        expected: &'s str,
+        /// The hash of the contents of the mapping.
+        computed: String,
+    },
+}
+
+impl<'s> ProguardMapping<'s> {
+    /// Verifies the `pg_map_hash` header R8 writes at the top of the mapping.
+    ///
+    /// Like R8, this computes the SHA-256 hash of everything following the
+    /// header line and compares it with the declared hash. This detects
+    /// mappings that were truncated or edited after they were written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use proguard::{MapHashError, ProguardMapping};
+    ///
+    /// let mapping = ProguardMapping::new(b"# compiler: R8\na -> b:\n");
+    /// assert_eq!(mapping.verify_map_hash(), Err(MapHashError::Missing));
+    /// ```
+    pub fn verify_map_hash(&self) -> Result<(), MapHashError<'s>> {
+        let (declared, body) = self.map_hash().ok_or(MapHashError::Missing)?;
+
+        let (algorithm, expected) = declared.split_once(' ').unwrap_or((declared, ""));
+        if algorithm != SHA_256 {
+            return Err(MapHashError::UnsupportedAlgorithm(algorithm));
+        }
+        let expected = expected.trim();
+
+        let computed: String = Sha256::digest(body)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        if computed.eq_ignore_ascii_case(expected) {
+            Ok(())
+        } else {
+            Err(MapHashError::Mismatch { expected, computed })
+        }
+    }
+
+    /// Finds the value of the `pg_map_hash` header and the part of the
+    /// mapping it covers.
+    ///
+    /// Just like R8, only the comments at the top of the file are considered.
+    fn map_hash(&self) -> Option<(&'s str, &'s [u8])> {
+        let mut rest = self.source;
+        loop {
+            let end = rest.iter().position(|&b| b == b'\n')?;
+            let line = std::str::from_utf8(&rest[..end]).ok()?.trim();
+            rest = &rest[end + 1..];
+
+            if line.is_empty() {
+                continue;
+            }
+            let header = line.strip_prefix('#')?.trim_start();
+            if let Some(value) = header.strip_prefix(MAP_HASH_KEY) {
+                let value = value.trim_start().strip_prefix(':')?;
+                return Some((value.trim(), rest));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_hash(hash: &str, body: &str) -> String {
+        format!("# compiler: R8\n# pg_map_id: 0a1b2c3\n# pg_map_hash: {hash}\n{body}")
+    }
+
+    #[test]
+    fn verify() {
+        let body = "a -> b:\n    void foo() -> a\n";
+        let hash = "SHA-256 84faf71b52c893a25ffba356640fa4c78576416ea84c7aec5f81fde515de7290";
+        let mapping = with_hash(hash, body);
+        let mapping = ProguardMapping::new(mapping.as_bytes());
+        assert_eq!(mapping.verify_map_hash(), Ok(()));
+        assert_eq!(mapping.summary().map_id(), Some("0a1b2c3"));
+
+        let truncated = with_hash(hash, "a -> b:\n");
+        let truncated = ProguardMapping::new(truncated.as_bytes());
+        assert!(matches!(
+            truncated.verify_map_hash(),
+            Err(MapHashError::Mismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_r8_mapping() {
+        let source = include_str!("../tests/res/mapping-r8-symbolicated_file_names.txt");
+        let mapping = ProguardMapping::new(source.as_bytes());
+        assert_eq!(mapping.verify_map_hash(), Ok(()));
+
+        // Change the line `1:3:void <init>():3:3 -> <init>`.
+        let edited = source.replacen("1:3:void <init>():3:3", "1:3:void <init>():3:4", 1);
+        assert_ne!(edited, source);
+        let edited = ProguardMapping::new(edited.as_bytes());
+        assert!(matches!(
+            edited.verify_map_hash(),
+            Err(MapHashError::Mismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn unsupported() {
+        let mapping = with_hash("MD5 abc", "");
+        let mapping = ProguardMapping::new(mapping.as_bytes());
+        assert_eq!(
+            mapping.verify_map_hash(),
+            Err(MapHashError::UnsupportedAlgorithm("MD5"))
+        );
+    }
+
+    #[test]
+    fn only_leading_comments() {
+        let mapping = "a -> b:\n# pg_map_hash: SHA-256 abc\n";
+        let mapping = ProguardMapping::new(mapping.as_bytes());
+        assert_eq!(mapping.verify_map_hash(), Err(MapHashError::Missing));
+    }
+}
@@ -14,12 +14,21 @@ use std::iter::FusedIterator;
 const MAX_SPAN_EXPANSION: usize = 65_535;
 
 use crate::builder::{
-    Member, MethodReceiver, ParsedProguardMapping, RewriteAction, RewriteCondition, RewriteRule,
+    Member, MergeError, MethodReceiver, ParsedProguardMapping, RewriteAction, RewriteCondition,
+    RewriteRule,
 };
+#[cfg(feature = "regex")]
+use crate::frame_regex::{format_captures, remap_type, FrameCaptures};
 use crate::java;
+use crate::logcat::{self, LogcatReader, PrefixedWriter};
 use crate::mapping::ProguardMapping;
-use crate::stacktrace::{self, StackFrame, StackTrace, Throwable};
-use crate::utils::{class_name_to_descriptor, extract_class_name, synthesize_source_file};
+use crate::message;
+use crate::options::RemapOptions;
+use crate::stacktrace::{self, FileSource, FrameProvenance, StackFrame, StackTrace, Throwable};
+use crate::thread_dump::{self, ThreadDump};
+use crate::utils::{
+    class_name_to_descriptor, extract_class_name, minified_line, synthesize_source_file,
+};
 
 /// A deobfuscated method signature.
 pub struct DeobfuscatedSignature {
@@ -71,12 +80,16 @@ struct MemberMapping<'s> {
     original_class: Option<&'s str>,
     original_file: Option<&'s str>,
     original: &'s str,
+    arguments: &'s str,
+    return_type: &'s str,
     original_startline: Option<usize>,
     original_endline: Option<usize>,
     is_synthesized: bool,
     is_outline: bool,
     outline_callsite_positions: Option<HashMap<usize, usize>>,
     rewrite_rules: Vec<RewriteRule<'s>>,
+    /// The JVM descriptor of the minified method, if the mapping declares it.
+    residual_signature: Option<&'s str>,
     /// The source file of the outer class, used for synthesizing file names when
     /// the inlined method's class doesn't have its own sourceFile metadata.
     outer_source_file: Option<&'s str>,
@@ -87,12 +100,22 @@ struct ClassMembers<'s> {
     all_mappings: Vec<MemberMapping<'s>>,
     // method_params -> Vec[MemberMapping]
     mappings_by_params: HashMap<&'s str, Vec<MemberMapping<'s>>>,
+    // minified method signature -> Vec[MemberMapping], only if there is more than one
+    mappings_by_signature: HashMap<Cow<'s, str>, Vec<MemberMapping<'s>>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct FieldMapping<'s> {
+    original: &'s str,
+    ty: &'s str,
 }
 
 #[derive(Clone, Debug, Default)]
 struct ClassMapping<'s> {
     original: &'s str,
     members: HashMap<&'s str, ClassMembers<'s>>,
+    // obfuscated field name -> Vec[FieldMapping]
+    fields: HashMap<&'s str, Vec<FieldMapping<'s>>>,
     #[expect(
         unused,
         reason = "Class-level synthesized is propagated to members in resolve_mapping; \
@@ -104,9 +127,62 @@ struct ClassMapping<'s> {
 #[derive(Default)]
 struct CollectedFrames<'s> {
     frames: Vec<StackFrame<'s>>,
+    /// Whether the frame at the same index is the outermost frame of its inline chain.
+    outermost: Vec<bool>,
     rewrite_rules: Vec<&'s RewriteRule<'s>>,
 }
 
+impl<'s> CollectedFrames<'s> {
+    fn push(&mut self, frame: StackFrame<'s>, outermost: bool) {
+        self.frames.push(frame);
+        self.outermost.push(outermost);
+
+        if outermost {
+            // All frames pushed since the previous chain were inlined into this one.
+            let chain_len = 1 + self
+                .outermost
+                .iter()
+                .rev()
+                .skip(1)
+                .take_while(|o| !**o)
+                .count();
+            let start = self.frames.len() - chain_len;
+            for (depth, frame) in self.frames[start..].iter_mut().rev().enumerate() {
+                if let Some(provenance) = &mut frame.provenance {
+                    provenance.inline_depth = depth;
+                }
+            }
+        }
+    }
+
+    /// Marks the frames as remapped from `frame`, whose line was replaced with
+    /// the position of an outline callsite.
+    fn mark_outline_callsite(&mut self, frame: &StackFrame<'s>) {
+        for remapped in &mut self.frames {
+            if let Some(provenance) = &mut remapped.provenance {
+                provenance.minified_line = frame.line;
+                provenance.outline_callsite = true;
+            }
+        }
+    }
+
+    /// Drops synthesized frames in favor of non-synthesized ones, see [`prune_synthesized`].
+    fn prune_synthesized(&mut self) {
+        let mut frames: Vec<_> = self
+            .frames
+            .drain(..)
+            .zip(self.outermost.drain(..))
+            .collect();
+        prune_synthesized(&mut frames);
+        (self.frames, self.outermost) = frames.into_iter().unzip();
+    }
+
+    /// Splits the frames into their inline chains.
+    fn into_chains(self) -> Vec<Vec<StackFrame<'s>>> {
+        split_chains(self.frames.into_iter().zip(self.outermost))
+    }
+}
+
 type MemberIter<'m> = std::slice::Iter<'m, MemberMapping<'m>>;
 
 /// An Iterator over remapped StackFrames.
@@ -164,26 +240,38 @@ fn map_member_with_lines<'a>(
         member.original_startline.unwrap_or(0) + frame_line - member.startline.unwrap_or(0)
     };
 
-    let class = member.original_class.unwrap_or(frame.class);
+    let class = member
+        .original_class
+        .map(Cow::Borrowed)
+        .unwrap_or_else(|| frame.class.clone());
 
-    let file: Option<Cow<'a, str>> = if let Some(file_name) = member.original_file {
-        if file_name == "R8$$SyntheticClass" {
-            // Synthesize from class name for synthetic classes
-            extract_class_name(class).map(Cow::Borrowed)
+    let (file, file_source): (Option<Cow<'a, str>>, _) =
+        if let Some(file_name) = member.original_file {
+            if file_name == "R8$$SyntheticClass" {
+                // Synthesize from class name for synthetic classes
+                (
+                    extract_class_name(&class).map(|name| Cow::Owned(name.to_owned())),
+                    FileSource::Synthesized,
+                )
+            } else {
+                (Some(Cow::Borrowed(file_name)), FileSource::Mapping)
+            }
         } else {
-            Some(Cow::Borrowed(file_name))
-        }
-    } else {
-        // Synthesize from class name (input filename is not reliable)
-        synthesize_source_file(class, member.outer_source_file).map(Cow::Owned)
-    };
+            // Synthesize from class name (input filename is not reliable)
+            (
+                synthesize_source_file(&class, member.outer_source_file).map(Cow::Owned),
+                FileSource::Synthesized,
+            )
+        };
 
     Some(StackFrame {
         class,
-        method: member.original,
+        method: Cow::Borrowed(member.original),
+        provenance: remapped_provenance(frame, &file, file_source),
         file,
         line: Some(line),
-        parameters: frame.parameters,
+        parameters: frame.parameters.clone(),
+        signature: None,
         method_synthesized: member.is_synthesized,
     })
 }
@@ -197,31 +285,57 @@ fn map_member_without_lines<'a>(
     member: &MemberMapping<'a>,
     output_line: Option<usize>,
 ) -> StackFrame<'a> {
-    let class = member.original_class.unwrap_or(frame.class);
+    let class = member
+        .original_class
+        .map(Cow::Borrowed)
+        .unwrap_or_else(|| frame.class.clone());
     // Synthesize from class name (input filename is not reliable)
-    let file = synthesize_source_file(class, member.outer_source_file).map(Cow::Owned);
+    let file = synthesize_source_file(&class, member.outer_source_file).map(Cow::Owned);
     StackFrame {
         class,
-        method: member.original,
+        method: Cow::Borrowed(member.original),
+        provenance: remapped_provenance(frame, &file, FileSource::Synthesized),
         file,
         line: output_line,
-        parameters: frame.parameters,
+        parameters: frame.parameters.clone(),
+        signature: None,
         method_synthesized: member.is_synthesized,
     }
 }
 
 fn remap_class_only<'a>(frame: &StackFrame<'a>, reference_file: Option<&str>) -> StackFrame<'a> {
-    let file = synthesize_source_file(frame.class, reference_file).map(Cow::Owned);
+    let file = synthesize_source_file(&frame.class, reference_file).map(Cow::Owned);
+    let provenance = remapped_provenance(frame, &file, FileSource::Synthesized).map(|provenance| {
+        FrameProvenance {
+            class_only: true,
+            ..provenance
+        }
+    });
     StackFrame {
-        class: frame.class,
-        method: frame.method,
+        class: frame.class.clone(),
+        method: frame.method.clone(),
         file,
         line: Some(frame.line.unwrap_or(0)),
-        parameters: frame.parameters,
+        parameters: frame.parameters.clone(),
+        signature: None,
         method_synthesized: false,
+        provenance,
     }
 }
 
+/// Builds the provenance of a frame remapped from the prepared `frame`, which
+/// carries the provenance of the minified frame.
+pub(crate) fn remapped_provenance<'a>(
+    frame: &StackFrame<'a>,
+    file: &Option<Cow<'_, str>>,
+    file_source: FileSource,
+) -> Option<FrameProvenance<'a>> {
+    frame.provenance.clone().map(|provenance| FrameProvenance {
+        file_source: file.as_ref().map(|_| file_source),
+        ..provenance
+    })
+}
+
 fn apply_rewrite_rules<'s>(collected: &mut CollectedFrames<'s>, thrown_descriptor: Option<&str>) {
     if collected.frames.is_empty() {
         return;
@@ -239,11 +353,9 @@ fn apply_rewrite_rules<'s>(collected: &mut CollectedFrames<'s>, thrown_descripto
         for action in &rule.actions {
             match action {
                 RewriteAction::RemoveInnerFrames(count) => {
-                    if *count >= collected.frames.len() {
-                        collected.frames.clear();
-                    } else {
-                        collected.frames.drain(0..*count);
-                    }
+                    let count = (*count).min(collected.frames.len());
+                    collected.frames.drain(0..count);
+                    collected.outermost.drain(0..count);
                 }
                 RewriteAction::Unknown(_) => {}
             }
@@ -260,7 +372,7 @@ fn iterate_with_lines<'a>(
     has_line_info: bool,
 ) -> Option<StackFrame<'a>> {
     let frame_line = frame.line.unwrap_or(0);
-    for member in members {
+    while let Some(member) = members.next() {
         // If this method has line mappings, skip base (no-line) entries when we have a concrete line.
         if has_line_info && frame_line > 0 && member.endline.unwrap_or(0) == 0 {
             continue;
@@ -277,7 +389,18 @@ fn iterate_with_lines<'a>(
             };
             return Some(map_member_without_lines(frame, member, output_line));
         }
-        if let Some(mapped) = map_member_with_lines(frame, member) {
+        if let Some(mut mapped) = map_member_with_lines(frame, member) {
+            // Inlined methods share the minified range of the method they were
+            // inlined into, which always comes last.
+            if let Some(provenance) = &mut mapped.provenance {
+                provenance.inline_depth = members
+                    .as_slice()
+                    .iter()
+                    .take_while(|next| {
+                        next.startline == member.startline && next.endline == member.endline
+                    })
+                    .count();
+            }
             return Some(mapped);
         }
     }
@@ -341,11 +464,10 @@ fn resolve_no_line_frames<'s>(
     if first_group.len() > 1 {
         // Inline group: multiple entries share the same range.
         // Resolve each with its proper original line.
-        for member in &first_group {
+        for (idx, member) in first_group.iter().enumerate() {
             let line = member.original_startline.filter(|&v| v > 0).or(Some(0));
-            collected
-                .frames
-                .push(map_member_without_lines(frame, member, line));
+            let outermost = idx == first_group.len() - 1;
+            collected.push(map_member_without_lines(frame, member, line), outermost);
             collected.rewrite_rules.extend(member.rewrite_rules.iter());
         }
     } else {
@@ -353,15 +475,11 @@ fn resolve_no_line_frames<'s>(
         // frame with line 0, matching retrace behavior.
         let unambiguous = mapping_entries.iter().all(|m| m.original == first.original);
         if unambiguous {
-            collected
-                .frames
-                .push(map_member_without_lines(frame, first, Some(0)));
+            collected.push(map_member_without_lines(frame, first, Some(0)), true);
             collected.rewrite_rules.extend(first.rewrite_rules.iter());
         } else {
             for member in mapping_entries {
-                collected
-                    .frames
-                    .push(map_member_without_lines(frame, member, Some(0)));
+                collected.push(map_member_without_lines(frame, member, Some(0)), true);
                 collected.rewrite_rules.extend(member.rewrite_rules.iter());
             }
         }
@@ -424,9 +542,7 @@ fn resolve_base_entries<'s>(
             } else {
                 None
             };
-            collected
-                .frames
-                .push(map_member_without_lines(frame, member, line));
+            collected.push(map_member_without_lines(frame, member, line), true);
             collected.rewrite_rules.extend(member.rewrite_rules.iter());
         } else if all_no_range_same_name {
             if !no_range_emitted {
@@ -436,15 +552,11 @@ fn resolve_base_entries<'s>(
                 } else {
                     Some(0)
                 };
-                collected
-                    .frames
-                    .push(map_member_without_lines(frame, member, line));
+                collected.push(map_member_without_lines(frame, member, line), true);
                 collected.rewrite_rules.extend(member.rewrite_rules.iter());
             }
         } else {
-            collected
-                .frames
-                .push(map_member_without_lines(frame, member, Some(0)));
+            collected.push(map_member_without_lines(frame, member, Some(0)), true);
             collected.rewrite_rules.extend(member.rewrite_rules.iter());
         }
     }
@@ -452,7 +564,7 @@ fn resolve_base_entries<'s>(
     // Sort no-range frames by original method name when all have line mappings;
     // bare method entries preserve original mapping file order.
     if !all_no_range_same_name && all_no_range_have_line_mapping {
-        collected.frames.sort_by_key(|f| f.method);
+        collected.frames.sort_by(|a, b| a.method.cmp(&b.method));
     }
 }
 
@@ -463,6 +575,8 @@ fn resolve_base_entries<'s>(
 #[derive(Clone, Debug)]
 pub struct ProguardMapper<'s> {
     classes: HashMap<&'s str, ClassMapping<'s>>,
+    // original class name -> obfuscated class name
+    obfuscated_classes: HashMap<&'s str, &'s str>,
 }
 
 impl<'s> From<&'s str> for ProguardMapper<'s> {
@@ -495,12 +609,51 @@ impl<'s> ProguardMapper<'s> {
         Self::create_proguard_mapper(mapping, initialize_param_mapping)
     }
 
+    /// Create a new ProguardMapper from several mappings.
+    ///
+    /// This is useful for apps split into modules that are obfuscated together
+    /// but come with a mapping per module, like Android dynamic feature
+    /// modules. A class contained in several of the mappings is merged, but
+    /// mapping the same obfuscated class to different original classes is an
+    /// error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use proguard::{ProguardMapper, ProguardMapping};
+    ///
+    /// let base = ProguardMapping::new(b"com.example.Base -> a:");
+    /// let feature = ProguardMapping::new(b"com.example.Feature -> b:");
+    ///
+    /// let mapper = ProguardMapper::new_merged(&[base, feature], false).unwrap();
+    /// assert_eq!(mapper.remap_class("a"), Some("com.example.Base"));
+    /// assert_eq!(mapper.remap_class("b"), Some("com.example.Feature"));
+    ///
+    /// let conflicting = ProguardMapping::new(b"com.example.Other -> a:");
+    /// assert!(ProguardMapper::new_merged(&[base, conflicting], false).is_err());
+    /// ```
+    pub fn new_merged(
+        mappings: &[ProguardMapping<'s>],
+        initialize_param_mapping: bool,
+    ) -> Result<Self, MergeError<'s>> {
+        let parsed = ParsedProguardMapping::parse_merged(
+            mappings.iter().copied(),
+            initialize_param_mapping,
+        )?;
+        Ok(Self::from_parsed(parsed))
+    }
+
     fn create_proguard_mapper(
         mapping: ProguardMapping<'s>,
         initialize_param_mapping: bool,
     ) -> Self {
-        let parsed = ParsedProguardMapping::parse(mapping, initialize_param_mapping);
+        Self::from_parsed(ParsedProguardMapping::parse(
+            mapping,
+            initialize_param_mapping,
+        ))
+    }
 
+    fn from_parsed(parsed: ParsedProguardMapping<'s>) -> Self {
         // Initialize class mappings with obfuscated -> original name data. The mappings will be filled in afterwards.
         let mut class_mappings: HashMap<&str, ClassMapping<'s>> = parsed
             .class_names
@@ -554,8 +707,42 @@ impl<'s> ProguardMapper<'s> {
             }
         }
 
+        for ((obfuscated_class, obfuscated_field), fields) in &parsed.fields {
+            let class_mapping = class_mappings.entry(obfuscated_class.as_str()).or_default();
+            class_mapping.fields.insert(
+                obfuscated_field.as_str(),
+                fields
+                    .iter()
+                    .map(|field| FieldMapping {
+                        original: field.original.as_str(),
+                        ty: field.ty,
+                    })
+                    .collect(),
+            );
+        }
+
+        // Several obfuscated classes may map back to the same original class,
+        // the first one by obfuscated name wins.
+        let mut obfuscated_classes: HashMap<&str, &str> = HashMap::new();
+        for (obfuscated, class) in &class_mappings {
+            obfuscated_classes
+                .entry(class.original)
+                .and_modify(|winner| *winner = (*winner).min(*obfuscated))
+                .or_insert(*obfuscated);
+        }
+
+        for class_mapping in class_mappings.values_mut() {
+            for method_mappings in class_mapping.members.values_mut() {
+                method_mappings.mappings_by_signature =
+                    group_by_signature(&method_mappings.all_mappings, |class| {
+                        obfuscated_classes.get(class).copied()
+                    });
+            }
+        }
+
         Self {
             classes: class_mappings,
+            obfuscated_classes,
         }
     }
 
@@ -597,12 +784,15 @@ impl<'s> ProguardMapper<'s> {
             original_class,
             original_file,
             original: member.method.name.as_str(),
+            arguments: member.method.arguments,
+            return_type: member.return_type,
             original_startline: member.original_startline,
             original_endline: member.original_endline,
             is_synthesized,
             is_outline,
             outline_callsite_positions,
             rewrite_rules: member.rewrite_rules.clone(),
+            residual_signature: member.residual_signature,
             outer_source_file,
         }
     }
@@ -653,26 +843,29 @@ impl<'s> ProguardMapper<'s> {
             .is_some_and(|m| m.is_outline)
     }
 
-    /// Applies any carried outline position to the frame line and returns the adjusted frame.
+    /// Applies any carried outline position to the frame line and returns the adjusted frame,
+    /// together with whether an outline position was applied.
     fn prepare_frame_for_mapping<'a>(
         &self,
         frame: &StackFrame<'a>,
         carried_outline_pos: &mut Option<usize>,
-    ) -> StackFrame<'a> {
+    ) -> (StackFrame<'a>, bool) {
         let mut effective = frame.clone();
+        let mut outline_callsite = false;
         if let Some(pos) = carried_outline_pos.take() {
             if let Some(mapped) = self.map_outline_position(
-                effective.class,
-                effective.method,
+                &effective.class,
+                &effective.method,
                 effective.line.unwrap_or(0),
                 pos,
-                effective.parameters,
+                effective.parameters(),
             ) {
                 effective.line = Some(mapped);
+                outline_callsite = true;
             }
         }
 
-        effective
+        (effective, outline_callsite)
     }
 
     /// Remaps an obfuscated Class.
@@ -693,32 +886,190 @@ impl<'s> ProguardMapper<'s> {
         self.classes.get(class).map(|class| class.original)
     }
 
+    /// Obfuscates an original Class.
+    ///
+    /// This is the inverse of [`remap_class`](Self::remap_class) and works on the
+    /// fully-qualified name of the class, with its complete module prefix. If
+    /// several obfuscated classes map to the same original class, the first one
+    /// in lexicographic order is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mapping = r#"android.arch.core.executor.ArchTaskExecutor -> a.a.a.a.c:"#;
+    /// let mapper = proguard::ProguardMapper::from(mapping);
+    ///
+    /// let obfuscated = mapper.obfuscate_class("android.arch.core.executor.ArchTaskExecutor");
+    /// assert_eq!(obfuscated, Some("a.a.a.a.c"));
+    /// ```
+    pub fn obfuscate_class(&'s self, class: &str) -> Option<&'s str> {
+        self.obfuscated_classes.get(class).copied()
+    }
+
+    /// Obfuscates an original Class Method.
+    ///
+    /// The `class` argument has to be the fully-qualified original name of the
+    /// class, and `arguments` the argument string as written in the mapping file,
+    /// for example `"java.lang.Object,java.lang.Object"`.
+    ///
+    /// Mapping entries of methods inlined into other methods are not considered.
+    /// If the method can be resolved unambiguously, the obfuscated class and
+    /// method names are returned, otherwise `None` is being returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mapping = r#"
+    /// android.arch.core.executor.ArchTaskExecutor -> a.a.a.a.c:
+    ///     1:1:void executeOnDiskIO(java.lang.Runnable):96:96 -> a
+    ///     2:2:boolean isMainThread():116:116 -> a
+    /// "#;
+    /// let mapper = proguard::ProguardMapper::from(mapping);
+    ///
+    /// let obfuscated = mapper.obfuscate_method(
+    ///     "android.arch.core.executor.ArchTaskExecutor",
+    ///     "isMainThread",
+    ///     "",
+    /// );
+    /// assert_eq!(obfuscated, Some(("a.a.a.a.c", "a")));
+    /// ```
+    pub fn obfuscate_method(
+        &'s self,
+        class: &str,
+        method: &str,
+        arguments: &str,
+    ) -> Option<(&'s str, &'s str)> {
+        let obfuscated_class = self.obfuscate_class(class)?;
+        let class = self.classes.get(obfuscated_class)?;
+
+        let mut candidates = class.members.iter().filter_map(|(obfuscated, members)| {
+            let mappings = &members.all_mappings;
+            mappings
+                .iter()
+                .enumerate()
+                .any(|(idx, m)| {
+                    m.original_class.is_none()
+                        && m.original == method
+                        && m.arguments == arguments
+                        && is_outermost_mapping(mappings, idx)
+                })
+                .then_some(*obfuscated)
+        });
+        let first = candidates.next()?;
+
+        // Different obfuscated names for the same method can't be told apart.
+        candidates
+            .all(|obfuscated| obfuscated == first)
+            .then_some((obfuscated_class, first))
+    }
+
+    /// Obfuscates a single original Stackframe.
+    ///
+    /// This is the inverse of [`remap_frame`](Self::remap_frame): the frame's
+    /// class and method are looked up by their original names, and its line is
+    /// mapped back into the minified line ranges that produce it. Every
+    /// returned frame remaps to a chain that contains the given `frame`. This
+    /// chain also contains the methods inlined into it or the methods it was
+    /// inlined into, so `frame` is not necessarily its top frame.
+    ///
+    /// If the frame has [parameters](StackFrame::with_parameters), only methods
+    /// with a matching argument string are considered. If the frame has no
+    /// line, one frame without a line is returned per obfuscated method.
+    ///
+    /// The returned frames are sorted by method name and line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use proguard::{ProguardMapper, StackFrame};
+    ///
+    /// let mapping = r#"
+    /// android.arch.core.internal.SafeIterableMap -> a.a.a.b.c:
+    ///     13:13:java.util.Map$Entry eldest():168:168 -> a
+    /// "#;
+    /// let mapper = ProguardMapper::from(mapping);
+    ///
+    /// let frame = StackFrame::new("android.arch.core.internal.SafeIterableMap", "eldest", 168);
+    /// assert_eq!(
+    ///     mapper.obfuscate_frame(&frame),
+    ///     vec![StackFrame::new("a.a.a.b.c", "a", 13)],
+    /// );
+    /// ```
+    pub fn obfuscate_frame(&'s self, frame: &StackFrame<'_>) -> Vec<StackFrame<'s>> {
+        let mut frames = Vec::new();
+        let Some(obfuscated_class) = self.obfuscate_class(&frame.class) else {
+            return frames;
+        };
+        let Some(class) = self.classes.get(obfuscated_class) else {
+            return frames;
+        };
+
+        for (obfuscated, members) in &class.members {
+            for m in &members.all_mappings {
+                if m.original_class.is_some()
+                    || m.original != frame.method
+                    || frame.parameters().is_some_and(|p| p != m.arguments)
+                {
+                    continue;
+                }
+
+                let line = match frame.line {
+                    Some(line) => match minified_line(
+                        m.startline,
+                        m.endline,
+                        m.original_startline,
+                        m.original_endline,
+                        line,
+                    ) {
+                        Some(line) => Some(line),
+                        None => continue,
+                    },
+                    None => None,
+                };
+
+                frames.push(StackFrame {
+                    class: Cow::Borrowed(obfuscated_class),
+                    method: Cow::Borrowed(obfuscated),
+                    line,
+                    file: None,
+                    parameters: None,
+                    signature: None,
+                    method_synthesized: false,
+                    provenance: None,
+                });
+            }
+        }
+
+        frames.sort_by(|a, b| (&a.method, a.line).cmp(&(&b.method, b.line)));
+        frames.dedup();
+        frames
+    }
+
     fn collect_remapped_frames(&'s self, frame: &StackFrame<'s>) -> CollectedFrames<'s> {
         let mut collected = CollectedFrames::default();
-        let Some(class) = self.classes.get(frame.class) else {
+        let Some(class) = self.classes.get(frame.class()) else {
             return collected;
         };
 
         let mut frame = frame.clone();
-        frame.class = class.original;
+        frame.provenance = Some(FrameProvenance::new(&frame));
+        frame.class = Cow::Borrowed(class.original);
 
         // If we don't have any member mappings, we can still remap the class name.
         // This is especially important for stack frames where the method is not mapped or the
         // stacktrace does not contain sufficient information to resolve the method.
-        let Some(members) = class.members.get(frame.method) else {
-            collected
-                .frames
-                .push(remap_class_only(&frame, frame.file()));
+        let Some(members) = class.members.get(frame.method()) else {
+            collected.push(remap_class_only(&frame, frame.file()), true);
             return collected;
         };
 
-        let mapping_entries: &[MemberMapping<'s>] = if let Some(parameters) = frame.parameters {
+        let mapping_entries: &[MemberMapping<'s>] = if let Some(parameters) = frame.parameters() {
             let Some(typed_members) = members.mappings_by_params.get(parameters) else {
                 return collected;
             };
             typed_members.as_slice()
         } else {
-            members.all_mappings.as_slice()
+            narrow_by_signature(members, frame.signature())
         };
 
         if frame.parameters.is_none() {
@@ -739,7 +1090,7 @@ impl<'s> ProguardMapper<'s> {
             }
 
             // Frame has a line number > 0.
-            for member in mapping_entries {
+            for (idx, member) in mapping_entries.iter().enumerate() {
                 if has_line_info && frame_line > 0 && member.endline.unwrap_or(0) == 0 {
                     continue;
                 }
@@ -747,9 +1098,7 @@ impl<'s> ProguardMapper<'s> {
                     // No-range entry with frame_line > 0.
                     if member.original_startline.is_none() {
                         // Bare method mapping (no line info) — pass through frame line.
-                        collected
-                            .frames
-                            .push(map_member_without_lines(&frame, member, frame.line));
+                        collected.push(map_member_without_lines(&frame, member, frame.line), true);
                         collected.rewrite_rules.extend(member.rewrite_rules.iter());
                         continue;
                     }
@@ -759,11 +1108,10 @@ impl<'s> ProguardMapper<'s> {
                         let os = member.original_startline.unwrap_or(0);
                         if oe > os && (oe - os) <= MAX_SPAN_EXPANSION {
                             for line in os..=oe {
-                                collected.frames.push(map_member_without_lines(
-                                    &frame,
-                                    member,
-                                    Some(line),
-                                ));
+                                collected.push(
+                                    map_member_without_lines(&frame, member, Some(line)),
+                                    true,
+                                );
                             }
                             collected.rewrite_rules.extend(member.rewrite_rules.iter());
                             continue;
@@ -775,12 +1123,10 @@ impl<'s> ProguardMapper<'s> {
                     } else {
                         None
                     };
-                    collected
-                        .frames
-                        .push(map_member_without_lines(&frame, member, output_line));
+                    collected.push(map_member_without_lines(&frame, member, output_line), true);
                     collected.rewrite_rules.extend(member.rewrite_rules.iter());
                 } else if let Some(mapped) = map_member_with_lines(&frame, member) {
-                    collected.frames.push(mapped);
+                    collected.push(mapped, is_outermost_mapping(mapping_entries, idx));
                     collected.rewrite_rules.extend(member.rewrite_rules.iter());
                 }
             }
@@ -788,9 +1134,7 @@ impl<'s> ProguardMapper<'s> {
             // Outside-range fallback: if we had line mappings but nothing matched,
             // remap only the class name, keeping the obfuscated method name and original line.
             if collected.frames.is_empty() && has_line_info {
-                collected
-                    .frames
-                    .push(remap_class_only(&frame, frame.file()));
+                collected.push(remap_class_only(&frame, frame.file()), true);
             }
         } else {
             for member in mapping_entries {
@@ -801,7 +1145,7 @@ impl<'s> ProguardMapper<'s> {
                     None
                 };
                 let mapped = map_member_without_lines(&frame, member, output_line);
-                collected.frames.push(mapped);
+                collected.push(mapped, true);
                 collected.rewrite_rules.extend(member.rewrite_rules.iter());
             }
         }
@@ -835,31 +1179,113 @@ impl<'s> ProguardMapper<'s> {
         all_matching.then_some((class.original, first.original))
     }
 
+    /// Remaps an obfuscated Class Field.
+    ///
+    /// The `class` argument has to be the fully-qualified obfuscated name of the
+    /// class, with its complete module prefix.
+    ///
+    /// If the `field` can be resolved unambiguously, the remapped `class`,
+    /// the original field name and the field type will be returned,
+    /// otherwise `None` is being returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mapping = r#"
+    /// android.arch.core.executor.ArchTaskExecutor -> a.a.a.a.c:
+    ///     android.arch.core.executor.TaskExecutor mDelegate -> d
+    /// "#;
+    /// let mapper = proguard::ProguardMapper::from(mapping);
+    ///
+    /// let mapped = mapper.remap_field("a.a.a.a.c", "d");
+    /// assert_eq!(
+    ///     mapped,
+    ///     Some((
+    ///         "android.arch.core.executor.ArchTaskExecutor",
+    ///         "mDelegate",
+    ///         "android.arch.core.executor.TaskExecutor"
+    ///     ))
+    /// );
+    /// ```
+    pub fn remap_field(&'s self, class: &str, field: &str) -> Option<(&'s str, &'s str, &'s str)> {
+        let class = self.classes.get(class)?;
+        let mut fields = class.fields.get(field)?.iter();
+        let first = fields.next()?;
+
+        // Obfuscated field names are only unique together with their type,
+        // which we don't know here, so all candidates have to agree.
+        let all_matching = fields.all(|f| f == first);
+
+        all_matching.then_some((class.original, first.original, first.ty))
+    }
+
+    /// Remaps the obfuscated class, method and field names in an exception message.
+    ///
+    /// Names are only replaced if they can be remapped unambiguously, see
+    /// [`remap_class`](Self::remap_class), [`remap_method`](Self::remap_method)
+    /// and [`remap_field`](Self::remap_field). If nothing was remapped, the
+    /// `message` is returned as it is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mapping = r#"
+    /// com.example.Main -> a.b:
+    ///     void run() -> a
+    /// com.example.Other -> a.c:
+    /// "#;
+    /// let mapper = proguard::ProguardMapper::from(mapping);
+    ///
+    /// assert_eq!(
+    ///     mapper.remap_message("a.b cannot be cast to a.c"),
+    ///     "com.example.Main cannot be cast to com.example.Other"
+    /// );
+    /// assert_eq!(
+    ///     mapper.remap_message("Attempt to invoke virtual method 'void a.b.a()' on a null object reference"),
+    ///     "Attempt to invoke virtual method 'void com.example.Main.run()' on a null object reference"
+    /// );
+    /// ```
+    pub fn remap_message<'m>(&'s self, message: &'m str) -> Cow<'m, str> {
+        message::remap_message(
+            message,
+            |class| self.remap_class(class),
+            |class, member, is_method| {
+                if is_method {
+                    self.remap_method(class, member)
+                } else {
+                    self.remap_field(class, member)
+                        .map(|(class, field, _)| (class, field))
+                }
+            },
+        )
+    }
+
     /// Remaps a single Stackframe.
     ///
     /// Returns zero or more [`StackFrame`]s, based on the information in
     /// the proguard mapping. This can return more than one frame in the case
     /// of inlined functions. In that case, frames are sorted top to bottom.
     pub fn remap_frame(&'s self, frame: &StackFrame<'s>) -> RemappedFrameIter<'s> {
-        let Some(class) = self.classes.get(frame.class) else {
+        let Some(class) = self.classes.get(frame.class()) else {
             return RemappedFrameIter::empty();
         };
 
-        let Some(members) = class.members.get(frame.method) else {
+        let Some(members) = class.members.get(frame.method()) else {
             return RemappedFrameIter::empty();
         };
 
         let mut frame = frame.clone();
-        frame.class = class.original;
+        frame.provenance = Some(FrameProvenance::new(&frame));
+        frame.class = Cow::Borrowed(class.original);
 
-        let mappings = if let Some(parameters) = frame.parameters {
+        let mappings = if let Some(parameters) = frame.parameters() {
             if let Some(typed_members) = members.mappings_by_params.get(parameters) {
                 typed_members.iter()
             } else {
                 return RemappedFrameIter::empty();
             }
         } else {
-            members.all_mappings.iter()
+            narrow_by_signature(members, frame.signature()).iter()
         };
 
         let has_line_info = members
@@ -869,6 +1295,74 @@ impl<'s> ProguardMapper<'s> {
         RemappedFrameIter::members(frame, mappings, has_line_info)
     }
 
+    /// Remaps a single Stackframe into its alternative inline chains.
+    ///
+    /// Unlike [`remap_frame`](Self::remap_frame), which returns all candidates
+    /// as one flat sequence, this keeps apart frames that were inlined into
+    /// each other and frames that are alternatives to each other. Each element
+    /// of the result is one possible original call chain, sorted top to
+    /// bottom. The result contains more than one chain when the mapping is
+    /// ambiguous for the frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use proguard::{ProguardMapper, StackFrame};
+    ///
+    /// let mapper = ProguardMapper::from(
+    ///     "com.example.Main -> a:
+    ///     1:1:void inlined():10:10 -> a
+    ///     1:1:void foo():20:20 -> a
+    ///     2:2:void foo():21:21 -> a
+    ///     1:1:void bar():30:30 -> a",
+    /// );
+    ///
+    /// let chains = mapper.remap_frame_alternatives(&StackFrame::new("a", "a", 1));
+    /// let methods: Vec<Vec<_>> = chains
+    ///     .iter()
+    ///     .map(|chain| chain.iter().map(|frame| frame.method()).collect())
+    ///     .collect();
+    /// assert_eq!(methods, [vec!["inlined", "foo"], vec!["bar"]]);
+    /// ```
+    pub fn remap_frame_alternatives(&'s self, frame: &StackFrame<'s>) -> Vec<Vec<StackFrame<'s>>> {
+        self.collect_remapped_frames(frame).into_chains()
+    }
+
+    /// Remaps a single Stackframe like [`remap_frame`](Self::remap_frame),
+    /// using the given `options`.
+    ///
+    /// Of the `options`, only [`RemapOptions::prefer_non_synthesized`] applies
+    /// to single frames.
+    pub fn remap_frame_with_options(
+        &'s self,
+        frame: &StackFrame<'s>,
+        options: &RemapOptions,
+    ) -> Vec<StackFrame<'s>> {
+        let mut collected = self.collect_remapped_frames(frame);
+        if options.prefer_non_synthesized {
+            collected.prune_synthesized();
+        }
+        collected.frames
+    }
+
+    /// Remaps a single Stackframe into its alternative inline chains like
+    /// [`remap_frame_alternatives`](Self::remap_frame_alternatives), using the
+    /// given `options`.
+    ///
+    /// Of the `options`, only [`RemapOptions::prefer_non_synthesized`] applies
+    /// to single frames.
+    pub fn remap_frame_alternatives_with_options(
+        &'s self,
+        frame: &StackFrame<'s>,
+        options: &RemapOptions,
+    ) -> Vec<Vec<StackFrame<'s>>> {
+        let mut collected = self.collect_remapped_frames(frame);
+        if options.prefer_non_synthesized {
+            collected.prune_synthesized();
+        }
+        collected.into_chains()
+    }
+
     /// Remaps a throwable which is the first line of a full stacktrace.
     ///
     /// # Example
@@ -888,93 +1382,228 @@ impl<'s> ProguardMapper<'s> {
     /// );
     /// ```
     pub fn remap_throwable<'a>(&'a self, throwable: &Throwable<'a>) -> Option<Throwable<'a>> {
-        self.remap_class(throwable.class).map(|class| Throwable {
-            class,
-            message: throwable.message,
+        self.remap_class(&throwable.class).map(|class| Throwable {
+            class: Cow::Borrowed(class),
+            message: throwable.message.clone(),
+        })
+    }
+
+    /// Remaps a [`Throwable`] like [`remap_throwable`](Self::remap_throwable),
+    /// also remapping its message if the `options` ask for it.
+    fn remap_throwable_with_options<'a>(
+        &'a self,
+        throwable: &Throwable<'a>,
+        options: &RemapOptions,
+    ) -> Option<Throwable<'a>> {
+        let remapped = self.remap_throwable(throwable);
+        if !options.remap_messages {
+            return remapped;
+        }
+
+        let message = throwable.message.as_deref().map(|m| self.remap_message(m));
+        let Some(Cow::Owned(message)) = message else {
+            return remapped;
+        };
+        Some(Throwable {
+            class: remapped.map_or_else(|| throwable.class.clone(), |t| t.class),
+            message: Some(Cow::Owned(message)),
         })
     }
 
     /// Remaps a complete Java StackTrace, similar to [`Self::remap_stacktrace_typed`] but instead works on
     /// strings as input and output.
+    ///
+    /// Lines of `adb logcat` output are recognized by their prefix, which is
+    /// kept as it is while the rest of the line is remapped. In the `long`
+    /// format, the lines following a header are attributed to the process and
+    /// thread it names. Lines logged by different processes may be interleaved.
     pub fn remap_stacktrace(&self, input: &str) -> Result<String, std::fmt::Error> {
+        self.remap_stacktrace_with_options(input, &RemapOptions::default())
+    }
+
+    /// Remaps a complete Java StackTrace like [`remap_stacktrace`](Self::remap_stacktrace),
+    /// using the given `options`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use proguard::{ProguardMapper, RemapOptions};
+    ///
+    /// let mapper = ProguardMapper::from(
+    ///     "com.example.Main -> a:
+    ///     void foo(int) -> a
+    ///     void bar(int,int) -> a",
+    /// );
+    ///
+    /// let options = RemapOptions::new().mark_alternatives(true);
+    /// let remapped = mapper
+    ///     .remap_stacktrace_with_options("\tat a.a(Unknown Source)", &options)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     remapped,
+    ///     "\tat com.example.Main.bar(Main.java)\n\t<OR> at com.example.Main.foo(Main.java)\n"
+    /// );
+    /// ```
+    pub fn remap_stacktrace_with_options(
+        &self,
+        input: &str,
+        options: &RemapOptions,
+    ) -> Result<String, std::fmt::Error> {
         let mut stacktrace = String::new();
-        let mut carried_outline_pos: Option<usize> = None;
-        let mut current_exception_descriptor: Option<String> = None;
-        let mut next_frame_can_rewrite = false;
+        let mut streams: HashMap<&str, StreamState> = HashMap::new();
+        let mut reader = LogcatReader::default();
 
         for line in input.lines() {
+            let Some(logcat) = reader.read_line(line) else {
+                writeln!(stacktrace, "{line}")?;
+                continue;
+            };
+            let state = streams.entry(logcat.stream).or_default();
+            let line = logcat.message;
+            let mut out = PrefixedWriter::new(&mut stacktrace, logcat.prefix);
+
+            #[cfg(feature = "regex")]
+            if let Some(captures) = options
+                .frame_regex
+                .as_ref()
+                .and_then(|regex| regex.captures(line))
+            {
+                state.current_exception_descriptor = None;
+                state.next_frame_can_rewrite = false;
+                self.format_regex_captures(&mut out, &captures, options)?;
+                continue;
+            }
+
+            if logcat::is_crash_header(line) {
+                state.current_exception_descriptor = None;
+                state.next_frame_can_rewrite = false;
+                writeln!(out, "{line}")?;
+                continue;
+            }
+
+            if let Some((caption, cause)) = split_caption(line) {
+                let remapped_cause = self.remap_throwable_with_options(&cause, options);
+                let descriptor_class = remapped_cause.as_ref().map_or(cause.class(), |t| t.class());
+                state.current_exception_descriptor =
+                    Some(class_name_to_descriptor(descriptor_class));
+                state.next_frame_can_rewrite = true;
+                format_cause(&mut out, line, caption, remapped_cause)?;
+                continue;
+            }
+
             if let Some(throwable) = stacktrace::parse_throwable(line) {
-                let remapped_throwable = self.remap_throwable(&throwable);
+                let remapped_throwable = self.remap_throwable_with_options(&throwable, options);
                 let descriptor_class = remapped_throwable
                     .as_ref()
-                    .map(|t| t.class)
-                    .unwrap_or(throwable.class);
-                current_exception_descriptor = Some(class_name_to_descriptor(descriptor_class));
-                next_frame_can_rewrite = true;
-                format_throwable(&mut stacktrace, line, remapped_throwable)?;
+                    .map_or(throwable.class(), |t| t.class());
+                state.current_exception_descriptor =
+                    Some(class_name_to_descriptor(descriptor_class));
+                state.next_frame_can_rewrite = true;
+                format_throwable(&mut out, line, remapped_throwable)?;
                 continue;
             }
 
             if let Some(frame) = stacktrace::parse_frame(line) {
-                if self.is_outline_frame(frame.class, frame.method) {
-                    carried_outline_pos = Some(frame.line.unwrap_or(0));
+                if self.is_outline_frame(&frame.class, &frame.method) {
+                    state.carried_outline_pos = Some(frame.line.unwrap_or(0));
                     continue;
                 }
 
-                let effective_frame =
-                    self.prepare_frame_for_mapping(&frame, &mut carried_outline_pos);
+                let (effective_frame, outline_callsite) =
+                    self.prepare_frame_for_mapping(&frame, &mut state.carried_outline_pos);
 
                 let mut collected = self.collect_remapped_frames(&effective_frame);
+                if outline_callsite {
+                    collected.mark_outline_callsite(&frame);
+                }
                 let had_frames = !collected.frames.is_empty();
-                if next_frame_can_rewrite {
-                    apply_rewrite_rules(&mut collected, current_exception_descriptor.as_deref());
+                if state.next_frame_can_rewrite {
+                    apply_rewrite_rules(
+                        &mut collected,
+                        state.current_exception_descriptor.as_deref(),
+                    );
+                }
+                if options.prefer_non_synthesized {
+                    collected.prune_synthesized();
                 }
 
-                next_frame_can_rewrite = false;
-                current_exception_descriptor = None;
+                state.next_frame_can_rewrite = false;
+                state.current_exception_descriptor = None;
 
                 // If rewrite rules cleared all frames, skip entirely
                 if had_frames && collected.frames.is_empty() {
                     continue;
                 }
 
-                format_frames(&mut stacktrace, line, collected.frames.into_iter())?;
-                continue;
-            }
-
-            if let Some(cause) = line
-                .strip_prefix("Caused by: ")
-                .and_then(stacktrace::parse_throwable)
-            {
-                let remapped_cause = self.remap_throwable(&cause);
-                let descriptor_class = remapped_cause
-                    .as_ref()
-                    .map(|t| t.class)
-                    .unwrap_or(cause.class);
-                current_exception_descriptor = Some(class_name_to_descriptor(descriptor_class));
-                next_frame_can_rewrite = true;
-                format_cause(&mut stacktrace, line, remapped_cause)?;
+                format_frames(
+                    &mut out,
+                    line,
+                    collected.frames.into_iter().zip(collected.outermost),
+                    options,
+                )?;
                 continue;
             }
 
-            current_exception_descriptor = None;
-            next_frame_can_rewrite = false;
-            writeln!(&mut stacktrace, "{line}")?;
+            state.current_exception_descriptor = None;
+            state.next_frame_can_rewrite = false;
+            writeln!(out, "{line}")?;
         }
         Ok(stacktrace)
     }
 
+    /// Writes a line matched by a [`FrameRegex`](crate::FrameRegex) with its captured parts remapped.
+    #[cfg(feature = "regex")]
+    fn format_regex_captures(
+        &self,
+        out: &mut impl Write,
+        captures: &FrameCaptures<'_>,
+        options: &RemapOptions,
+    ) -> Result<(), FmtError> {
+        let frames = match captures.frame() {
+            Some(frame) => {
+                let mut collected = self.collect_remapped_frames(&frame);
+                if options.prefer_non_synthesized {
+                    collected.prune_synthesized();
+                }
+                collected.frames
+            }
+            None => Vec::new(),
+        };
+        let class = captures.class().and_then(|class| self.remap_class(class));
+        let ty = captures
+            .ty()
+            .and_then(|ty| remap_type(ty, |class| self.remap_class(class)));
+        let field = captures
+            .class()
+            .zip(captures.field())
+            .and_then(|(class, field)| self.remap_field(class, field))
+            .map(|(_, field, _)| field);
+
+        format_captures(out, captures, &frames, class, ty.as_deref(), field)
+    }
+
     /// Remaps a complete Java StackTrace.
+    ///
+    /// The `trace` doesn't need to borrow from the same data as `self`: an
+    /// owned trace created with [`StackTrace::into_owned`] can be remapped, and
+    /// the result can be detached from `self` the same way.
     pub fn remap_stacktrace_typed<'a>(&'a self, trace: &StackTrace<'a>) -> StackTrace<'a> {
+        self.remap_stacktrace_typed_with_options(trace, &RemapOptions::default())
+    }
+
+    /// Remaps a complete Java StackTrace like [`remap_stacktrace_typed`](Self::remap_stacktrace_typed),
+    /// using the given `options`.
+    pub fn remap_stacktrace_typed_with_options<'a>(
+        &'a self,
+        trace: &StackTrace<'a>,
+        options: &RemapOptions,
+    ) -> StackTrace<'a> {
         let exception = trace
             .exception
             .as_ref()
-            .and_then(|t| self.remap_throwable(t));
+            .and_then(|t| self.remap_throwable_with_options(t, options));
         let exception_descriptor = trace.exception.as_ref().map(|original| {
-            let class = exception
-                .as_ref()
-                .map(|t| t.class)
-                .unwrap_or(original.class);
+            let class = exception.as_ref().map_or(original.class(), |t| t.class());
             class_name_to_descriptor(class)
         });
 
@@ -982,17 +1611,24 @@ impl<'s> ProguardMapper<'s> {
         let mut frames_out = Vec::with_capacity(trace.frames.len());
         let mut next_frame_can_rewrite = exception_descriptor.is_some();
         for f in trace.frames.iter() {
-            if self.is_outline_frame(f.class, f.method) {
+            if self.is_outline_frame(&f.class, &f.method) {
                 carried_outline_pos = Some(f.line.unwrap_or(0));
                 continue;
             }
 
-            let effective = self.prepare_frame_for_mapping(f, &mut carried_outline_pos);
+            let (effective, outline_callsite) =
+                self.prepare_frame_for_mapping(f, &mut carried_outline_pos);
             let mut collected = self.collect_remapped_frames(&effective);
+            if outline_callsite {
+                collected.mark_outline_callsite(f);
+            }
             let had_frames = !collected.frames.is_empty();
             if next_frame_can_rewrite {
                 apply_rewrite_rules(&mut collected, exception_descriptor.as_deref());
             }
+            if options.prefer_non_synthesized {
+                collected.prune_synthesized();
+            }
             next_frame_can_rewrite = false;
 
             // If rewrite rules cleared all frames, skip entirely
@@ -1010,14 +1646,152 @@ impl<'s> ProguardMapper<'s> {
         let cause = trace
             .cause
             .as_ref()
-            .map(|c| Box::new(self.remap_stacktrace_typed(c)));
+            .map(|c| Box::new(self.remap_stacktrace_typed_with_options(c, options)));
+        let suppressed = trace
+            .suppressed
+            .iter()
+            .map(|s| self.remap_stacktrace_typed_with_options(s, options))
+            .collect();
 
         StackTrace {
             exception,
             frames: frames_out,
             cause,
+            suppressed,
+            elided_frames: trace.elided_frames,
+        }
+    }
+
+    /// Remaps the frames of all threads in a [`ThreadDump`], as well as the
+    /// classes of the monitors they hold or wait for.
+    pub fn remap_thread_dump<'a>(&'a self, dump: &ThreadDump<'a>) -> ThreadDump<'a> {
+        thread_dump::remap_thread_dump(
+            dump,
+            |trace| self.remap_stacktrace_typed(trace),
+            |class| self.remap_class(class),
+        )
+    }
+}
+
+/// Narrows the mappings of `members` down to the ones of the minified method
+/// with the given signature.
+///
+/// If no minified method has the signature, all mappings are returned.
+fn narrow_by_signature<'a, 's>(
+    members: &'a ClassMembers<'s>,
+    signature: Option<&str>,
+) -> &'a [MemberMapping<'s>] {
+    signature
+        .and_then(|signature| members.mappings_by_signature.get(signature))
+        .unwrap_or(&members.all_mappings)
+}
+
+/// Groups `mappings` by the signature of the minified method they belong to.
+///
+/// Each inline group belongs to the minified method of its outermost mapping.
+/// Its signature is the residual signature if the mapping declares one, and is
+/// derived from the original types of the outermost method otherwise. If all
+/// mappings have the same signature, there is nothing to narrow down and the
+/// result is empty.
+fn group_by_signature<'s>(
+    mappings: &[MemberMapping<'s>],
+    obfuscate_class: impl Fn(&str) -> Option<&'s str>,
+) -> HashMap<Cow<'s, str>, Vec<MemberMapping<'s>>> {
+    let mut groups: HashMap<Cow<'s, str>, Vec<MemberMapping<'s>>> = HashMap::new();
+    let mut start = 0;
+    for (idx, outermost) in mappings.iter().enumerate() {
+        if !is_outermost_mapping(mappings, idx) {
+            continue;
+        }
+        let signature = match outermost.residual_signature {
+            Some(signature) => Cow::Borrowed(signature),
+            None => Cow::Owned(java::obfuscated_method_descriptor(
+                outermost.arguments,
+                outermost.return_type,
+                &obfuscate_class,
+            )),
+        };
+        groups
+            .entry(signature)
+            .or_default()
+            .extend_from_slice(&mappings[start..=idx]);
+        start = idx + 1;
+    }
+
+    if groups.len() < 2 {
+        groups.clear();
+    }
+    groups
+}
+
+/// Whether the mapping at `idx` is the outermost frame of its inline group.
+///
+/// Inlined methods share the minified range of the method they were inlined
+/// into, which always comes last.
+fn is_outermost_mapping(mappings: &[MemberMapping<'_>], idx: usize) -> bool {
+    let member = &mappings[idx];
+    member.endline.unwrap_or(0) == 0
+        || mappings
+            .get(idx + 1)
+            .is_none_or(|next| next.startline != member.startline || next.endline != member.endline)
+}
+
+/// Drops the synthesized frames among the remapped `frames` of a single frame,
+/// unless all of them are synthesized. This matches R8's retrace, which hides
+/// compiler-generated frames such as lambda bridges when it can.
+///
+/// The frames are given together with whether they are the outermost frame of
+/// their inline chain. When the outermost frame of a chain is dropped, the
+/// frame inlined into it becomes the new outermost frame.
+pub(crate) fn prune_synthesized(frames: &mut Vec<(StackFrame<'_>, bool)>) {
+    if frames.iter().all(|(frame, _)| frame.method_synthesized) {
+        return;
+    }
+
+    let mut pruned: Vec<(StackFrame<'_>, bool)> = Vec::with_capacity(frames.len());
+    for (frame, outermost) in frames.drain(..) {
+        if !frame.method_synthesized {
+            pruned.push((frame, outermost));
+        } else if outermost {
+            // A kept frame that isn't outermost belongs to the dropped frame's chain.
+            if let Some((_, last_outermost)) = pruned.last_mut() {
+                *last_outermost = true;
+            }
         }
     }
+    *frames = pruned;
+}
+
+/// Splits remapped frames into inline chains, given whether each frame is the
+/// outermost frame of its chain.
+///
+/// Frames following the last outermost frame form a chain of their own.
+pub(crate) fn split_chains<'s>(
+    frames: impl IntoIterator<Item = (StackFrame<'s>, bool)>,
+) -> Vec<Vec<StackFrame<'s>>> {
+    let mut chains = Vec::new();
+    let mut chain = Vec::new();
+    for (frame, outermost) in frames {
+        chain.push(frame);
+        if outermost {
+            chains.push(std::mem::take(&mut chain));
+        }
+    }
+    if !chain.is_empty() {
+        chains.push(chain);
+    }
+    chains
+}
+
+/// The state of remapping a stack trace that carries over from one line to the next.
+///
+/// This is kept per `adb logcat` stream, since lines logged by different
+/// processes may be interleaved.
+#[derive(Default)]
+pub(crate) struct StreamState {
+    pub(crate) carried_outline_pos: Option<usize>,
+    pub(crate) current_exception_descriptor: Option<String>,
+    pub(crate) next_frame_can_rewrite: bool,
 }
 
 pub(crate) fn format_throwable(
@@ -1032,30 +1806,74 @@ pub(crate) fn format_throwable(
     }
 }
 
+/// Writes the remapped frames of an input `line`, given together with whether
+/// they are the outermost frame of their inline chain.
 pub(crate) fn format_frames<'s>(
     stacktrace: &mut impl Write,
     line: &str,
-    remapped: impl Iterator<Item = StackFrame<'s>>,
+    remapped: impl Iterator<Item = (StackFrame<'s>, bool)>,
+    options: &RemapOptions,
 ) -> Result<(), FmtError> {
     let mut remapped = remapped.peekable();
 
     if remapped.peek().is_none() {
         return writeln!(stacktrace, "{line}");
     }
-    for line in remapped {
-        writeln!(stacktrace, "    {line}")?;
+
+    if !options.mark_alternatives {
+        for (frame, _) in remapped {
+            writeln!(stacktrace, "    {frame}")?;
+        }
+        return Ok(());
+    }
+
+    // Like R8's retrace, sort the alternatives by their top frame, keep the
+    // indentation of the input line, mark the start of every alternative
+    // after the first one, and leave out unknown lines.
+    fn top_frame<'a>(
+        chain: &'a [StackFrame<'_>],
+    ) -> Option<(&'a str, &'a str, Option<&'a str>, Option<usize>)> {
+        let frame = chain.first()?;
+        Some((frame.class(), frame.method(), frame.file(), frame.line))
+    }
+    let mut chains = split_chains(remapped);
+    chains.sort_by(|a, b| top_frame(a).cmp(&top_frame(b)));
+
+    let indent = &line[..line.len() - line.trim_start().len()];
+    for (idx, chain) in chains.into_iter().enumerate() {
+        for (position, mut frame) in chain.into_iter().enumerate() {
+            frame.line = frame.line.filter(|&line| line > 0);
+            let marker = if idx > 0 && position == 0 {
+                "<OR> "
+            } else {
+                ""
+            };
+            writeln!(stacktrace, "{indent}{marker}{frame}")?;
+        }
     }
 
     Ok(())
 }
 
+/// Splits a line such as `Caused by: a.b: boom` or `\tSuppressed: a.b: boom`
+/// into its caption, including the indentation, and the exception.
+pub(crate) fn split_caption(line: &str) -> Option<(&str, Throwable<'_>)> {
+    let trimmed = line.trim_start();
+    let exception = trimmed
+        .strip_prefix("Caused by: ")
+        .or_else(|| trimmed.strip_prefix("Suppressed: "))?;
+    let caption = &line[..line.len() - exception.len()];
+    Some((caption, stacktrace::parse_throwable(exception)?))
+}
+
 pub(crate) fn format_cause(
     stacktrace: &mut impl Write,
     line: &str,
+    caption: &str,
     cause: Option<Throwable<'_>>,
 ) -> Result<(), FmtError> {
     if let Some(cause) = cause {
-        writeln!(stacktrace, "Caused by: {cause}")
+        writeln!(stacktrace, "{caption}{cause}")
     } else {
         writeln!(stacktrace, "{line}")
     }
@@ -1079,42 +1897,52 @@ com.example.MainFragment$onActivityCreated$4 -> com.example.MainFragment$g:
     ";
         let stacktrace = StackTrace {
             exception: Some(Throwable {
-                class: "com.example.MainFragment$e",
-                message: Some("Crash!"),
+                class: "com.example.MainFragment$e".into(),
+                message: Some("Crash!".into()),
             }),
             frames: vec![
                 StackFrame {
-                    class: "com.example.MainFragment$g",
-                    method: "onClick",
+                    class: "com.example.MainFragment$g".into(),
+                    method: "onClick".into(),
                     line: Some(2),
                     file: Some(Cow::Borrowed("SourceFile")),
                     parameters: None,
+                    signature: None,
                     method_synthesized: false,
+                    provenance: None,
                 },
                 StackFrame {
-                    class: "android.view.View",
-                    method: "performClick",
+                    class: "android.view.View".into(),
+                    method: "performClick".into(),
                     line: Some(7393),
                     file: Some(Cow::Borrowed("View.java")),
                     parameters: None,
+                    signature: None,
                     method_synthesized: false,
+                    provenance: None,
                 },
             ],
             cause: Some(Box::new(StackTrace {
                 exception: Some(Throwable {
-                    class: "com.example.MainFragment$d",
-                    message: Some("Engines overheating"),
+                    class: "com.example.MainFragment$d".into(),
+                    message: Some("Engines overheating".into()),
                 }),
                 frames: vec![StackFrame {
-                    class: "com.example.MainFragment$g",
-                    method: "onClick",
+                    class: "com.example.MainFragment$g".into(),
+                    method: "onClick".into(),
                     line: Some(1),
                     file: Some(Cow::Borrowed("SourceFile")),
                     parameters: None,
+                    signature: None,
                     method_synthesized: false,
+                    provenance: None,
                 }],
                 cause: None,
+                suppressed: vec![],
+                elided_frames: 0,
             })),
+            suppressed: vec![],
+            elided_frames: 0,
         };
         let expect = "\
 com.example.MainFragment$RocketException: Crash!
@@ -1222,18 +2050,22 @@ some.Class -> a:
 ";
         let trace = StackTrace {
             exception: Some(Throwable {
-                class: "java.lang.NullPointerException",
-                message: Some("Boom"),
+                class: "java.lang.NullPointerException".into(),
+                message: Some("Boom".into()),
             }),
             frames: vec![StackFrame {
-                class: "a",
-                method: "a",
+                class: "a".into(),
+                method: "a".into(),
                 line: Some(4),
                 file: Some(Cow::Borrowed("SourceFile")),
                 parameters: None,
+                signature: None,
                 method_synthesized: false,
+                provenance: None,
             }],
             cause: None,
+            suppressed: vec![],
+            elided_frames: 0,
         };
 
         let mapper = ProguardMapper::from(mapping);
@@ -1342,28 +2174,34 @@ some.Other -> b:
 
         let trace = StackTrace {
             exception: Some(Throwable {
-                class: "java.lang.NullPointerException",
-                message: Some("Boom"),
+                class: "java.lang.NullPointerException".into(),
+                message: Some("Boom".into()),
             }),
             frames: vec![
                 StackFrame {
-                    class: "a",
-                    method: "call",
+                    class: "a".into(),
+                    method: "call".into(),
                     line: Some(4),
                     file: Some(Cow::Borrowed("SourceFile")),
                     parameters: None,
+                    signature: None,
                     method_synthesized: false,
+                    provenance: None,
                 },
                 StackFrame {
-                    class: "b",
-                    method: "run",
+                    class: "b".into(),
+                    method: "run".into(),
                     line: Some(5),
                     file: Some(Cow::Borrowed("SourceFile")),
                     parameters: None,
+                    signature: None,
                     method_synthesized: false,
+                    provenance: None,
                 },
             ],
             cause: None,
+            suppressed: vec![],
+            elided_frames: 0,
         };
 
         let remapped = mapper.remap_stacktrace_typed(&trace);
@@ -1375,4 +2213,43 @@ some.Other -> b:
         assert_eq!(remapped.frames[0].method, "method");
         assert_eq!(remapped.frames[0].line, Some(30));
     }
+
+    #[test]
+    fn prune_synthesized_frames() {
+        let inlined = StackFrame::new("a.A", "inlined", 1);
+        let bridge = StackFrame::new("a.A$$Lambda", "run", 0).with_method_synthesized(true);
+        let other = StackFrame::new("a.B", "other", 2);
+
+        // The frame inlined into the dropped bridge becomes the end of its chain.
+        let mut frames = vec![
+            (inlined.clone(), false),
+            (bridge.clone(), true),
+            (other.clone(), true),
+        ];
+        prune_synthesized(&mut frames);
+        assert_eq!(frames, [(inlined, true), (other, true)]);
+
+        // Synthesized frames are kept if nothing else remains.
+        let mut frames = vec![(bridge.clone(), true)];
+        prune_synthesized(&mut frames);
+        assert_eq!(frames, [(bridge, true)]);
+    }
+
+    #[test]
+    fn split_frames_into_chains() {
+        let inlined = StackFrame::new("a.A", "inlined", 1);
+        let outer = StackFrame::new("a.A", "outer", 2);
+        let other = StackFrame::new("a.B", "other", 3);
+
+        let chains = split_chains([
+            (inlined.clone(), false),
+            (outer.clone(), true),
+            (other.clone(), true),
+        ]);
+        assert_eq!(chains, [vec![inlined.clone(), outer.clone()], vec![other]]);
+
+        // Frames after the last outermost frame are not dropped.
+        let chains = split_chains([(outer.clone(), true), (inlined.clone(), false)]);
+        assert_eq!(chains, [vec![outer], vec![inlined]]);
+    }
 }
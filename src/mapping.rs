@@ -7,7 +7,9 @@ use std::fmt;
 use std::ops::Range;
 use std::str;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::map_hash::MAP_ID_KEY;
 
 #[cfg(feature = "uuid")]
 use uuid::Uuid;
@@ -20,14 +22,31 @@ use uuid::Uuid;
 pub struct ParseError<'s> {
     line: &'s [u8],
     kind: ParseErrorKind,
+    position: SourcePosition,
 }
 
-impl ParseError<'_> {
+impl<'s> ParseError<'s> {
+    fn new(line: &'s [u8], kind: ParseErrorKind) -> Self {
+        Self {
+            line,
+            kind,
+            position: SourcePosition::default(),
+        }
+    }
+
     /// The offending line that caused the error.
     pub fn line(&self) -> &[u8] {
         self.line
     }
 
+    /// The position of the offending line in the mapping file.
+    ///
+    /// Errors returned by [`ProguardRecord::try_parse`] always point at the
+    /// start of the parsed line.
+    pub fn position(&self) -> SourcePosition {
+        self.position
+    }
+
     /// The specific parse Error.
     pub fn kind(&self) -> ParseErrorKind {
         self.kind
@@ -52,6 +71,31 @@ impl std::error::Error for ParseError<'_> {
     }
 }
 
+/// A position in a mapping file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourcePosition {
+    line: usize,
+    offset: usize,
+}
+
+impl SourcePosition {
+    /// The 1-based line number.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The byte offset from the start of the mapping file.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl Default for SourcePosition {
+    fn default() -> Self {
+        Self { line: 1, offset: 0 }
+    }
+}
+
 /// The specific parse Error.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ParseErrorKind {
@@ -61,22 +105,121 @@ pub enum ParseErrorKind {
     ParseError(&'static str),
 }
 
+/// A version of the R8 mapping file format.
+///
+/// R8 declares the version it used in a `com.android.tools.r8.mapping` header
+/// at the top of the mapping file. Some headers only exist from a certain
+/// version on, and are ignored in files declaring an older version.
+///
+/// # Examples
+///
+/// ```
+/// use proguard::MappingVersion;
+///
+/// let version = MappingVersion::parse("2.1").unwrap();
+/// assert!(version > MappingVersion::V2_0);
+/// assert!(version.is_supported());
+/// assert_eq!(version.to_string(), "2.1");
+///
+/// assert!(!MappingVersion::new(3, 0).is_supported());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MappingVersion {
+    major: u32,
+    minor: u32,
+}
+
+impl MappingVersion {
+    /// Version 1.0, the first version of the format.
+    pub const V1_0: Self = Self::new(1, 0);
+    /// Version 2.0, which introduced outlines and rewrite frame rules.
+    pub const V2_0: Self = Self::new(2, 0);
+    /// Version 2.2, which introduced residual signatures and added the
+    /// `outline` key to outline callsites.
+    pub const V2_2: Self = Self::new(2, 2);
+    /// The newest version this crate understands.
+    pub const LATEST: Self = Self::V2_2;
+
+    /// Creates a version from its major and minor number.
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    /// Parses a version of the form `major.minor`.
+    ///
+    /// Returns `None` for anything else, like R8's `"experimental"` version.
+    pub fn parse(version: &str) -> Option<Self> {
+        let (major, minor) = version.trim().split_once('.')?;
+        Some(Self::new(major.parse().ok()?, minor.parse().ok()?))
+    }
+
+    /// The major version number.
+    pub fn major(&self) -> u32 {
+        self.major
+    }
+
+    /// The minor version number.
+    pub fn minor(&self) -> u32 {
+        self.minor
+    }
+
+    /// Whether this crate understands all headers of this version.
+    pub fn is_supported(&self) -> bool {
+        *self <= Self::LATEST
+    }
+}
+
+impl fmt::Display for MappingVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
 /// Summary of a mapping file.
 pub struct MappingSummary<'s> {
     compiler: Option<&'s str>,
     compiler_version: Option<&'s str>,
+    mapping_version: Option<&'s str>,
+    map_id: Option<&'s str>,
     min_api: Option<u32>,
     class_count: usize,
     method_count: usize,
+    field_count: usize,
+    inlined_count: usize,
+    outline_count: usize,
+    outline_callsite_count: usize,
+    synthesized_count: usize,
+    source_file_count: usize,
+    rewrite_rule_count: usize,
+    no_line_info_count: usize,
+    max_inline_depth: usize,
 }
 
 impl<'s> MappingSummary<'s> {
     fn new(mapping: &'s ProguardMapping<'s>) -> MappingSummary<'s> {
         let mut compiler = None;
         let mut compiler_version = None;
+        let mut mapping_version = None;
+        let mut map_id = None;
         let mut min_api = None;
         let mut class_count = 0;
         let mut method_count = 0;
+        let mut field_count = 0;
+        let mut inlined_count = 0;
+        let mut outline_count = 0;
+        let mut outline_callsite_count = 0;
+        let mut synthesized_count = 0;
+        let mut source_file_count = 0;
+        let mut rewrite_rule_count = 0;
+        let mut no_line_info_count = 0;
+        let mut max_inline_depth = 0;
+
+        // Whether R8 headers currently attach to a method rather than a class.
+        let mut in_method = false;
+        // The obfuscated name and minified range of the current inline group,
+        // and the number of methods inlined into it so far.
+        let mut group = None;
+        let mut inline_depth = 0;
 
         for record in mapping.iter() {
             match record {
@@ -90,10 +233,59 @@ impl<'s> MappingSummary<'s> {
                     "min_api" => {
                         min_api = value.and_then(|x| x.parse().ok());
                     }
+                    MAP_ID_KEY => {
+                        map_id = value;
+                    }
+                    _ => {}
+                },
+                Ok(ProguardRecord::R8Header(R8Header::MappingVersion { version })) => {
+                    mapping_version = Some(version);
+                }
+                Ok(ProguardRecord::R8Header(header)) => match header {
+                    R8Header::SourceFile { .. } if !in_method => source_file_count += 1,
+                    R8Header::Synthesized if in_method => synthesized_count += 1,
+                    R8Header::Outline => outline_count += 1,
+                    R8Header::OutlineCallsite { .. } => outline_callsite_count += 1,
+                    R8Header::RewriteFrame { .. } => rewrite_rule_count += 1,
                     _ => {}
                 },
-                Ok(ProguardRecord::Class { .. }) => class_count += 1,
-                Ok(ProguardRecord::Method { .. }) => method_count += 1,
+                Ok(ProguardRecord::Class { .. }) => {
+                    class_count += 1;
+                    in_method = false;
+                    group = None;
+                }
+                Ok(ProguardRecord::Field { .. }) => {
+                    field_count += 1;
+                    in_method = false;
+                }
+                Ok(ProguardRecord::Method {
+                    obfuscated,
+                    line_mapping,
+                    ..
+                }) => {
+                    method_count += 1;
+                    in_method = true;
+
+                    let range = line_mapping.and_then(|lm| match (lm.startline, lm.endline) {
+                        (Some(start), Some(end)) if end > 0 => Some((start, end)),
+                        _ => None,
+                    });
+                    if line_mapping.is_none() {
+                        no_line_info_count += 1;
+                    }
+
+                    // Methods sharing the minified range of the next line were
+                    // inlined into it.
+                    let key = range.map(|range| (obfuscated, range));
+                    if key.is_some() && key == group {
+                        inlined_count += 1;
+                        inline_depth += 1;
+                        max_inline_depth = max_inline_depth.max(inline_depth);
+                    } else {
+                        group = key;
+                        inline_depth = 0;
+                    }
+                }
                 _ => {}
             }
         }
@@ -101,9 +293,20 @@ impl<'s> MappingSummary<'s> {
         MappingSummary {
             compiler,
             compiler_version,
+            mapping_version,
+            map_id,
             min_api,
             class_count,
             method_count,
+            field_count,
+            inlined_count,
+            outline_count,
+            outline_callsite_count,
+            synthesized_count,
+            source_file_count,
+            rewrite_rule_count,
+            no_line_info_count,
+            max_inline_depth,
         }
     }
 
@@ -117,6 +320,22 @@ impl<'s> MappingSummary<'s> {
         self.compiler_version
     }
 
+    /// Returns the version of the R8 mapping format the file declares.
+    ///
+    /// Use [`MappingVersion::parse`] to compare it with other versions.
+    pub fn mapping_version(&self) -> Option<&str> {
+        self.mapping_version
+    }
+
+    /// Returns the id R8 assigned to the mapping.
+    ///
+    /// The same id is embedded into the app, which makes it possible to find
+    /// the mapping for a crash. Use [`ProguardMapping::verify_map_hash`] to
+    /// check that the mapping wasn't modified since it was written.
+    pub fn map_id(&self) -> Option<&str> {
+        self.map_id
+    }
+
     /// Returns the min-api value.
     pub fn min_api(&self) -> Option<u32> {
         self.min_api
@@ -131,12 +350,64 @@ impl<'s> MappingSummary<'s> {
     pub fn method_count(&self) -> usize {
         self.method_count
     }
+
+    /// Returns the number of fields in the mapping file.
+    pub fn field_count(&self) -> usize {
+        self.field_count
+    }
+
+    /// Returns the number of method lines that were inlined into the method
+    /// on a later line with the same minified range.
+    pub fn inlined_count(&self) -> usize {
+        self.inlined_count
+    }
+
+    /// Returns the number of methods marked as outlines.
+    pub fn outline_count(&self) -> usize {
+        self.outline_count
+    }
+
+    /// Returns the number of methods that call an outline.
+    pub fn outline_callsite_count(&self) -> usize {
+        self.outline_callsite_count
+    }
+
+    /// Returns the number of methods marked as synthesized by the compiler.
+    ///
+    /// Synthesized classes are not included.
+    pub fn synthesized_count(&self) -> usize {
+        self.synthesized_count
+    }
+
+    /// Returns the number of classes with a source file.
+    pub fn source_file_count(&self) -> usize {
+        self.source_file_count
+    }
+
+    /// Returns the number of rewrite frame rules.
+    pub fn rewrite_rule_count(&self) -> usize {
+        self.rewrite_rule_count
+    }
+
+    /// Returns the number of methods without any line information.
+    ///
+    /// A high number compared to [`method_count`](Self::method_count) usually
+    /// means that line number tables were stripped before minification.
+    pub fn no_line_info_count(&self) -> usize {
+        self.no_line_info_count
+    }
+
+    /// Returns the largest number of methods inlined into a single minified
+    /// range, `0` if nothing was inlined.
+    pub fn max_inline_depth(&self) -> usize {
+        self.max_inline_depth
+    }
 }
 
 /// A Proguard Mapping file.
 #[derive(Clone, Copy, Default)]
 pub struct ProguardMapping<'s> {
-    source: &'s [u8],
+    pub(crate) source: &'s [u8],
 }
 
 impl fmt::Debug for ProguardMapping<'_> {
@@ -232,7 +503,33 @@ impl<'s> ProguardMapping<'s> {
     ///
     /// [`ProguardRecord`]: enum.ProguardRecord.html
     pub fn iter(&self) -> ProguardRecordIter<'s> {
-        ProguardRecordIter { slice: self.source }
+        ProguardRecordIter {
+            slice: self.source,
+            position: SourcePosition::default(),
+        }
+    }
+
+    /// Create an Iterator over [`ProguardRecord`]s together with the position
+    /// each record starts at.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use proguard::ProguardMapping;
+    ///
+    /// let mapping = ProguardMapping::new(b"a -> b:\n\n    not a record\n");
+    /// let (position, record) = mapping.iter_with_positions().nth(1).unwrap();
+    /// assert_eq!(position.line(), 3);
+    /// assert_eq!(position.offset(), 9);
+    ///
+    /// let error = record.unwrap_err();
+    /// assert_eq!(error.position(), position);
+    /// ```
+    pub fn iter_with_positions(
+        &self,
+    ) -> impl Iterator<Item = (SourcePosition, Result<ProguardRecord<'s>, ParseError<'s>>)> {
+        let mut iter = self.iter();
+        std::iter::from_fn(move || iter.next_with_position())
     }
 
     /// Returns the "submapping" of this within the given byte range.
@@ -253,6 +550,35 @@ impl<'s> ProguardMapping<'s> {
 #[derive(Clone, Default)]
 pub struct ProguardRecordIter<'s> {
     slice: &'s [u8],
+    /// The position at which `slice` starts.
+    position: SourcePosition,
+}
+
+impl<'s> ProguardRecordIter<'s> {
+    fn next_with_position(
+        &mut self,
+    ) -> Option<(SourcePosition, Result<ProguardRecord<'s>, ParseError<'s>>)> {
+        let rest = consume_leading_newlines(self.slice);
+        self.advance(self.slice.len() - rest.len());
+        if self.slice.is_empty() {
+            return None;
+        }
+
+        let position = self.position;
+        let (result, rest) = parse_proguard_record(self.slice);
+        self.advance(self.slice.len() - rest.len());
+
+        let result = result.map_err(|err| ParseError { position, ..err });
+        Some((position, result))
+    }
+
+    /// Moves `slice` forward by `len` bytes.
+    fn advance(&mut self, len: usize) {
+        let (consumed, rest) = self.slice.split_at(len);
+        self.position.line += count_newlines(consumed);
+        self.position.offset += len;
+        self.slice = rest;
+    }
 }
 
 impl fmt::Debug for ProguardRecordIter<'_> {
@@ -264,13 +590,7 @@ impl fmt::Debug for ProguardRecordIter<'_> {
 impl<'s> Iterator for ProguardRecordIter<'s> {
     type Item = Result<ProguardRecord<'s>, ParseError<'s>>;
     fn next(&mut self) -> Option<Self::Item> {
-        if self.slice.is_empty() {
-            return None;
-        }
-
-        let (result, slice) = parse_proguard_record(self.slice);
-        self.slice = slice;
-        Some(result)
+        self.next_with_position().map(|(_, result)| result)
     }
 }
 
@@ -295,14 +615,28 @@ pub struct LineMapping {
 /// <https://r8.googlesource.com/r8/+/refs/heads/main/doc/retrace.md#additional-information-appended-as-comments-to-the-file>.
 ///
 /// The format is a line starting with `#` and followed by a JSON object.
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(tag = "id", rename_all = "camelCase")]
+#[non_exhaustive]
 pub enum R8Header<'s> {
+    /// A mapping header, stating which version of the mapping format the file
+    /// uses. It appears once at the top of the file.
+    ///
+    /// See [`MappingVersion`] for the versions this crate understands.
+    #[serde(rename = "com.android.tools.r8.mapping")]
+    MappingVersion {
+        /// The declared version, for example `"2.2"`.
+        version: &'s str,
+    },
+
     /// A source file header, stating what source file a class originated from.
     ///
     /// See <https://r8.googlesource.com/r8/+/refs/heads/main/doc/retrace.md#source-file>.
     #[serde(rename_all = "camelCase")]
-    SourceFile { file_name: &'s str },
+    SourceFile {
+        /// The name of the source file.
+        file_name: &'s str,
+    },
 
     /// A synthesized header, stating that the class or method it's attached to
     /// was synthesized by the compiler.
@@ -326,8 +660,11 @@ pub enum R8Header<'s> {
     #[serde(rename_all = "camelCase")]
     #[serde(rename = "com.android.tools.r8.outlineCallsite")]
     OutlineCallsite {
+        /// Maps positions in the outline to positions at the callsite.
+        #[serde(serialize_with = "serialize_positions")]
         positions: std::collections::HashMap<&'s str, usize>,
-        #[serde(default)]
+        /// The residual descriptor of the outline method.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         outline: Option<&'s str>,
     },
 
@@ -337,13 +674,51 @@ pub enum R8Header<'s> {
     #[serde(rename_all = "camelCase")]
     #[serde(rename = "com.android.tools.r8.rewriteFrame")]
     RewriteFrame {
+        /// The conditions under which the frame should be rewritten.
         conditions: Vec<&'s str>,
+        /// The rewrite actions to apply.
         actions: Vec<&'s str>,
     },
 
+    /// A residual signature header, stating the JVM descriptor of the method
+    /// after minification. It is only present if the descriptor can't be
+    /// derived from the original signature, for example because arguments
+    /// were removed.
+    ///
+    /// See <https://r8.googlesource.com/r8/+/refs/heads/main/doc/retrace.md#residualsignature-introduced-at-version-2_2>.
+    #[serde(rename = "com.android.tools.r8.residualsignature")]
+    ResidualSignature {
+        /// The residual descriptor, for example `"(La/b;I)V"`.
+        signature: &'s str,
+    },
+
     /// Catchall variant for headers we don't support.
-    #[serde(other)]
-    Other,
+    ///
+    /// Holds the raw JSON object of the header, which is written back as it
+    /// is. Known headers whose contents can't be parsed end up here as well.
+    /// This variant is skipped by serde, [`Display`](fmt::Display) writes it.
+    #[serde(skip)]
+    Other(&'s str),
+}
+
+/// Serializes outline callsite positions ordered by position, like R8 does.
+fn serialize_positions<S: Serializer>(
+    positions: &std::collections::HashMap<&str, usize>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut sorted: Vec<_> = positions.iter().collect();
+    sorted.sort_by_key(|(position, _)| (position.parse::<usize>().ok(), **position));
+    serializer.collect_map(sorted)
+}
+
+impl fmt::Display for R8Header<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let R8Header::Other(json) = self {
+            return f.write_str(json);
+        }
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
 }
 
 /// A Proguard Mapping Record.
@@ -475,15 +850,83 @@ impl<'s> ProguardRecord<'s> {
             (Err(err), _) => Err(err),
             // We were able to extract a record from the line but there are bytes remaining
             // when they should have all been consumed during parsing
-            (Ok(_), slice) if !slice.is_empty() => Err(ParseError {
+            (Ok(_), slice) if !slice.is_empty() => Err(ParseError::new(
                 line,
-                kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
-            }),
+                ParseErrorKind::ParseError("line is not a valid proguard record"),
+            )),
             (Ok(record), _) => Ok(record),
         }
     }
 }
 
+/// Formats the record as a single line of a mapping file, without a trailing newline.
+///
+/// Field and method records are indented, so that the output can be parsed
+/// again with [`ProguardRecord::try_parse`]. Use a
+/// [`MappingWriter`](crate::MappingWriter) to write complete mapping files.
+///
+/// # Examples
+///
+/// ```
+/// use proguard::ProguardRecord;
+///
+/// let line = "    1016:1016:void com.example1.domain.MyBean.doWork():16:16 -> buttonClicked";
+/// let record = ProguardRecord::try_parse(line.as_bytes()).unwrap();
+/// assert_eq!(record.to_string(), line);
+/// ```
+impl fmt::Display for ProguardRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProguardRecord::Header { key, value } => match value {
+                Some(value) => write!(f, "# {key}: {value}"),
+                None => write!(f, "# {key}"),
+            },
+            ProguardRecord::R8Header(header) => write!(f, "# {header}"),
+            ProguardRecord::Class {
+                original,
+                obfuscated,
+            } => write!(f, "{original} -> {obfuscated}:"),
+            ProguardRecord::Field {
+                ty,
+                original,
+                obfuscated,
+            } => write!(f, "    {ty} {original} -> {obfuscated}"),
+            ProguardRecord::Method {
+                ty,
+                original,
+                obfuscated,
+                arguments,
+                original_class,
+                line_mapping,
+            } => {
+                f.write_str("    ")?;
+                if let Some(LineMapping {
+                    startline: Some(startline),
+                    endline: Some(endline),
+                    ..
+                }) = line_mapping
+                {
+                    write!(f, "{startline}:{endline}:")?;
+                }
+                write!(f, "{ty} ")?;
+                if let Some(original_class) = original_class {
+                    write!(f, "{original_class}.")?;
+                }
+                write!(f, "{original}({arguments})")?;
+                if let Some(line_mapping) = line_mapping {
+                    if let Some(original_startline) = line_mapping.original_startline {
+                        write!(f, ":{original_startline}")?;
+                        if let Some(original_endline) = line_mapping.original_endline {
+                            write!(f, ":{original_endline}")?;
+                        }
+                    }
+                }
+                write!(f, " -> {obfuscated}")
+            }
+        }
+    }
+}
+
 /// Parses a single line from a Proguard File.
 ///
 /// Returns `Err(ParseError)` if the line could not be parsed.
@@ -510,10 +953,10 @@ fn parse_proguard_record(bytes: &[u8]) -> (Result<ProguardRecord<'_>, ParseError
         Err(_) => {
             let (line, bytes) = split_line(bytes);
             (
-                Err(ParseError {
+                Err(ParseError::new(
                     line,
-                    kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
-                }),
+                    ParseErrorKind::ParseError("line is not a valid proguard record"),
+                )),
                 bytes,
             )
         }
@@ -544,11 +987,24 @@ fn parse_r8_header(bytes: &[u8]) -> Result<(ProguardRecord<'_>, &[u8]), ParseErr
     // Note: the leading `#` has already been parsed.
 
     let (header, rest) = parse_until(bytes, is_newline)?;
+    let header = header.trim_end();
+
+    let has_id = |header| {
+        serde_json::from_str::<serde_json::Value>(header)
+            .is_ok_and(|value| value.get("id").is_some_and(serde_json::Value::is_string))
+    };
 
-    let header = serde_json::from_str(header).map_err(|_| ParseError {
-        line: bytes,
-        kind: ParseErrorKind::ParseError("invalid r8 header"),
-    })?;
+    let header = match serde_json::from_str(header) {
+        Ok(header) => header,
+        // Keep headers we don't support as they are, so they can be written back.
+        Err(_) if has_id(header) => R8Header::Other(header),
+        Err(_) => {
+            return Err(ParseError::new(
+                bytes,
+                ParseErrorKind::ParseError("invalid r8 header"),
+            ))
+        }
+    };
     Ok((
         ProguardRecord::R8Header(header),
         consume_leading_newlines(rest),
@@ -623,10 +1079,10 @@ fn parse_proguard_field_or_method(
     let record = match arguments {
         Some(arguments) => {
             let mut split_class = original.rsplitn(2, '.');
-            let original = split_class.next().ok_or(ParseError {
-                line: bytes,
-                kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
-            })?;
+            let original = split_class.next().ok_or(ParseError::new(
+                bytes,
+                ParseErrorKind::ParseError("line is not a valid proguard record"),
+            ))?;
             let original_class = split_class.next();
 
             let line_mapping = match (startline, endline, original_startline) {
@@ -695,23 +1151,20 @@ fn parse_usize(bytes: &[u8]) -> Result<(usize, &[u8]), ParseError<'_>> {
     match std::str::from_utf8(slice) {
         Ok(s) => match s.parse() {
             Ok(value) => Ok((value, rest)),
-            Err(_) => Err(ParseError {
-                line: slice,
-                kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
-            }),
+            Err(_) => Err(ParseError::new(
+                slice,
+                ParseErrorKind::ParseError("line is not a valid proguard record"),
+            )),
         },
-        Err(err) => Err(ParseError {
-            line: slice,
-            kind: ParseErrorKind::Utf8Error(err),
-        }),
+        Err(err) => Err(ParseError::new(slice, ParseErrorKind::Utf8Error(err))),
     }
 }
 
 fn parse_prefix<'s>(bytes: &'s [u8], prefix: &'s [u8]) -> Result<&'s [u8], ParseError<'s>> {
-    bytes.strip_prefix(prefix).ok_or(ParseError {
-        line: bytes,
-        kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
-    })
+    bytes.strip_prefix(prefix).ok_or(ParseError::new(
+        bytes,
+        ParseErrorKind::ParseError("line is not a valid proguard record"),
+    ))
 }
 
 fn parse_until<P>(bytes: &[u8], predicate: P) -> Result<(&str, &[u8]), ParseError<'_>>
@@ -725,10 +1178,7 @@ where
 
     match std::str::from_utf8(slice) {
         Ok(s) => Ok((s, rest)),
-        Err(err) => Err(ParseError {
-            line: slice,
-            kind: ParseErrorKind::Utf8Error(err),
-        }),
+        Err(err) => Err(ParseError::new(slice, ParseErrorKind::Utf8Error(err))),
     }
 }
 
@@ -739,10 +1189,10 @@ where
     match parse_until(bytes, |byte| is_newline(byte) || predicate(byte)) {
         Ok((slice, bytes)) => {
             if !bytes.is_empty() && is_newline(&bytes[0]) {
-                Err(ParseError {
-                    line: slice.as_bytes(),
-                    kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
-                })
+                Err(ParseError::new(
+                    slice.as_bytes(),
+                    ParseErrorKind::ParseError("line is not a valid proguard record"),
+                ))
             } else {
                 Ok((slice, bytes))
             }
@@ -767,6 +1217,10 @@ fn split_line(bytes: &[u8]) -> (&[u8], &[u8]) {
     bytes.split_at(pos)
 }
 
+fn count_newlines(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|c| **c == b'\n').count()
+}
+
 fn is_newline(byte: &u8) -> bool {
     *byte == b'\r' || *byte == b'\n'
 }
@@ -850,19 +1304,94 @@ mod tests {
         let bytes = br#"# {"id":"foobar"}"#;
         assert_eq!(
             ProguardRecord::try_parse(bytes).unwrap(),
-            ProguardRecord::R8Header(R8Header::Other),
+            ProguardRecord::R8Header(R8Header::Other(r#"{"id":"foobar"}"#)),
         );
 
         let bytes = br#"    #{"id":"foobar"}"#;
         assert_eq!(
             ProguardRecord::try_parse(bytes).unwrap(),
-            ProguardRecord::R8Header(R8Header::Other),
+            ProguardRecord::R8Header(R8Header::Other(r#"{"id":"foobar"}"#)),
         );
         let bytes = br#"#     {"id":"foobar"}"#;
         assert_eq!(
             ProguardRecord::try_parse(bytes).unwrap(),
-            ProguardRecord::R8Header(R8Header::Other),
+            ProguardRecord::R8Header(R8Header::Other(r#"{"id":"foobar"}"#)),
+        );
+    }
+
+    #[test]
+    fn try_parse_header_mapping_version() {
+        let bytes = br#"# {"id":"com.android.tools.r8.mapping","version":"2.2"}"#;
+        let record = ProguardRecord::try_parse(bytes).unwrap();
+        assert_eq!(
+            record,
+            ProguardRecord::R8Header(R8Header::MappingVersion { version: "2.2" })
         );
+        assert_eq!(record.to_string().as_bytes(), bytes);
+
+        let mapping = ProguardMapping::new(bytes);
+        assert_eq!(mapping.summary().mapping_version(), Some("2.2"));
+    }
+
+    #[test]
+    fn try_parse_header_residual_signature() {
+        let bytes = br#"# {"id":"com.android.tools.r8.residualsignature","signature":"(I)V"}"#;
+        let record = ProguardRecord::try_parse(bytes).unwrap();
+        assert_eq!(
+            record,
+            ProguardRecord::R8Header(R8Header::ResidualSignature { signature: "(I)V" })
+        );
+        assert_eq!(record.to_string().as_bytes(), bytes);
+    }
+
+    #[test]
+    fn summary_statistics() {
+        let bytes = br#"# compiler: R8
+com.example.Main -> a:
+# {"id":"sourceFile","fileName":"Main.kt"}
+    int field -> a
+    1:1:void inner():10:10 -> a
+    1:1:void middle():20:20 -> a
+    1:1:void run():30:30 -> a
+    2:2:void other.Class.inlined():40:40 -> a
+    2:2:void run():31:31 -> a
+    # {"id":"com.android.tools.r8.rewriteFrame","conditions":["throws(Ljava/lang/NullPointerException;)"],"actions":["removeInnerFrames(1)"]}
+    void noLines() -> b
+com.example.Synthetic -> b:
+# {"id":"com.android.tools.r8.synthesized"}
+    1:1:int outline():0:0 -> a
+    # {"id":"com.android.tools.r8.synthesized"}
+    # {"id":"com.android.tools.r8.outline"}
+    1:3:int caller():5:7 -> b
+    # {"id":"com.android.tools.r8.outlineCallsite","positions":{"1":5}}
+"#;
+        let mapping = ProguardMapping::new(bytes);
+        let summary = mapping.summary();
+
+        assert_eq!(summary.class_count(), 2);
+        assert_eq!(summary.method_count(), 8);
+        assert_eq!(summary.field_count(), 1);
+        assert_eq!(summary.inlined_count(), 3);
+        assert_eq!(summary.max_inline_depth(), 2);
+        assert_eq!(summary.outline_count(), 1);
+        assert_eq!(summary.outline_callsite_count(), 1);
+        assert_eq!(summary.synthesized_count(), 1);
+        assert_eq!(summary.source_file_count(), 1);
+        assert_eq!(summary.rewrite_rule_count(), 1);
+        assert_eq!(summary.no_line_info_count(), 1);
+    }
+
+    #[test]
+    fn parse_mapping_version() {
+        assert_eq!(MappingVersion::parse("2.2"), Some(MappingVersion::V2_2));
+        assert_eq!(MappingVersion::parse("1.0"), Some(MappingVersion::V1_0));
+        assert_eq!(
+            MappingVersion::parse("10.3"),
+            Some(MappingVersion::new(10, 3))
+        );
+        assert_eq!(MappingVersion::parse("experimental"), None);
+        assert_eq!(MappingVersion::parse("2"), None);
+        assert!(MappingVersion::new(2, 10) > MappingVersion::V2_2);
     }
 
     #[test]
@@ -1035,10 +1564,10 @@ mod tests {
         let parsed = ProguardRecord::try_parse(bytes);
         assert_eq!(
             parsed,
-            Err(ParseError {
-                line: bytes,
-                kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
-            }),
+            Err(ParseError::new(
+                bytes,
+                ParseErrorKind::ParseError("line is not a valid proguard record")
+            )),
         );
     }
 
@@ -1049,10 +1578,10 @@ mod tests {
         let parsed = ProguardRecord::try_parse(bytes);
         assert_eq!(
             parsed,
-            Err(ParseError {
-                line: bytes,
-                kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
-            }),
+            Err(ParseError::new(
+                bytes,
+                ParseErrorKind::ParseError("line is not a valid proguard record")
+            )),
         );
     }
 
@@ -1076,10 +1605,10 @@ mod tests {
         let parsed = ProguardRecord::try_parse(bytes);
         assert_eq!(
             parsed,
-            Err(ParseError {
-                line: bytes,
-                kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
-            }),
+            Err(ParseError::new(
+                bytes,
+                ParseErrorKind::ParseError("line is not a valid proguard record")
+            )),
         );
     }
 
@@ -1089,10 +1618,10 @@ mod tests {
         let parsed = ProguardRecord::try_parse(bytes);
         assert_eq!(
             parsed,
-            Err(ParseError {
-                line: bytes,
-                kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
-            }),
+            Err(ParseError::new(
+                bytes,
+                ParseErrorKind::ParseError("line is not a valid proguard record")
+            )),
         );
     }
 
@@ -1129,6 +1658,10 @@ androidx.activity.OnBackPressedCallback
                 Err(ParseError {
                     line: b"androidx.activity.OnBackPressedCallback->c.a.b:\n",
                     kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
+                    position: SourcePosition {
+                        line: 4,
+                        offset: 39,
+                    },
                 }),
                 Ok(ProguardRecord::Class {
                     original: "androidx.activity.OnBackPressedCallback",
@@ -1166,19 +1699,60 @@ androidx.activity.OnBackPressedCallback
                 Err(ParseError {
                     line: b"androidx.activity.OnBackPressedCallback \n",
                     kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
+                    position: SourcePosition {
+                        line: 11,
+                        offset: 331,
+                    },
                 }),
                 Err(ParseError {
                     line: b"-> c.a.b:\n",
                     kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
+                    position: SourcePosition {
+                        line: 12,
+                        offset: 372,
+                    },
                 }),
                 Err(ParseError {
                     line: b"        ",
                     kind: ParseErrorKind::ParseError("line is not a valid proguard record"),
+                    position: SourcePosition {
+                        line: 13,
+                        offset: 382,
+                    },
                 }),
             ],
         );
     }
 
+    #[test]
+    fn display_roundtrip() {
+        let lines: &[&[u8]] = &[
+            b"# compiler: R8",
+            b"# common_typos_disable",
+            br#"# {"id":"sourceFile","fileName":"Foobar.kt"}"#,
+            br#"# {"id":"com.android.tools.r8.synthesized"}"#,
+            br#"# {"id":"com.android.tools.r8.outline"}"#,
+            br#"# {"id":"com.android.tools.r8.outlineCallsite","positions":{"2":42,"10":10}}"#,
+            br#"# {"id":"com.android.tools.r8.outlineCallsite","positions":{"5":13},"outline":"La;a()I"}"#,
+            br#"# {"id":"com.android.tools.r8.rewriteFrame","conditions":["throws(Ljava/lang/NullPointerException;)"],"actions":["removeInnerFrames(1)"]}"#,
+            br#"# {"id":"other"}"#,
+            br#"# {"id":"com.example.unknown","values":[2,1],"nested":{"b":null,"a":true}}"#,
+            b"android.arch.core.executor.ArchTaskExecutor -> a.a.a.a.c:",
+            b"    android.app.Activity mActivity -> a",
+            b"    boolean equals(java.lang.Object,java.lang.Object) -> a",
+            b"    14:15:void androidx.appcompat.app.AppCompatDelegateImpl.setSupportActionBar(androidx.appcompat.widget.Toolbar) -> onCreate",
+            b"    14:15:void setSupportActionBar(androidx.appcompat.widget.Toolbar):436 -> onCreate",
+            b"    14:15:void setSupportActionBar(androidx.appcompat.widget.Toolbar):436:437 -> onCreate",
+            b"    void foo():42 -> a",
+            b"    void foo():42:44 -> a",
+        ];
+
+        for line in lines {
+            let record = ProguardRecord::try_parse(line).unwrap();
+            assert_eq!(record.to_string().as_bytes(), *line);
+        }
+    }
+
     #[test]
     fn try_parse_header_outline() {
         let bytes = br#"# {"id":"com.android.tools.r8.outline"}"#;
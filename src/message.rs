@@ -0,0 +1,285 @@
+//! Remapping of obfuscated names in exception messages.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Returns whether `c` can be part of a Java identifier.
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Remaps the obfuscated names in an exception `message`.
+///
+/// This recognizes:
+/// - dotted class names, as in `a.b.c cannot be cast to a.b.d`,
+/// - methods and fields of classes, as in Android's null pointer messages
+///   `Attempt to invoke virtual method 'void a.b.c.a(int)' on a null object reference`,
+/// - classes in JVM descriptors, as in `La/b/c;` or `(La/b/c;)V`,
+/// - the members in `No virtual method a()V in class La/b/c;` and
+///   `No field a of type I in class La/b/c;`.
+///
+/// `remap_class` remaps an obfuscated class name, and `remap_member` remaps
+/// a method (if its last argument is `true`) or field of an obfuscated class
+/// to the original class and member name.
+pub(crate) fn remap_message<'a, 'r>(
+    message: &'a str,
+    remap_class: impl Fn(&str) -> Option<&'r str>,
+    remap_member: impl Fn(&str, &str, bool) -> Option<(&'r str, &'r str)>,
+) -> Cow<'a, str> {
+    let mut replacements: Vec<(Range<usize>, String)> = Vec::new();
+
+    if let Some((name, class, is_method)) = missing_member(message) {
+        let class = class.replace('/', ".");
+        if let Some((_, original)) = remap_member(&class, &message[name.clone()], is_method) {
+            replacements.push((name, original.to_owned()));
+        }
+    }
+
+    let mut idx = 0;
+    while let Some(c) = message[idx..].chars().next() {
+        if c == '(' {
+            if let Some(end) =
+                parse_method_descriptor(message, idx, &remap_class, &mut replacements)
+            {
+                idx = end;
+                continue;
+            }
+        }
+
+        let at_word_start = message[..idx]
+            .chars()
+            .next_back()
+            .is_none_or(|prev| !is_identifier_char(prev) && prev != '.');
+        if at_word_start && (c == 'L' || c == '[') {
+            let mut classes = Vec::new();
+            if let Some(end) = parse_field_descriptor(message, idx, &remap_class, &mut classes) {
+                if !classes.is_empty() {
+                    replacements.append(&mut classes);
+                    idx = end;
+                    continue;
+                }
+            }
+        }
+
+        if at_word_start && is_identifier_char(c) {
+            let len = message[idx..]
+                .find(|c: char| !is_identifier_char(c) && c != '.')
+                .unwrap_or(message.len() - idx);
+            let token = message[idx..idx + len].trim_end_matches('.');
+            let end = idx + token.len();
+            let is_method = message[end..].starts_with('(');
+            if let Some(remapped) = remap_name(token, is_method, &remap_class, &remap_member) {
+                replacements.push((idx..end, remapped));
+            }
+            idx += len;
+            continue;
+        }
+
+        idx += c.len_utf8();
+    }
+
+    if replacements.is_empty() {
+        return Cow::Borrowed(message);
+    }
+
+    replacements.sort_by_key(|(range, _)| range.start);
+    let mut remapped = String::with_capacity(message.len());
+    let mut last = 0;
+    for (range, replacement) in replacements {
+        remapped.push_str(&message[last..range.start]);
+        remapped.push_str(&replacement);
+        last = range.end;
+    }
+    remapped.push_str(&message[last..]);
+    Cow::Owned(remapped)
+}
+
+/// Remaps a dotted name, which is either a class or a member of a class.
+fn remap_name<'r>(
+    token: &str,
+    is_method: bool,
+    remap_class: impl Fn(&str) -> Option<&'r str>,
+    remap_member: impl Fn(&str, &str, bool) -> Option<(&'r str, &'r str)>,
+) -> Option<String> {
+    if !token.contains('.') || token.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    if !is_method {
+        if let Some(class) = remap_class(token) {
+            return Some(class.to_owned());
+        }
+    }
+
+    let (class, member) = token.rsplit_once('.')?;
+    if let Some((class, member)) = remap_member(class, member, is_method) {
+        return Some(format!("{class}.{member}"));
+    }
+    // The class of a method call may be known even if the method is ambiguous.
+    is_method
+        .then(|| remap_class(class))
+        .flatten()
+        .map(|class| format!("{class}.{member}"))
+}
+
+/// Finds the member name and the binary name of its class in messages of the
+/// form `No virtual method a()V in class La/b/c;`.
+fn missing_member(message: &str) -> Option<(Range<usize>, &str, bool)> {
+    if !message.starts_with("No ") {
+        return None;
+    }
+
+    const IN_CLASS: &str = " in class L";
+    let class_start = message.find(IN_CLASS)? + IN_CLASS.len();
+    let class_len = message[class_start..].find(';')?;
+    let class = &message[class_start..class_start + class_len];
+
+    let (name_start, is_method) = if let Some(pos) = message.find(" method ") {
+        (pos + " method ".len(), true)
+    } else {
+        (message.find(" field ")? + " field ".len(), false)
+    };
+    let name_len = message[name_start..]
+        .find(|c: char| !is_identifier_char(c) && c != '<' && c != '>')
+        .filter(|&len| len > 0 && name_start + len < class_start)?;
+
+    Some((name_start..name_start + name_len, class, is_method))
+}
+
+/// Parses a JVM method descriptor such as `(La/b;I)V` starting at `start`,
+/// collecting the remapped classes in it.
+///
+/// Returns the end of the descriptor.
+fn parse_method_descriptor<'r>(
+    message: &str,
+    start: usize,
+    remap_class: impl Fn(&str) -> Option<&'r str>,
+    replacements: &mut Vec<(Range<usize>, String)>,
+) -> Option<usize> {
+    let mut classes = Vec::new();
+    let mut idx = start + 1;
+    while !message[idx..].starts_with(')') {
+        idx = parse_field_descriptor(message, idx, &remap_class, &mut classes)?;
+    }
+    idx = parse_field_descriptor(message, idx + 1, &remap_class, &mut classes)?;
+
+    replacements.append(&mut classes);
+    Some(idx)
+}
+
+/// Parses a JVM field descriptor such as `La/b/c;` or `[I` starting at
+/// `start`, collecting the remapped class in it.
+///
+/// Returns the end of the descriptor.
+fn parse_field_descriptor<'r>(
+    message: &str,
+    start: usize,
+    remap_class: impl Fn(&str) -> Option<&'r str>,
+    replacements: &mut Vec<(Range<usize>, String)>,
+) -> Option<usize> {
+    let rest = &message[start..];
+    let dimensions = rest.len() - rest.trim_start_matches('[').len();
+    let idx = start + dimensions;
+
+    match message[idx..].chars().next()? {
+        'Z' | 'B' | 'C' | 'S' | 'I' | 'J' | 'F' | 'D' | 'V' => Some(idx + 1),
+        'L' => {
+            let class_start = idx + 1;
+            let class_len = message[class_start..]
+                .find(|c: char| !is_identifier_char(c) && c != '/' && c != '.')?;
+            if class_len == 0 || !message[class_start + class_len..].starts_with(';') {
+                return None;
+            }
+
+            let binary_name = &message[class_start..class_start + class_len];
+            if let Some(class) = remap_class(&binary_name.replace('/', ".")) {
+                let class = if binary_name.contains('/') {
+                    class.replace('.', "/")
+                } else {
+                    class.to_owned()
+                };
+                replacements.push((class_start..class_start + class_len, class));
+            }
+            Some(class_start + class_len + 1)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remap(message: &str) -> Cow<'_, str> {
+        remap_message(
+            message,
+            |class| match class {
+                "a.b.c" => Some("com.example.Foo"),
+                "a.b.d" => Some("com.example.Bar"),
+                _ => None,
+            },
+            |class, member, is_method| match (class, member, is_method) {
+                ("a.b.c", "a", true) => Some(("com.example.Foo", "run")),
+                ("a.b.c", "b", false) => Some(("com.example.Foo", "count")),
+                _ => None,
+            },
+        )
+    }
+
+    #[test]
+    fn class_cast() {
+        assert_eq!(
+            remap("a.b.c cannot be cast to a.b.d."),
+            "com.example.Foo cannot be cast to com.example.Bar."
+        );
+        assert_eq!(
+            remap("[La.b.c; cannot be cast to [Ljava.lang.String;"),
+            "[Lcom.example.Foo; cannot be cast to [Ljava.lang.String;"
+        );
+    }
+
+    #[test]
+    fn null_pointer() {
+        assert_eq!(
+            remap("Attempt to invoke virtual method 'void a.b.c.a(a.b.d)' on a null object reference"),
+            "Attempt to invoke virtual method 'void com.example.Foo.run(com.example.Bar)' on a null object reference"
+        );
+        assert_eq!(
+            remap("Attempt to read from field 'int a.b.c.b' on a null object reference"),
+            "Attempt to read from field 'int com.example.Foo.count' on a null object reference"
+        );
+        // An unknown method still has its class remapped.
+        assert_eq!(
+            remap("Attempt to invoke interface method 'a.b.d a.b.c.x()'"),
+            "Attempt to invoke interface method 'com.example.Bar com.example.Foo.x()'"
+        );
+    }
+
+    #[test]
+    fn missing_members() {
+        assert_eq!(
+            remap("No virtual method a(La/b/d;I)V in class La/b/c; or its super classes"),
+            "No virtual method run(Lcom/example/Bar;I)V in class Lcom/example/Foo; or its super classes"
+        );
+        assert_eq!(
+            remap("No field b of type I in class La/b/c; or its superclasses"),
+            "No field count of type I in class Lcom/example/Foo; or its superclasses"
+        );
+    }
+
+    #[test]
+    fn untouched() {
+        for message in [
+            "Unable to instantiate activity ComponentInfo{com.example/com.example.Main}",
+            "version 1.2.3 of a.b.x is not supported",
+            "Lorem ipsum (I think)",
+            "a.b.",
+        ] {
+            assert!(matches!(remap(message), Cow::Borrowed(_)), "{message}");
+        }
+        assert_eq!(
+            remap("Unable to instantiate activity ComponentInfo{com.example/a.b.c}"),
+            "Unable to instantiate activity ComponentInfo{com.example/com.example.Foo}"
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! Options for remapping complete stack traces.
+
+#[cfg(feature = "regex")]
+use crate::FrameRegex;
+
+/// Options for remapping complete stack traces.
+///
+/// See [`ProguardMapper::remap_stacktrace_with_options`](crate::ProguardMapper::remap_stacktrace_with_options)
+/// and [`ProguardCache::remap_stacktrace_with_options`](crate::ProguardCache::remap_stacktrace_with_options),
+/// as well as their typed counterparts.
+#[derive(Clone, Debug, Default)]
+pub struct RemapOptions {
+    pub(crate) mark_alternatives: bool,
+    pub(crate) prefer_non_synthesized: bool,
+    pub(crate) remap_messages: bool,
+    #[cfg(feature = "regex")]
+    pub(crate) frame_regex: Option<FrameRegex>,
+}
+
+impl RemapOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prints ambiguous frames the way R8's `retrace` tool does.
+    ///
+    /// By default, all candidates of an ambiguous frame are printed as normal
+    /// frames, so alternatives can't be told apart from inlined frames. With
+    /// this option, the alternative inline chains are sorted by their top
+    /// frame and the first frame of every chain but the first is prefixed with
+    /// `<OR>`. Frames keep the indentation of the input line, and frames without
+    /// line information don't print a line.
+    pub fn mark_alternatives(mut self, mark_alternatives: bool) -> Self {
+        self.mark_alternatives = mark_alternatives;
+        self
+    }
+
+    /// Hides frames of methods synthesized by the compiler when the same
+    /// minified frame also remaps to methods that are not synthesized.
+    ///
+    /// This matches R8's `retrace` tool, and removes noise such as lambda
+    /// bridges from the remapped stack trace. Frames are only hidden if at
+    /// least one frame remains for the minified frame.
+    pub fn prefer_non_synthesized(mut self, prefer_non_synthesized: bool) -> Self {
+        self.prefer_non_synthesized = prefer_non_synthesized;
+        self
+    }
+
+    /// Remaps obfuscated names in the messages of exceptions and causes.
+    ///
+    /// Messages such as `a.b.c cannot be cast to a.b.d` or
+    /// `Attempt to invoke virtual method 'void a.b.c.a()' on a null object reference`
+    /// mention classes and members by their obfuscated names. With this
+    /// option, names that can be remapped unambiguously are replaced, even if
+    /// the class of the exception itself is not part of the mapping.
+    pub fn remap_messages(mut self, remap_messages: bool) -> Self {
+        self.remap_messages = remap_messages;
+        self
+    }
+
+    /// Matches lines with a custom pattern before trying the standard
+    /// stack trace formats.
+    ///
+    /// Lines matching `regex` are remapped as described in [`FrameRegex`].
+    /// Other lines are remapped as usual.
+    #[cfg(feature = "regex")]
+    pub fn frame_regex(mut self, regex: FrameRegex) -> Self {
+        self.frame_regex = Some(regex);
+        self
+    }
+}
@@ -0,0 +1,97 @@
+//! A [`ProguardMapper`] that owns its mapping.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::mapper::ProguardMapper;
+use crate::mapping::ProguardMapping;
+
+/// A [`ProguardMapper`] that owns the mapping it was created from.
+///
+/// A `ProguardMapper` borrows from the mapping text, which makes it hard to
+/// keep around in long-running services. This type keeps the mapping alive
+/// next to the mapper and is `Send + Sync`, so it can be shared by several
+/// threads in an [`Arc`].
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use proguard::OwnedProguardMapper;
+///
+/// let mapping = b"android.arch.core.internal.SafeIterableMap -> a.a.a.b.c:".to_vec();
+/// let mapper = Arc::new(OwnedProguardMapper::new(mapping));
+///
+/// let worker = {
+///     let mapper = Arc::clone(&mapper);
+///     std::thread::spawn(move || mapper.get().remap_class("a.a.a.b.c").map(String::from))
+/// };
+/// assert_eq!(
+///     worker.join().unwrap().as_deref(),
+///     Some("android.arch.core.internal.SafeIterableMap"),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct OwnedProguardMapper {
+    // Borrows from `data`. The `'static` lifetime is never exposed, `get`
+    // shortens it to the lifetime of `self`.
+    mapper: ProguardMapper<'static>,
+    data: Arc<[u8]>,
+}
+
+impl OwnedProguardMapper {
+    /// Creates a new OwnedProguardMapper from the bytes of a mapping.
+    pub fn new(data: impl Into<Arc<[u8]>>) -> Self {
+        Self::new_with_param_mapping(data, false)
+    }
+
+    /// Creates a new OwnedProguardMapper with the extra mappings_by_params.
+    ///
+    /// See [`ProguardMapper::new_with_param_mapping`].
+    pub fn new_with_param_mapping(
+        data: impl Into<Arc<[u8]>>,
+        initialize_param_mapping: bool,
+    ) -> Self {
+        let data = data.into();
+        // SAFETY: The bytes are on the heap, so they don't move when `data`
+        // does, and they are never mutated. `data` is stored next to the
+        // mapper, so the bytes live at least as long as the mapper.
+        let bytes: &'static [u8] = unsafe { &*Arc::as_ptr(&data) };
+        let mapper = ProguardMapper::new_with_param_mapping(
+            ProguardMapping::new(bytes),
+            initialize_param_mapping,
+        );
+        Self { mapper, data }
+    }
+
+    /// The mapper, borrowing from `self`.
+    pub fn get(&self) -> &ProguardMapper<'_> {
+        &self.mapper
+    }
+
+    /// The bytes of the mapping.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl From<Vec<u8>> for OwnedProguardMapper {
+    fn from(data: Vec<u8>) -> Self {
+        Self::new(data)
+    }
+}
+
+impl From<String> for OwnedProguardMapper {
+    fn from(data: String) -> Self {
+        Self::new(data.into_bytes())
+    }
+}
+
+impl fmt::Debug for OwnedProguardMapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnedProguardMapper")
+            .field("mapper", &self.mapper)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,372 @@
+//! A registry of many [`ProguardCache`]s.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use thiserror::Error;
+#[cfg(feature = "uuid")]
+use uuid::Uuid;
+
+use crate::cache::{CacheError, ProguardCache};
+use crate::mapping::ProguardMapping;
+use crate::options::RemapOptions;
+use crate::stacktrace::StackTrace;
+
+/// Identifies a mapping in a [`CacheRegistry`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MappingKey {
+    /// The UUID of the mapping, see [`ProguardMapping::uuid`].
+    #[cfg(feature = "uuid")]
+    Uuid(Uuid),
+    /// The id R8 assigned to the mapping, see [`MappingSummary::map_id`](crate::MappingSummary::map_id).
+    MapId(String),
+}
+
+impl MappingKey {
+    /// Creates a key for the `pg_map_id` of a mapping.
+    pub fn map_id(map_id: impl Into<String>) -> Self {
+        Self::MapId(map_id.into())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<Uuid> for MappingKey {
+    fn from(uuid: Uuid) -> Self {
+        Self::Uuid(uuid)
+    }
+}
+
+impl fmt::Display for MappingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "uuid")]
+            Self::Uuid(uuid) => write!(f, "uuid {uuid}"),
+            Self::MapId(map_id) => write!(f, "map id {map_id}"),
+        }
+    }
+}
+
+/// An error returned when using a [`CacheRegistry`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RegistryError {
+    /// No cache was registered for the key.
+    #[error("no cache registered for {0}")]
+    UnknownKey(MappingKey),
+    /// Reading or writing a cache failed.
+    #[error("failed to read or write cache")]
+    Io(#[from] io::Error),
+    /// The cache is not a valid [`ProguardCache`].
+    #[error("invalid cache")]
+    InvalidCache(#[from] CacheError),
+    /// Writing the remapped stack trace failed.
+    #[error("failed to write remapped stack trace")]
+    Fmt(#[from] fmt::Error),
+}
+
+enum CacheSource {
+    Bytes(Arc<[u8]>),
+    File(PathBuf),
+}
+
+/// A registry of [`ProguardCache`]s for many mappings.
+///
+/// Services that remap crashes of many app versions need to find the right
+/// mapping for every crash. The registry holds caches keyed by the UUID or the
+/// `pg_map_id` of their mapping, and routes remapping to the right one.
+///
+/// Caches can be registered from memory or as files, which are only read when
+/// they are first used. At most `capacity` cache files are kept in memory;
+/// when another one is needed, the least recently used one is dropped.
+///
+/// All methods take `&self` once the caches are registered, so the registry
+/// can be shared by several threads in an [`Arc`].
+///
+/// # Examples
+///
+/// ```
+/// use proguard::{CacheRegistry, MappingKey, ProguardMapping};
+///
+/// let mapping = ProguardMapping::new(b"# pg_map_id: 6e3f1ac
+/// some.Main -> b:
+///     1:1:void main(java.lang.String[]):5:5 -> a
+/// ");
+///
+/// let mut registry = CacheRegistry::new(8);
+/// registry.insert_mapping(&mapping).unwrap();
+///
+/// let remapped = registry
+///     .remap_stacktrace(&MappingKey::map_id("6e3f1ac"), "    at b.a(SourceFile:1)")
+///     .unwrap();
+/// assert_eq!(remapped, "    at some.Main.main(Main.java:5)\n");
+/// ```
+pub struct CacheRegistry {
+    // Sources by index. The slots of removed sources are `None` until reused.
+    sources: Vec<Option<CacheSource>>,
+    keys: HashMap<MappingKey, usize>,
+    capacity: usize,
+    // Loaded cache files by source index, most recently used first.
+    loaded: Mutex<VecDeque<(usize, Arc<[u8]>)>>,
+}
+
+impl CacheRegistry {
+    /// Creates an empty registry that keeps at most `capacity` cache files in memory.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            sources: Vec::new(),
+            keys: HashMap::new(),
+            capacity: capacity.max(1),
+            loaded: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Registers the bytes of a [`ProguardCache`] under `key`.
+    ///
+    /// The bytes are kept in memory and don't count towards the capacity. A
+    /// cache that was registered under `key` before is replaced.
+    pub fn insert(
+        &mut self,
+        key: MappingKey,
+        data: impl Into<Arc<[u8]>>,
+    ) -> Result<(), RegistryError> {
+        let data = data.into();
+        ProguardCache::parse(&data)?;
+        self.insert_source(&[key], CacheSource::Bytes(data));
+        Ok(())
+    }
+
+    /// Registers the [`ProguardCache`] file at `path` under `key`.
+    ///
+    /// The file is read when the cache is first used, and again after it was
+    /// evicted. A cache that was registered under `key` before is replaced.
+    pub fn insert_file(&mut self, key: MappingKey, path: impl Into<PathBuf>) {
+        self.insert_source(&[key], CacheSource::File(path.into()));
+    }
+
+    /// Converts `mapping` into a [`ProguardCache`] and registers it.
+    ///
+    /// The cache is registered under the `pg_map_id` of the mapping, if it has
+    /// one, and with the `uuid` feature also under the UUID of the mapping.
+    pub fn insert_mapping(&mut self, mapping: &ProguardMapping<'_>) -> Result<(), RegistryError> {
+        #[allow(unused_mut)]
+        let mut keys: Vec<_> = mapping
+            .summary()
+            .map_id()
+            .map(MappingKey::map_id)
+            .into_iter()
+            .collect();
+        #[cfg(feature = "uuid")]
+        keys.push(MappingKey::Uuid(mapping.uuid()));
+
+        let mut data = Vec::new();
+        ProguardCache::write(mapping, &mut data)?;
+        self.insert_source(&keys, CacheSource::Bytes(data.into()));
+        Ok(())
+    }
+
+    fn insert_source(&mut self, keys: &[MappingKey], source: CacheSource) {
+        for key in keys {
+            self.remove(key);
+        }
+
+        let index = match self.sources.iter().position(Option::is_none) {
+            Some(index) => {
+                self.sources[index] = Some(source);
+                index
+            }
+            None => {
+                self.sources.push(Some(source));
+                self.sources.len() - 1
+            }
+        };
+        for key in keys {
+            self.keys.insert(key.clone(), index);
+        }
+    }
+
+    /// Removes the cache registered under `key`.
+    ///
+    /// The cache is dropped once no other key refers to it. Returns whether a
+    /// cache was registered under `key`.
+    pub fn remove(&mut self, key: &MappingKey) -> bool {
+        let Some(index) = self.keys.remove(key) else {
+            return false;
+        };
+
+        if !self.keys.values().any(|&other| other == index) {
+            self.sources[index] = None;
+            self.loaded
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .retain(|(loaded, _)| *loaded != index);
+        }
+        true
+    }
+
+    /// Returns whether a cache is registered under `key`.
+    pub fn contains(&self, key: &MappingKey) -> bool {
+        self.keys.contains_key(key)
+    }
+
+    /// Calls `f` with the cache registered under `key`.
+    pub fn with_cache<R>(
+        &self,
+        key: &MappingKey,
+        f: impl FnOnce(&ProguardCache<'_>) -> R,
+    ) -> Result<R, RegistryError> {
+        let data = self.load(key)?;
+        let cache = ProguardCache::parse(&data)?;
+        Ok(f(&cache))
+    }
+
+    /// Remaps a complete Java StackTrace with the cache registered under `key`.
+    ///
+    /// See [`ProguardCache::remap_stacktrace`].
+    pub fn remap_stacktrace(&self, key: &MappingKey, input: &str) -> Result<String, RegistryError> {
+        self.remap_stacktrace_with_options(key, input, &RemapOptions::default())
+    }
+
+    /// Remaps a complete Java StackTrace with the cache registered under `key`.
+    ///
+    /// See [`ProguardCache::remap_stacktrace_with_options`].
+    pub fn remap_stacktrace_with_options(
+        &self,
+        key: &MappingKey,
+        input: &str,
+        options: &RemapOptions,
+    ) -> Result<String, RegistryError> {
+        let remapped = self.with_cache(key, |cache| {
+            cache.remap_stacktrace_with_options(input, options)
+        })?;
+        Ok(remapped?)
+    }
+
+    /// Remaps a complete Java StackTrace with the cache registered under `key`.
+    ///
+    /// See [`ProguardCache::remap_stacktrace_typed`].
+    pub fn remap_stacktrace_typed(
+        &self,
+        key: &MappingKey,
+        trace: &StackTrace<'_>,
+    ) -> Result<StackTrace<'static>, RegistryError> {
+        self.remap_stacktrace_typed_with_options(key, trace, &RemapOptions::default())
+    }
+
+    /// Remaps a complete Java StackTrace with the cache registered under `key`.
+    ///
+    /// See [`ProguardCache::remap_stacktrace_typed_with_options`].
+    pub fn remap_stacktrace_typed_with_options(
+        &self,
+        key: &MappingKey,
+        trace: &StackTrace<'_>,
+        options: &RemapOptions,
+    ) -> Result<StackTrace<'static>, RegistryError> {
+        self.with_cache(key, |cache| {
+            cache
+                .remap_stacktrace_typed_with_options(trace, options)
+                .into_owned()
+        })
+    }
+
+    /// Returns the bytes of the cache registered under `key`, reading the
+    /// cache file if it isn't loaded.
+    fn load(&self, key: &MappingKey) -> Result<Arc<[u8]>, RegistryError> {
+        let index = *self
+            .keys
+            .get(key)
+            .ok_or_else(|| RegistryError::UnknownKey(key.clone()))?;
+        let path = match self.sources.get(index).and_then(Option::as_ref) {
+            Some(CacheSource::Bytes(data)) => return Ok(Arc::clone(data)),
+            Some(CacheSource::File(path)) => path,
+            None => return Err(RegistryError::UnknownKey(key.clone())),
+        };
+
+        {
+            let mut loaded = self.loaded.lock().unwrap_or_else(PoisonError::into_inner);
+            let pos = loaded.iter().position(|(loaded, _)| *loaded == index);
+            if let Some(entry) = pos.and_then(|pos| loaded.remove(pos)) {
+                let data = Arc::clone(&entry.1);
+                loaded.push_front(entry);
+                return Ok(data);
+            }
+        }
+
+        // Read the file without holding the lock, so other caches stay usable.
+        let data: Arc<[u8]> = std::fs::read(path)?.into();
+        ProguardCache::parse(&data)?;
+
+        let mut loaded = self.loaded.lock().unwrap_or_else(PoisonError::into_inner);
+        if !loaded.iter().any(|(loaded, _)| *loaded == index) {
+            loaded.push_front((index, Arc::clone(&data)));
+            loaded.truncate(self.capacity);
+        }
+        Ok(data)
+    }
+}
+
+impl fmt::Debug for CacheRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CacheRegistry")
+            .field("keys", &self.keys)
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> Vec<u8> {
+        let mut buf = Vec::new();
+        ProguardCache::write(&ProguardMapping::new(b"some.Main -> b:"), &mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn reuse_slots() {
+        let a = MappingKey::map_id("a");
+        let b = MappingKey::map_id("b");
+        let mut registry = CacheRegistry::new(4);
+
+        registry.insert(a.clone(), cache()).unwrap();
+        registry.insert(a.clone(), cache()).unwrap();
+        registry.insert(b.clone(), cache()).unwrap();
+        assert_eq!(registry.sources.len(), 2);
+
+        assert!(registry.remove(&a));
+        assert!(!registry.remove(&a));
+        assert!(registry.sources[registry.keys[&b]].is_some());
+        assert_eq!(registry.sources.iter().flatten().count(), 1);
+
+        registry.insert(a.clone(), cache()).unwrap();
+        assert_eq!(registry.sources.len(), 2);
+    }
+
+    #[test]
+    fn remove_loaded() {
+        let path =
+            std::env::temp_dir().join(format!("proguard-remove-{}.prgcache", std::process::id()));
+        std::fs::write(&path, cache()).unwrap();
+
+        let key = MappingKey::map_id("a");
+        let mut registry = CacheRegistry::new(4);
+        registry.insert_file(key.clone(), &path);
+        registry.remap_stacktrace(&key, "").unwrap();
+        assert_eq!(registry.loaded.get_mut().unwrap().len(), 1);
+
+        registry.insert_file(key.clone(), &path);
+        assert!(registry.loaded.get_mut().unwrap().is_empty());
+
+        registry.remap_stacktrace(&key, "").unwrap();
+        registry.remove(&key);
+        assert!(registry.loaded.get_mut().unwrap().is_empty());
+        assert_eq!(registry.sources.iter().flatten().count(), 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
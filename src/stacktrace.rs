@@ -2,15 +2,25 @@
 
 use std::borrow::Cow;
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::iter::Peekable;
+use std::str::Lines;
 
 /// A full Java StackTrace as printed by [`Throwable.printStackTrace()`].
 ///
 /// [`Throwable.printStackTrace()`]: https://docs.oracle.com/en/java/javase/14/docs/api/java.base/java/lang/Throwable.html#printStackTrace()
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StackTrace<'s> {
+    #[cfg_attr(feature = "serde", serde(borrow, default))]
     pub(crate) exception: Option<Throwable<'s>>,
+    #[cfg_attr(feature = "serde", serde(borrow, default))]
     pub(crate) frames: Vec<StackFrame<'s>>,
+    #[cfg_attr(feature = "serde", serde(borrow, default))]
     pub(crate) cause: Option<Box<StackTrace<'s>>>,
+    #[cfg_attr(feature = "serde", serde(borrow, default))]
+    pub(crate) suppressed: Vec<StackTrace<'s>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) elided_frames: usize,
 }
 
 impl<'s> StackTrace<'s> {
@@ -20,6 +30,8 @@ impl<'s> StackTrace<'s> {
             exception,
             frames,
             cause: None,
+            suppressed: Vec::new(),
+            elided_frames: 0,
         }
     }
 
@@ -33,9 +45,23 @@ impl<'s> StackTrace<'s> {
             exception,
             frames,
             cause: Some(Box::new(cause)),
+            suppressed: Vec::new(),
+            elided_frames: 0,
         }
     }
 
+    /// Adds exceptions that were suppressed in favor of this one.
+    pub fn with_suppressed(mut self, suppressed: Vec<StackTrace<'s>>) -> Self {
+        self.suppressed = suppressed;
+        self
+    }
+
+    /// Sets the number of frames omitted by a `... N more` line.
+    pub fn with_elided_frames(mut self, elided_frames: usize) -> Self {
+        self.elided_frames = elided_frames;
+        self
+    }
+
     /// Parses a StackTrace from a full Java StackTrace.
     ///
     /// # Examples
@@ -91,26 +117,79 @@ impl<'s> StackTrace<'s> {
     pub fn cause(&self) -> Option<&StackTrace<'_>> {
         self.cause.as_deref()
     }
-}
 
-impl Display for StackTrace<'_> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    /// The exceptions that were suppressed in favor of this one, for example
+    /// by a `try`-with-resources statement.
+    pub fn suppressed(&self) -> &[StackTrace<'_>] {
+        &self.suppressed
+    }
+
+    /// The number of frames omitted by a `... N more` line.
+    ///
+    /// Java omits the frames a cause or suppressed exception has in common
+    /// with the enclosing trace.
+    pub fn elided_frames(&self) -> usize {
+        self.elided_frames
+    }
+
+    /// Converts `self` into a StackTrace that doesn't borrow from anything.
+    ///
+    /// This allows keeping a parsed or remapped StackTrace around after the
+    /// input or the mapper it borrows from was dropped.
+    pub fn into_owned(self) -> StackTrace<'static> {
+        StackTrace {
+            exception: self.exception.map(Throwable::into_owned),
+            frames: self
+                .frames
+                .into_iter()
+                .map(StackFrame::into_owned)
+                .collect(),
+            cause: self.cause.map(|cause| Box::new(cause.into_owned())),
+            suppressed: self
+                .suppressed
+                .into_iter()
+                .map(StackTrace::into_owned)
+                .collect(),
+            elided_frames: self.elided_frames,
+        }
+    }
+
+    /// Writes the trace like Java's `printEnclosedStackTrace`, with `caption`
+    /// before the exception and `prefix` before every line.
+    fn fmt_enclosed(&self, f: &mut Formatter<'_>, prefix: &str, caption: &str) -> FmtResult {
         if let Some(exception) = &self.exception {
-            writeln!(f, "{exception}")?;
+            writeln!(f, "{prefix}{caption}{exception}")?;
+        } else if !caption.is_empty() {
+            writeln!(f, "{prefix}{caption}")?;
         }
 
         for frame in &self.frames {
-            writeln!(f, "    {frame}")?;
+            writeln!(f, "{prefix}    {frame}")?;
+        }
+
+        if self.elided_frames > 0 {
+            writeln!(f, "{prefix}    ... {} more", self.elided_frames)?;
+        }
+
+        let suppressed_prefix = format!("{prefix}    ");
+        for suppressed in &self.suppressed {
+            suppressed.fmt_enclosed(f, &suppressed_prefix, "Suppressed: ")?;
         }
 
         if let Some(cause) = &self.cause {
-            write!(f, "Caused by: {cause}")?;
+            cause.fmt_enclosed(f, prefix, "Caused by: ")?;
         }
 
         Ok(())
     }
 }
 
+impl Display for StackTrace<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.fmt_enclosed(f, "", "")
+    }
+}
+
 fn parse_stacktrace(content: &str) -> Option<StackTrace<'_>> {
     let mut lines = content.lines().peekable();
 
@@ -119,24 +198,7 @@ fn parse_stacktrace(content: &str) -> Option<StackTrace<'_>> {
         lines.next();
     }
 
-    let mut stacktrace = StackTrace {
-        exception,
-        frames: vec![],
-        cause: None,
-    };
-    let mut current = &mut stacktrace;
-
-    for line in &mut lines {
-        if let Some(frame) = parse_frame(line) {
-            current.frames.push(frame);
-        } else if let Some(line) = line.strip_prefix("Caused by: ") {
-            current = current.cause.insert(Box::new(StackTrace {
-                exception: parse_throwable(line),
-                frames: vec![],
-                cause: None,
-            }));
-        }
-    }
+    let stacktrace = parse_enclosed(&mut lines, exception, None);
 
     if stacktrace.exception.is_some() || !stacktrace.frames.is_empty() {
         Some(stacktrace)
@@ -145,43 +207,125 @@ fn parse_stacktrace(content: &str) -> Option<StackTrace<'_>> {
     }
 }
 
+/// Parses the frames, suppressed exceptions and causes of a trace from `lines`.
+///
+/// `indent` is the indentation of the line introducing the trace, and `None`
+/// for the outermost trace. Parsing stops at the first line that belongs to
+/// an enclosing trace, which is a `Suppressed: ` line that is not indented
+/// deeper, or a `Caused by: ` line that is indented less.
+fn parse_enclosed<'s>(
+    lines: &mut Peekable<Lines<'s>>,
+    exception: Option<Throwable<'s>>,
+    indent: Option<usize>,
+) -> StackTrace<'s> {
+    let mut trace = StackTrace::new(exception, vec![]);
+
+    while let Some(line) = lines.peek() {
+        let trimmed = line.trim_start();
+        let line_indent = line.len() - trimmed.len();
+
+        if let Some(frame) = parse_frame(line) {
+            trace.frames.push(frame);
+        } else if let Some(elided_frames) = parse_elided_frames(trimmed) {
+            trace.elided_frames = elided_frames;
+        } else if let Some(suppressed) = trimmed.strip_prefix("Suppressed: ") {
+            if indent.is_some_and(|indent| line_indent <= indent) {
+                break;
+            }
+            let exception = parse_throwable(suppressed);
+            lines.next();
+            let suppressed = parse_enclosed(lines, exception, Some(line_indent));
+            trace.suppressed.push(suppressed);
+            continue;
+        } else if let Some(cause) = trimmed.strip_prefix("Caused by: ") {
+            if indent.is_some_and(|indent| line_indent < indent) {
+                break;
+            }
+            let exception = parse_throwable(cause);
+            lines.next();
+            let cause = parse_enclosed(lines, exception, Some(line_indent));
+            trace.cause = Some(Box::new(cause));
+            // Anything after the cause belongs to an enclosing trace.
+            break;
+        }
+        lines.next();
+    }
+
+    trace
+}
+
+/// Parses the number of omitted frames from a `... N more` line.
+fn parse_elided_frames(line: &str) -> Option<usize> {
+    line.strip_prefix("... ")?
+        .strip_suffix(" more")?
+        .parse()
+        .ok()
+}
+
 /// A Java StackFrame.
 ///
 /// Basically a Rust version of the Java [`StackTraceElement`].
 ///
 /// [`StackTraceElement`]: https://docs.oracle.com/en/java/javase/14/docs/api/java.base/java/lang/StackTraceElement.html
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StackFrame<'s> {
-    pub(crate) class: &'s str,
-    pub(crate) method: &'s str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub(crate) class: Cow<'s, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub(crate) method: Cow<'s, str>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub(crate) line: Option<usize>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(borrow, default, deserialize_with = "deserialize_optional_cow")
+    )]
     pub(crate) file: Option<Cow<'s, str>>,
-    pub(crate) parameters: Option<&'s str>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(borrow, default, deserialize_with = "deserialize_optional_cow")
+    )]
+    pub(crate) parameters: Option<Cow<'s, str>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(borrow, default, deserialize_with = "deserialize_optional_cow")
+    )]
+    pub(crate) signature: Option<Cow<'s, str>>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub(crate) method_synthesized: bool,
+    #[cfg_attr(
+        feature = "serde",
+        serde(borrow, default, skip_serializing_if = "Option::is_none")
+    )]
+    pub(crate) provenance: Option<FrameProvenance<'s>>,
 }
 
 impl<'s> StackFrame<'s> {
     /// Create a new StackFrame.
     pub fn new(class: &'s str, method: &'s str, line: usize) -> Self {
         Self {
-            class,
-            method,
+            class: Cow::Borrowed(class),
+            method: Cow::Borrowed(method),
             line: Some(line),
             file: None,
             parameters: None,
+            signature: None,
             method_synthesized: false,
+            provenance: None,
         }
     }
 
     /// Create a new StackFrame with file information.
     pub fn with_file(class: &'s str, method: &'s str, line: usize, file: &'s str) -> Self {
         Self {
-            class,
-            method,
+            class: Cow::Borrowed(class),
+            method: Cow::Borrowed(method),
             line: Some(line),
             file: Some(Cow::Borrowed(file)),
             parameters: None,
+            signature: None,
             method_synthesized: false,
+            provenance: None,
         }
     }
 
@@ -189,15 +333,26 @@ impl<'s> StackFrame<'s> {
     /// This is useful for when we try to do deobfuscation with no line information.
     pub fn with_parameters(class: &'s str, method: &'s str, arguments: &'s str) -> Self {
         Self {
-            class,
-            method,
+            class: Cow::Borrowed(class),
+            method: Cow::Borrowed(method),
             line: None,
             file: None,
-            parameters: Some(arguments),
+            parameters: Some(Cow::Borrowed(arguments)),
+            signature: None,
             method_synthesized: false,
+            provenance: None,
         }
     }
 
+    /// Attaches the obfuscated JVM descriptor of `self`'s method, for example `"(I)V"`.
+    ///
+    /// When remapping, the descriptor is compared with the residual signatures
+    /// in the mapping to tell apart overloads that were renamed to the same name.
+    pub fn with_signature(mut self, signature: &'s str) -> Self {
+        self.signature = Some(Cow::Borrowed(signature));
+        self
+    }
+
     /// Flags `self`'s method as being synthesized by the compiler according to `is_synthesized`.
     pub fn with_method_synthesized(mut self, is_synthesized: bool) -> Self {
         self.method_synthesized = is_synthesized;
@@ -229,12 +384,12 @@ impl<'s> StackFrame<'s> {
 
     /// The class of the StackFrame.
     pub fn class(&self) -> &str {
-        self.class
+        &self.class
     }
 
     /// The method of the StackFrame.
     pub fn method(&self) -> &str {
-        self.method
+        &self.method
     }
 
     /// The fully qualified method name, including the class.
@@ -256,13 +411,161 @@ impl<'s> StackFrame<'s> {
 
     /// The parameters of the StackFrame
     pub fn parameters(&self) -> Option<&str> {
-        self.parameters
+        self.parameters.as_deref()
+    }
+
+    /// The obfuscated JVM descriptor of the StackFrame's method, if known.
+    pub fn signature(&self) -> Option<&str> {
+        self.signature.as_deref()
     }
 
     /// Returns whether this frame's method was synthesized by the compiler.
     pub fn method_synthesized(&self) -> bool {
         self.method_synthesized
     }
+
+    /// Describes how this frame was produced by remapping.
+    ///
+    /// Returns `None` for frames that were not produced by remapping.
+    pub fn provenance(&self) -> Option<&FrameProvenance<'s>> {
+        self.provenance.as_ref()
+    }
+
+    /// Converts `self` into a StackFrame that doesn't borrow from anything.
+    pub fn into_owned(self) -> StackFrame<'static> {
+        StackFrame {
+            class: Cow::Owned(self.class.into_owned()),
+            method: Cow::Owned(self.method.into_owned()),
+            line: self.line,
+            file: self.file.map(|file| Cow::Owned(file.into_owned())),
+            parameters: self
+                .parameters
+                .map(|parameters| Cow::Owned(parameters.into_owned())),
+            signature: self
+                .signature
+                .map(|signature| Cow::Owned(signature.into_owned())),
+            method_synthesized: self.method_synthesized,
+            provenance: self.provenance.map(FrameProvenance::into_owned),
+        }
+    }
+}
+
+// The provenance is not part of a frame's identity, so that remapped frames
+// still compare equal to the frames they describe.
+impl PartialEq for StackFrame<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.class == other.class
+            && self.method == other.method
+            && self.line == other.line
+            && self.file == other.file
+            && self.parameters == other.parameters
+            && self.signature == other.signature
+            && self.method_synthesized == other.method_synthesized
+    }
+}
+
+/// Where the file name of a remapped [`StackFrame`] comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum FileSource {
+    /// The file name is declared by a `sourceFile` header in the mapping.
+    Mapping,
+    /// The file name was synthesized from the class name.
+    Synthesized,
+}
+
+/// Describes how a remapped [`StackFrame`] was produced.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameProvenance<'s> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub(crate) minified_class: Cow<'s, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub(crate) minified_method: Cow<'s, str>,
+    pub(crate) minified_line: Option<usize>,
+    pub(crate) inline_depth: usize,
+    pub(crate) outline_callsite: bool,
+    pub(crate) class_only: bool,
+    pub(crate) file_source: Option<FileSource>,
+}
+
+impl<'s> FrameProvenance<'s> {
+    /// Creates the provenance of the frames remapped from the minified `frame`.
+    pub(crate) fn new(frame: &StackFrame<'s>) -> Self {
+        Self {
+            minified_class: frame.class.clone(),
+            minified_method: frame.method.clone(),
+            minified_line: frame.line,
+            inline_depth: 0,
+            outline_callsite: false,
+            class_only: false,
+            file_source: None,
+        }
+    }
+
+    /// The class of the minified frame this frame was remapped from.
+    pub fn minified_class(&self) -> &str {
+        &self.minified_class
+    }
+
+    /// The method of the minified frame this frame was remapped from.
+    pub fn minified_method(&self) -> &str {
+        &self.minified_method
+    }
+
+    /// The line of the minified frame this frame was remapped from.
+    pub fn minified_line(&self) -> Option<usize> {
+        self.minified_line
+    }
+
+    /// The number of frames this frame's method was inlined through.
+    ///
+    /// This is `0` for the outermost frame of an inline chain, which is the
+    /// method that exists in the minified code, `1` for a method inlined into
+    /// it, and so on.
+    pub fn inline_depth(&self) -> usize {
+        self.inline_depth
+    }
+
+    /// Returns whether this frame's method was inlined into another method.
+    pub fn is_inlined(&self) -> bool {
+        self.inline_depth > 0
+    }
+
+    /// Returns whether this frame called an outlined method, and its line was
+    /// taken from the outline callsite information of the mapping.
+    pub fn is_outline_callsite(&self) -> bool {
+        self.outline_callsite
+    }
+
+    /// Returns whether only the class of this frame could be remapped.
+    ///
+    /// This happens when the mapping doesn't know the frame's method, or no
+    /// mapping of the method covers the frame's line. The method and line are
+    /// then kept from the minified frame.
+    pub fn is_class_only(&self) -> bool {
+        self.class_only
+    }
+
+    /// Where the file name of this frame comes from, if it has one.
+    pub fn file_source(&self) -> Option<FileSource> {
+        self.file_source
+    }
+
+    /// Converts `self` into a FrameProvenance that doesn't borrow from anything.
+    pub fn into_owned(self) -> FrameProvenance<'static> {
+        FrameProvenance {
+            minified_class: Cow::Owned(self.minified_class.into_owned()),
+            minified_method: Cow::Owned(self.minified_method.into_owned()),
+            minified_line: self.minified_line,
+            inline_depth: self.inline_depth,
+            outline_callsite: self.outline_callsite,
+            class_only: self.class_only,
+            file_source: self.file_source,
+        }
+    }
 }
 
 impl Display for StackFrame<'_> {
@@ -297,12 +600,14 @@ pub(crate) fn parse_frame(line: &str) -> Option<StackFrame<'_>> {
     };
 
     Some(StackFrame {
-        class,
-        method,
+        class: Cow::Borrowed(class),
+        method: Cow::Borrowed(method),
         file: Some(Cow::Borrowed(file)),
         line,
         parameters: None,
+        signature: None,
         method_synthesized: false,
+        provenance: None,
     })
 }
 
@@ -312,16 +617,22 @@ pub(crate) fn parse_frame(line: &str) -> Option<StackFrame<'_>> {
 ///
 /// [`Throwable.printStackTrace()`]: https://docs.oracle.com/en/java/javase/14/docs/api/java.base/java/lang/Throwable.html#printStackTrace()
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Throwable<'s> {
-    pub(crate) class: &'s str,
-    pub(crate) message: Option<&'s str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub(crate) class: Cow<'s, str>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(borrow, default, deserialize_with = "deserialize_optional_cow")
+    )]
+    pub(crate) message: Option<Cow<'s, str>>,
 }
 
 impl<'s> Throwable<'s> {
     /// Create a new Throwable.
     pub fn new(class: &'s str) -> Self {
         Self {
-            class,
+            class: Cow::Borrowed(class),
             message: None,
         }
     }
@@ -329,8 +640,8 @@ impl<'s> Throwable<'s> {
     /// Create a new Throwable with message.
     pub fn with_message(class: &'s str, message: &'s str) -> Self {
         Self {
-            class,
-            message: Some(message),
+            class: Cow::Borrowed(class),
+            message: Some(Cow::Borrowed(message)),
         }
     }
 
@@ -352,12 +663,20 @@ impl<'s> Throwable<'s> {
 
     /// The class of this Throwable.
     pub fn class(&self) -> &str {
-        self.class
+        &self.class
     }
 
     /// The optional message of this Throwable.
     pub fn message(&self) -> Option<&str> {
-        self.message
+        self.message.as_deref()
+    }
+
+    /// Converts `self` into a Throwable that doesn't borrow from anything.
+    pub fn into_owned(self) -> Throwable<'static> {
+        Throwable {
+            class: Cow::Owned(self.class.into_owned()),
+            message: self.message.map(|message| Cow::Owned(message.into_owned())),
+        }
     }
 }
 
@@ -365,7 +684,7 @@ impl Display for Throwable<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         write!(f, "{}", self.class)?;
 
-        if let Some(message) = self.message {
+        if let Some(message) = &self.message {
             write!(f, ": {message}")?;
         }
 
@@ -384,15 +703,35 @@ pub(crate) fn parse_throwable(line: &str) -> Option<Throwable<'_>> {
 
     let mut class_split = line.splitn(2, ": ");
     let class = class_split.next()?;
-    let message = class_split.next();
+    let message = class_split.next().map(Cow::Borrowed);
 
     if class.contains(' ') {
         None
     } else {
-        Some(Throwable { class, message })
+        Some(Throwable {
+            class: Cow::Borrowed(class),
+            message,
+        })
     }
 }
 
+/// Deserializes an optional string, borrowing it from the input if possible.
+///
+/// `serde` only borrows `Cow<str>` fields that are not wrapped in an `Option`.
+#[cfg(feature = "serde")]
+fn deserialize_optional_cow<'de: 's, 's, D>(
+    deserializer: D,
+) -> Result<Option<Cow<'s, str>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(serde::Deserialize)]
+    struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    let value: Option<Borrowed<'s>> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(value.map(|Borrowed(value)| value))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -401,32 +740,40 @@ mod tests {
     fn print_stack_trace() {
         let trace = StackTrace {
             exception: Some(Throwable {
-                class: "com.example.MainFragment",
-                message: Some("Crash"),
+                class: "com.example.MainFragment".into(),
+                message: Some("Crash".into()),
             }),
             frames: vec![StackFrame {
-                class: "com.example.Util",
-                method: "show",
+                class: "com.example.Util".into(),
+                method: "show".into(),
                 line: Some(5),
                 file: Some(Cow::Borrowed("Util.java")),
                 parameters: None,
+                signature: None,
                 method_synthesized: false,
+                provenance: None,
             }],
             cause: Some(Box::new(StackTrace {
                 exception: Some(Throwable {
-                    class: "com.example.Other",
-                    message: Some("Invalid data"),
+                    class: "com.example.Other".into(),
+                    message: Some("Invalid data".into()),
                 }),
                 frames: vec![StackFrame {
-                    class: "com.example.Parser",
-                    method: "parse",
+                    class: "com.example.Parser".into(),
+                    method: "parse".into(),
                     line: Some(115),
                     file: None,
                     parameters: None,
+                    signature: None,
                     method_synthesized: false,
+                    provenance: None,
                 }],
                 cause: None,
+                suppressed: vec![],
+                elided_frames: 0,
             })),
+            suppressed: vec![],
+            elided_frames: 0,
         };
         let expect = "\
 com.example.MainFragment: Crash
@@ -437,17 +784,81 @@ Caused by: com.example.Other: Invalid data
         assert_eq!(expect, trace.to_string());
     }
 
+    #[test]
+    fn suppressed_stack_trace() {
+        // As printed by `Throwable.printStackTrace()`.
+        let input = "\
+java.lang.Exception: Main
+\tat Main.main(Main.java:5)
+\tSuppressed: java.lang.Exception: Close 1
+\t\tat Resource.close(Resource.java:10)
+\t\tat Main.main(Main.java:4)
+\t\t... 1 more
+\tCaused by: java.lang.Exception: Inner
+\t\tat Resource.flush(Resource.java:20)
+\t\t... 3 more
+\tSuppressed: java.lang.Exception: Close 2
+\t\t... 1 more
+Caused by: java.lang.Exception: Cause
+\tat Main.run(Main.java:9)
+\t... 1 more
+";
+        let trace = parse_stacktrace(input).unwrap();
+        assert_eq!(trace.frames().len(), 1);
+        assert_eq!(trace.elided_frames(), 0);
+
+        let [first, second] = trace.suppressed() else {
+            panic!("expected two suppressed exceptions");
+        };
+        assert_eq!(first.exception().unwrap().message(), Some("Close 1"));
+        assert_eq!(first.frames().len(), 2);
+        assert_eq!(first.elided_frames(), 1);
+        let inner = first.cause().unwrap();
+        assert_eq!(inner.exception().unwrap().class(), "java.lang.Exception");
+        assert_eq!(inner.frames().len(), 1);
+        assert_eq!(inner.elided_frames(), 3);
+        assert!(second.frames().is_empty());
+        assert_eq!(second.elided_frames(), 1);
+
+        let cause = trace.cause().unwrap();
+        assert_eq!(cause.exception().unwrap().message(), Some("Cause"));
+        assert_eq!(cause.frames().len(), 1);
+        assert_eq!(cause.elided_frames(), 1);
+        assert!(cause.suppressed().is_empty());
+
+        let expect = "\
+java.lang.Exception: Main
+    at Main.main(Main.java:5)
+    Suppressed: java.lang.Exception: Close 1
+        at Resource.close(Resource.java:10)
+        at Main.main(Main.java:4)
+        ... 1 more
+    Caused by: java.lang.Exception: Inner
+        at Resource.flush(Resource.java:20)
+        ... 3 more
+    Suppressed: java.lang.Exception: Close 2
+        ... 1 more
+Caused by: java.lang.Exception: Cause
+    at Main.run(Main.java:9)
+    ... 1 more
+";
+        assert_eq!(trace.to_string(), expect);
+        assert_eq!(parse_stacktrace(expect).unwrap(), trace);
+    }
+
     #[test]
     fn stack_frame() {
         let line = "at com.example.MainFragment.onClick(SourceFile:1)";
         let stack_frame = parse_frame(line);
         let expect = Some(StackFrame {
-            class: "com.example.MainFragment",
-            method: "onClick",
+            class: "com.example.MainFragment".into(),
+            method: "onClick".into(),
             line: Some(1),
             file: Some(Cow::Borrowed("SourceFile")),
             parameters: None,
+            signature: None,
             method_synthesized: false,
+            provenance: None,
         });
 
         assert_eq!(expect, stack_frame);
@@ -466,12 +877,14 @@ Caused by: com.example.Other: Invalid data
     #[test]
     fn print_stack_frame() {
         let frame = StackFrame {
-            class: "com.example.MainFragment",
-            method: "onClick",
+            class: "com.example.MainFragment".into(),
+            method: "onClick".into(),
             line: Some(1),
             file: None,
             parameters: None,
+            signature: None,
             method_synthesized: false,
+            provenance: None,
         };
 
         assert_eq!(
@@ -480,12 +893,14 @@ Caused by: com.example.Other: Invalid data
         );
 
         let frame = StackFrame {
-            class: "com.example.MainFragment",
-            method: "onClick",
+            class: "com.example.MainFragment".into(),
+            method: "onClick".into(),
             line: Some(1),
             file: Some(Cow::Borrowed("SourceFile")),
             parameters: None,
+            signature: None,
             method_synthesized: false,
+            provenance: None,
         };
 
         assert_eq!(
@@ -499,8 +914,8 @@ Caused by: com.example.Other: Invalid data
         let line = "com.example.MainFragment: Crash!";
         let throwable = parse_throwable(line);
         let expect = Some(Throwable {
-            class: "com.example.MainFragment",
-            message: Some("Crash!"),
+            class: "com.example.MainFragment".into(),
+            message: Some("Crash!".into()),
         });
 
         assert_eq!(expect, throwable);
@@ -509,17 +924,47 @@ Caused by: com.example.Other: Invalid data
     #[test]
     fn print_throwable() {
         let throwable = Throwable {
-            class: "com.example.MainFragment",
+            class: "com.example.MainFragment".into(),
             message: None,
         };
 
         assert_eq!("com.example.MainFragment", throwable.to_string());
 
         let throwable = Throwable {
-            class: "com.example.MainFragment",
-            message: Some("Crash"),
+            class: "com.example.MainFragment".into(),
+            message: Some("Crash".into()),
         };
 
         assert_eq!("com.example.MainFragment: Crash", throwable.to_string());
     }
+
+    #[test]
+    fn into_owned() {
+        let trace = {
+            let stacktrace = String::from("a.b: Crash\n    at a.c.d(SourceFile:1)\n");
+            parse_stacktrace(&stacktrace).unwrap().into_owned()
+        };
+
+        let expect = StackTrace::new(
+            Some(Throwable::with_message("a.b", "Crash")),
+            vec![StackFrame::with_file("a.c", "d", 1, "SourceFile")],
+        );
+        assert_eq!(trace, expect);
+        assert!(matches!(trace.frames[0].class, Cow::Owned(_)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_borrowed() {
+        let json = r#"{
+            "exception": {"class": "a.b", "message": "Crash"},
+            "frames": [{"class": "a.c", "method": "d", "line": 1, "file": "SourceFile"}]
+        }"#;
+        let trace: StackTrace = serde_json::from_str(json).unwrap();
+
+        let message = &trace.exception.as_ref().unwrap().message;
+        assert!(matches!(message, Some(Cow::Borrowed("Crash"))));
+        let file = &trace.frames[0].file;
+        assert!(matches!(file, Some(Cow::Borrowed("SourceFile"))));
+    }
 }
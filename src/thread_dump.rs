@@ -0,0 +1,465 @@
+//! A Parser for Java thread dumps.
+
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::stacktrace::{self, StackFrame, StackTrace};
+
+/// A Java thread dump, as printed by `jstack` or when sending `SIGQUIT` to
+/// the JVM or an Android app.
+///
+/// A thread ends at the next line that is not indented. Lines outside of
+/// threads, such as the `Full thread dump` banner or ART's
+/// `----- end 1234 -----`, are not part of the model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreadDump<'s> {
+    pub(crate) threads: Vec<Thread<'s>>,
+}
+
+impl<'s> ThreadDump<'s> {
+    /// Create a new ThreadDump.
+    pub fn new(threads: Vec<Thread<'s>>) -> Self {
+        Self { threads }
+    }
+
+    /// Parses a ThreadDump from the output of `jstack` or `kill -3`.
+    ///
+    /// Returns `None` if the dump does not contain any threads.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use proguard::{StackFrame, ThreadDump, ThreadLine};
+    ///
+    /// let dump = "\
+    /// \"main\" prio=5 tid=1 Blocked
+    ///   at a.b.c(SourceFile:12)
+    ///   - waiting to lock <0x0abc> (a a.d) held by thread 12
+    /// ";
+    /// let parsed = ThreadDump::try_parse(dump.as_bytes()).unwrap();
+    /// let [thread] = parsed.threads() else { panic!() };
+    /// assert_eq!(thread.name(), "main");
+    ///
+    /// let [ThreadLine::Frame(frame), ThreadLine::Monitor(monitor)] = thread.lines() else {
+    ///     panic!()
+    /// };
+    /// assert_eq!(frame, &StackFrame::with_file("a.b", "c", 12, "SourceFile"));
+    /// assert_eq!(monitor.class(), Some("a.d"));
+    /// ```
+    pub fn try_parse(dump: &'s [u8]) -> Option<Self> {
+        let dump = std::str::from_utf8(dump).ok()?;
+        parse_thread_dump(dump)
+    }
+
+    /// All threads of the dump.
+    pub fn threads(&self) -> &[Thread<'s>] {
+        &self.threads
+    }
+}
+
+impl Display for ThreadDump<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for (idx, thread) in self.threads.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{thread}")?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_thread_dump(content: &str) -> Option<ThreadDump<'_>> {
+    let mut threads: Vec<Thread<'_>> = vec![];
+    let mut in_thread = false;
+
+    for line in content.lines() {
+        if let Some(thread) = parse_thread_header(line) {
+            threads.push(thread);
+            in_thread = true;
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with(char::is_whitespace) {
+            in_thread = false;
+        }
+        let Some(thread) = threads.last_mut().filter(|_| in_thread) else {
+            continue;
+        };
+
+        let line = if let Some(frame) = stacktrace::parse_frame(line) {
+            ThreadLine::Frame(frame)
+        } else if let Some(monitor) = parse_monitor(line) {
+            ThreadLine::Monitor(monitor)
+        } else {
+            ThreadLine::Other(line.trim_end())
+        };
+        thread.lines.push(line);
+    }
+
+    (!threads.is_empty()).then_some(ThreadDump { threads })
+}
+
+/// A thread in a [`ThreadDump`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Thread<'s> {
+    pub(crate) header: &'s str,
+    pub(crate) name: &'s str,
+    pub(crate) lines: Vec<ThreadLine<'s>>,
+}
+
+impl<'s> Thread<'s> {
+    /// Create a new Thread from its header line and the lines following it.
+    ///
+    /// Returns `None` if `header` does not start with the quoted name of the
+    /// thread.
+    pub fn new(header: &'s str, lines: Vec<ThreadLine<'s>>) -> Option<Self> {
+        let mut thread = parse_thread_header(header)?;
+        thread.lines = lines;
+        Some(thread)
+    }
+
+    /// The complete header line of the thread, such as
+    /// `"main" prio=5 tid=1 Blocked`.
+    pub fn header(&self) -> &'s str {
+        self.header
+    }
+
+    /// The name of the thread.
+    pub fn name(&self) -> &'s str {
+        self.name
+    }
+
+    /// The lines following the header.
+    pub fn lines(&self) -> &[ThreadLine<'s>] {
+        &self.lines
+    }
+
+    /// The frames of the thread's stack.
+    pub fn frames(&self) -> impl Iterator<Item = &StackFrame<'s>> {
+        self.lines.iter().filter_map(|line| match line {
+            ThreadLine::Frame(frame) => Some(frame),
+            _ => None,
+        })
+    }
+}
+
+impl Display for Thread<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(f, "{}", self.header)?;
+        for line in &self.lines {
+            match line {
+                ThreadLine::Other(line) => writeln!(f, "{line}")?,
+                line => writeln!(f, "    {line}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_thread_header(line: &str) -> Option<Thread<'_>> {
+    let header = line.trim_end();
+    let (name, _) = header.strip_prefix('"')?.split_once('"')?;
+    Some(Thread {
+        header,
+        name,
+        lines: vec![],
+    })
+}
+
+/// A line following the header of a [`Thread`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ThreadLine<'s> {
+    /// A frame of the thread's stack.
+    Frame(StackFrame<'s>),
+    /// A monitor the thread holds or waits for.
+    Monitor(Monitor<'s>),
+    /// Any other line, such as thread details or native frames, with its
+    /// indentation but without trailing whitespace.
+    Other(&'s str),
+}
+
+impl Display for ThreadLine<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ThreadLine::Frame(frame) => frame.fmt(f),
+            ThreadLine::Monitor(monitor) => monitor.fmt(f),
+            ThreadLine::Other(line) => f.write_str(line),
+        }
+    }
+}
+
+/// A lock or monitor line in a [`Thread`], such as
+/// `- waiting to lock <0x0abc> (a a.b.c) held by thread 12`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Monitor<'s> {
+    pub(crate) action: &'s str,
+    pub(crate) address: &'s str,
+    pub(crate) class: Option<Cow<'s, str>>,
+    pub(crate) suffix: &'s str,
+}
+
+impl<'s> Monitor<'s> {
+    /// Parses a Monitor from a lock line.
+    pub fn try_parse(line: &'s [u8]) -> Option<Self> {
+        let line = std::str::from_utf8(line).ok()?;
+        parse_monitor(line)
+    }
+
+    /// What the thread does with the monitor, such as `locked` or
+    /// `waiting to lock`.
+    ///
+    /// This is empty for the entries of `Locked ownable synchronizers`.
+    pub fn action(&self) -> &str {
+        self.action
+    }
+
+    /// The address of the monitor, without the angle brackets.
+    pub fn address(&self) -> &str {
+        self.address
+    }
+
+    /// The class of the monitor object, if known.
+    pub fn class(&self) -> Option<&str> {
+        self.class.as_deref()
+    }
+
+    /// The rest of the line, such as ` held by thread 12`.
+    pub fn suffix(&self) -> &str {
+        self.suffix
+    }
+}
+
+impl Display for Monitor<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("-")?;
+        if !self.action.is_empty() {
+            write!(f, " {}", self.action)?;
+        }
+        write!(f, " <{}>", self.address)?;
+        if let Some(class) = &self.class {
+            write!(f, " (a {class})")?;
+        }
+        f.write_str(self.suffix)
+    }
+}
+
+fn parse_monitor(line: &str) -> Option<Monitor<'_>> {
+    let line = line.trim().strip_prefix("- ")?;
+    let (action, rest) = line.split_once('<')?;
+    let (address, rest) = rest.split_once('>')?;
+
+    let (class, suffix) = match rest.trim_start().strip_prefix("(a ") {
+        Some(rest) => {
+            let (class, suffix) = rest.split_once(')')?;
+            (Some(Cow::Borrowed(class)), suffix)
+        }
+        None => (None, rest),
+    };
+
+    Some(Monitor {
+        action: action.trim(),
+        address,
+        class,
+        suffix,
+    })
+}
+
+/// Remaps the frames and monitor classes of a [`ThreadDump`].
+///
+/// Consecutive frames are remapped together with `remap_frames`, and the
+/// classes of monitors with `remap_class`.
+pub(crate) fn remap_thread_dump<'a>(
+    dump: &ThreadDump<'a>,
+    remap_frames: impl Fn(&StackTrace<'a>) -> StackTrace<'a>,
+    remap_class: impl Fn(&str) -> Option<&'a str>,
+) -> ThreadDump<'a> {
+    let threads = dump
+        .threads
+        .iter()
+        .map(|thread| {
+            let mut lines = Vec::with_capacity(thread.lines.len());
+            let mut frames = vec![];
+            for line in &thread.lines {
+                if let ThreadLine::Frame(frame) = line {
+                    frames.push(frame.clone());
+                    continue;
+                }
+                flush_frames(&mut lines, &mut frames, &remap_frames);
+
+                let line = match line {
+                    ThreadLine::Monitor(monitor) => {
+                        let mut monitor = monitor.clone();
+                        if let Some(class) = monitor
+                            .class
+                            .as_deref()
+                            .and_then(|class| remap_monitor_class(class, &remap_class))
+                        {
+                            monitor.class = Some(class);
+                        }
+                        ThreadLine::Monitor(monitor)
+                    }
+                    line => line.clone(),
+                };
+                lines.push(line);
+            }
+            flush_frames(&mut lines, &mut frames, &remap_frames);
+
+            Thread {
+                header: thread.header,
+                name: thread.name,
+                lines,
+            }
+        })
+        .collect();
+
+    ThreadDump { threads }
+}
+
+/// Remaps the collected `frames` and appends them to `lines`.
+fn flush_frames<'a>(
+    lines: &mut Vec<ThreadLine<'a>>,
+    frames: &mut Vec<StackFrame<'a>>,
+    remap_frames: impl Fn(&StackTrace<'a>) -> StackTrace<'a>,
+) {
+    if frames.is_empty() {
+        return;
+    }
+    let trace = StackTrace::new(None, std::mem::take(frames));
+    let remapped = remap_frames(&trace);
+    lines.extend(remapped.frames.into_iter().map(ThreadLine::Frame));
+}
+
+/// Remaps the class of a monitor object, which is `java.lang.Class<a.b.c>`
+/// for `synchronized` static methods.
+fn remap_monitor_class<'a>(
+    class: &str,
+    remap_class: impl Fn(&str) -> Option<&'a str>,
+) -> Option<Cow<'a, str>> {
+    if let Some(remapped) = remap_class(class) {
+        return Some(Cow::Borrowed(remapped));
+    }
+
+    let inner = class.strip_prefix("java.lang.Class<")?.strip_suffix('>')?;
+    let remapped = remap_class(inner)?;
+    Some(Cow::Owned(format!("java.lang.Class<{remapped}>")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ART_DUMP: &str = r#"----- pid 1234 at 2024-10-16 12:00:00 -----
+Cmd line: com.example
+
+"main" prio=5 tid=1 Blocked
+  | group="main" sCount=1 dsCount=0 flags=1 obj=0x72f3a000 self=0xb400007
+  native: #00 pc 000000000004e5b4  /apex/com.android.runtime/lib64/bionic/libc.so (syscall+36)
+  at a.b.c(SourceFile:12)
+  - waiting to lock <0x0abc> (a a.d) held by thread 12
+  at a.b.d(SourceFile:3)
+  - locked <0x0def> (a java.lang.Class<a.b>)
+
+"Thread-2" prio=5 tid=12 Sleeping
+  at java.lang.Thread.sleep(Native method)
+  - sleeping on <0x0123> (a java.lang.Object)
+
+----- end 1234 -----
+"#;
+
+    #[test]
+    fn parse_art_dump() {
+        let dump = parse_thread_dump(ART_DUMP).unwrap();
+        let [main, other] = dump.threads() else {
+            panic!("expected two threads");
+        };
+
+        assert_eq!(main.name(), "main");
+        assert_eq!(main.header(), r#""main" prio=5 tid=1 Blocked"#);
+        assert_eq!(main.lines().len(), 6);
+        assert_eq!(main.frames().count(), 2);
+        assert!(
+            matches!(main.lines()[0], ThreadLine::Other(line) if line.starts_with("  | group"))
+        );
+        assert_eq!(
+            main.lines()[3],
+            ThreadLine::Monitor(Monitor {
+                action: "waiting to lock",
+                address: "0x0abc",
+                class: Some(Cow::Borrowed("a.d")),
+                suffix: " held by thread 12",
+            })
+        );
+
+        assert_eq!(other.name(), "Thread-2");
+        let frame = other.frames().next().unwrap();
+        assert_eq!(frame.file(), Some("Native method"));
+        assert_eq!(frame.line(), None);
+        // The thread ends before the unindented trailer.
+        assert!(matches!(other.lines().last(), Some(ThreadLine::Monitor(_))));
+    }
+
+    #[test]
+    fn parse_hotspot_dump() {
+        let dump = r#"Full thread dump OpenJDK 64-Bit Server VM (17.0.2+8-86 mixed mode, sharing):
+
+"main" #1 prio=5 os_prio=0 cpu=81.25ms elapsed=12.50s tid=0x00007f2 nid=0x1c03 waiting for monitor entry  [0x00007f2c]
+   java.lang.Thread.State: BLOCKED (on object monitor)
+	at a.b.c(SourceFile:12)
+	- waiting to lock <0x000000071a4f5e88> (a a.d)
+	- parking to wait for  <0x000000071a4f5e90> (a java.util.concurrent.locks.ReentrantLock$NonfairSync)
+
+   Locked ownable synchronizers:
+	- <0x000000071a4f5e98> (a a.d)
+	- None
+"#;
+        let dump = parse_thread_dump(dump).unwrap();
+        let [main] = dump.threads() else {
+            panic!("expected one thread");
+        };
+
+        let monitors: Vec<_> = main
+            .lines()
+            .iter()
+            .filter_map(|line| match line {
+                ThreadLine::Monitor(monitor) => Some((monitor.action(), monitor.class())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            monitors,
+            [
+                ("waiting to lock", Some("a.d")),
+                (
+                    "parking to wait for",
+                    Some("java.util.concurrent.locks.ReentrantLock$NonfairSync")
+                ),
+                ("", Some("a.d")),
+            ]
+        );
+        assert_eq!(main.lines().last(), Some(&ThreadLine::Other("\t- None")));
+    }
+
+    #[test]
+    fn print_thread_dump() {
+        let dump = parse_thread_dump(ART_DUMP).unwrap();
+        let expect = r#""main" prio=5 tid=1 Blocked
+  | group="main" sCount=1 dsCount=0 flags=1 obj=0x72f3a000 self=0xb400007
+  native: #00 pc 000000000004e5b4  /apex/com.android.runtime/lib64/bionic/libc.so (syscall+36)
+    at a.b.c(SourceFile:12)
+    - waiting to lock <0x0abc> (a a.d) held by thread 12
+    at a.b.d(SourceFile:3)
+    - locked <0x0def> (a java.lang.Class<a.b>)
+
+"Thread-2" prio=5 tid=12 Sleeping
+    at java.lang.Thread.sleep(Native method)
+    - sleeping on <0x0123> (a java.lang.Object)
+"#;
+        assert_eq!(dump.to_string(), expect);
+        assert_eq!(parse_thread_dump(expect), Some(dump));
+    }
+}
@@ -49,9 +49,79 @@ pub fn class_name_to_descriptor(class: &str) -> String {
     descriptor
 }
 
+/// Maps an original line back to a minified line of a single member mapping.
+///
+/// This is the inverse of remapping a frame through that member: remapping the
+/// returned line yields `line` again. Returns `None` if the member does not
+/// cover `line`.
+///
+/// Members without any line information pass `line` through unchanged, and
+/// members without a minified range map to line `0`.
+pub(crate) fn minified_line(
+    startline: Option<usize>,
+    endline: Option<usize>,
+    original_startline: Option<usize>,
+    original_endline: Option<usize>,
+    line: usize,
+) -> Option<usize> {
+    let Some(original_startline) = original_startline else {
+        return match endline.unwrap_or(0) {
+            0 => Some(line),
+            _ => None,
+        };
+    };
+    let original_endline = original_endline.unwrap_or(original_startline);
+    if line < original_startline || line > original_endline {
+        return None;
+    }
+
+    let (Some(startline), Some(endline)) = (startline, endline) else {
+        return Some(0);
+    };
+    if endline == 0 || original_endline == original_startline {
+        return Some(startline);
+    }
+
+    let minified = startline + (line - original_startline);
+    (minified <= endline).then_some(minified)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::synthesize_source_file;
+    use super::{minified_line, synthesize_source_file};
+
+    #[test]
+    fn minified_line_offsets_into_matching_ranges() {
+        assert_eq!(
+            minified_line(Some(1), Some(4), Some(184), Some(187), 186),
+            Some(3)
+        );
+        assert_eq!(
+            minified_line(Some(1), Some(4), Some(184), Some(187), 188),
+            None
+        );
+        assert_eq!(
+            minified_line(Some(1), Some(4), Some(184), Some(187), 183),
+            None
+        );
+    }
+
+    #[test]
+    fn minified_line_single_original_line_uses_range_start() {
+        assert_eq!(minified_line(Some(1), Some(3), Some(57), None, 58), None);
+        assert_eq!(minified_line(Some(5), Some(7), Some(57), None, 57), Some(5));
+        assert_eq!(
+            minified_line(Some(5), Some(7), Some(57), Some(57), 57),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn minified_line_without_line_info() {
+        assert_eq!(minified_line(None, None, None, None, 42), Some(42));
+        assert_eq!(minified_line(None, None, Some(42), None, 42), Some(0));
+        assert_eq!(minified_line(None, None, Some(42), None, 43), None);
+    }
 
     #[test]
     fn kotlin_top_level_class_uses_kt_extension() {
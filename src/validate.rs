@@ -0,0 +1,369 @@
+//! Validation of ProGuard mapping files.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::mapping::R8Header;
+use crate::{MappingVersion, ProguardMapping, ProguardRecord};
+
+/// The severity of a [`Diagnostic`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The mapping can still be used, but parts of it may be ignored or
+    /// interpreted differently than intended.
+    Warning,
+    /// The mapping is broken and remapping with it will give wrong or
+    /// incomplete results.
+    Error,
+}
+
+/// The specific problem reported by a [`Diagnostic`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DiagnosticKind {
+    /// The line is not a valid mapping record.
+    InvalidLine,
+    /// The line is an R8 header whose JSON could not be decoded.
+    InvalidR8Header,
+    /// A field or method appears before the first class.
+    MemberBeforeClass,
+    /// The obfuscated class name was already used by an earlier class.
+    DuplicateClass {
+        /// The line of the earlier class.
+        first_line: usize,
+    },
+    /// The minified range of a method partially overlaps another range of the
+    /// same obfuscated method.
+    OverlappingRange {
+        /// The line of the method with the other range.
+        other_line: usize,
+    },
+    /// A minified or original line range ends before it starts.
+    InvertedRange,
+    /// The mapping declares a version of the mapping format that is newer than
+    /// [`MappingVersion::LATEST`], so some of its headers may be ignored.
+    UnsupportedMappingVersion,
+    /// The mapping declares R8's `experimental` version of the mapping format,
+    /// whose headers may change without a new version.
+    ExperimentalMappingVersion,
+    /// The declared version of the mapping format is not a valid version.
+    InvalidMappingVersion,
+}
+
+impl DiagnosticKind {
+    /// Returns the severity of this kind of problem.
+    pub fn severity(&self) -> Severity {
+        match self {
+            DiagnosticKind::InvalidLine
+            | DiagnosticKind::MemberBeforeClass
+            | DiagnosticKind::DuplicateClass { .. } => Severity::Error,
+            DiagnosticKind::InvalidR8Header
+            | DiagnosticKind::OverlappingRange { .. }
+            | DiagnosticKind::InvertedRange
+            | DiagnosticKind::UnsupportedMappingVersion
+            | DiagnosticKind::ExperimentalMappingVersion
+            | DiagnosticKind::InvalidMappingVersion => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticKind::InvalidLine => f.write_str("line is not a valid proguard record"),
+            DiagnosticKind::InvalidR8Header => f.write_str("invalid r8 header"),
+            DiagnosticKind::MemberBeforeClass => f.write_str("member before the first class"),
+            DiagnosticKind::DuplicateClass { first_line } => {
+                write!(f, "obfuscated class name already used on line {first_line}")
+            }
+            DiagnosticKind::OverlappingRange { other_line } => {
+                write!(f, "minified range overlaps the range on line {other_line}")
+            }
+            DiagnosticKind::InvertedRange => f.write_str("line range ends before it starts"),
+            DiagnosticKind::UnsupportedMappingVersion => write!(
+                f,
+                "mapping version is newer than the supported version {}",
+                MappingVersion::LATEST
+            ),
+            DiagnosticKind::ExperimentalMappingVersion => {
+                f.write_str("mapping uses the experimental version of the mapping format")
+            }
+            DiagnosticKind::InvalidMappingVersion => f.write_str("invalid mapping version"),
+        }
+    }
+}
+
+/// A problem found by [`ProguardMapping::validate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    line: usize,
+    kind: DiagnosticKind,
+}
+
+impl Diagnostic {
+    /// The 1-based line number of the offending line.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The specific problem.
+    pub fn kind(&self) -> DiagnosticKind {
+        self.kind
+    }
+
+    /// The severity of the problem.
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+/// A distinct minified range of a method, as `(start, end, line)` where
+/// `line` is the line the range first appears on.
+type Range = (usize, usize, usize);
+
+fn is_inverted(start: Option<usize>, end: Option<usize>) -> bool {
+    matches!((start, end), (Some(start), Some(end)) if start > end)
+}
+
+impl ProguardMapping<'_> {
+    /// Checks the whole mapping file for problems.
+    ///
+    /// Unlike [`is_valid`](Self::is_valid), this looks at every line and
+    /// reports each problem together with the line it was found on. The
+    /// diagnostics are ordered by line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use proguard::{DiagnosticKind, ProguardMapping, Severity};
+    ///
+    /// let mapping = ProguardMapping::new(
+    ///     b"com.example.Foo -> a:
+    ///     1:3:void foo():10:12 -> a
+    ///     not a valid line
+    /// com.example.Bar -> a:",
+    /// );
+    ///
+    /// let diagnostics = mapping.validate();
+    /// assert_eq!(diagnostics.len(), 2);
+    /// assert_eq!(diagnostics[0].line(), 3);
+    /// assert_eq!(diagnostics[0].kind(), DiagnosticKind::InvalidLine);
+    /// assert_eq!(diagnostics[1].line(), 4);
+    /// assert_eq!(diagnostics[1].severity(), Severity::Error);
+    /// assert_eq!(
+    ///     diagnostics[1].to_string(),
+    ///     "line 4: obfuscated class name already used on line 1"
+    /// );
+    /// ```
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut report = |line, kind| diagnostics.push(Diagnostic { line, kind });
+
+        let mut classes: HashMap<&str, usize> = HashMap::new();
+        let mut current_class = None;
+        let mut ranges: HashMap<(&str, &str), Vec<Range>> = HashMap::new();
+
+        for (position, record) in self.iter_with_positions() {
+            let line = position.line();
+            match record {
+                Err(err) => {
+                    let kind = if err.line().trim_ascii_start().starts_with(b"#") {
+                        DiagnosticKind::InvalidR8Header
+                    } else {
+                        DiagnosticKind::InvalidLine
+                    };
+                    report(line, kind);
+                }
+                Ok(ProguardRecord::Class { obfuscated, .. }) => {
+                    match classes.get(obfuscated) {
+                        Some(&first_line) => {
+                            report(line, DiagnosticKind::DuplicateClass { first_line })
+                        }
+                        None => {
+                            classes.insert(obfuscated, line);
+                        }
+                    }
+                    current_class = Some(obfuscated);
+                }
+                Ok(ProguardRecord::R8Header(R8Header::MappingVersion { version })) => {
+                    match MappingVersion::parse(version) {
+                        Some(version) if version.is_supported() => {}
+                        Some(_) => report(line, DiagnosticKind::UnsupportedMappingVersion),
+                        None if version == "experimental" => {
+                            report(line, DiagnosticKind::ExperimentalMappingVersion)
+                        }
+                        None => report(line, DiagnosticKind::InvalidMappingVersion),
+                    }
+                }
+                Ok(ProguardRecord::Field { .. }) if current_class.is_none() => {
+                    report(line, DiagnosticKind::MemberBeforeClass);
+                }
+                Ok(ProguardRecord::Method {
+                    obfuscated,
+                    line_mapping,
+                    ..
+                }) => {
+                    let Some(class) = current_class else {
+                        report(line, DiagnosticKind::MemberBeforeClass);
+                        continue;
+                    };
+                    let Some(line_mapping) = line_mapping else {
+                        continue;
+                    };
+
+                    if is_inverted(line_mapping.startline, line_mapping.endline)
+                        || is_inverted(
+                            line_mapping.original_startline,
+                            line_mapping.original_endline,
+                        )
+                    {
+                        report(line, DiagnosticKind::InvertedRange);
+                    }
+
+                    if let (Some(start), Some(end)) = (line_mapping.startline, line_mapping.endline)
+                    {
+                        let (start, end) = (start.min(end), start.max(end));
+                        // Ranges without minified lines don't take part in lookups.
+                        if end > 0 {
+                            let method_ranges = ranges.entry((class, obfuscated)).or_default();
+                            if !method_ranges.iter().any(|r| (r.0, r.1) == (start, end)) {
+                                method_ranges.push((start, end, line));
+                            }
+                        }
+                    }
+                }
+                Ok(_) => {}
+            }
+        }
+
+        for method_ranges in ranges.values_mut() {
+            method_ranges.sort_unstable();
+            // The range reaching furthest so far, as `(end, line)`.
+            let mut furthest: Option<(usize, usize)> = None;
+            for &(start, end, line) in method_ranges.iter() {
+                match furthest {
+                    Some((furthest_end, other_line)) if start <= furthest_end => {
+                        report(line, DiagnosticKind::OverlappingRange { other_line });
+                        if end > furthest_end {
+                            furthest = Some((end, line));
+                        }
+                    }
+                    _ => furthest = Some((end, line)),
+                }
+            }
+        }
+
+        diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(mapping: &str) -> Vec<(usize, DiagnosticKind)> {
+        ProguardMapping::new(mapping.as_bytes())
+            .validate()
+            .iter()
+            .map(|diagnostic| (diagnostic.line(), diagnostic.kind()))
+            .collect()
+    }
+
+    #[test]
+    fn valid_mapping() {
+        let mapping = include_str!("../tests/res/mapping-r8.txt");
+        assert_eq!(validate(mapping), []);
+    }
+
+    #[test]
+    fn invalid_lines() {
+        let mapping = "\
+# compiler: R8
+# {\"id\":\"sourceFile\",\"fileName\":
+a -> b:
+
+    some garbage
+    void foo() -> a
+";
+        assert_eq!(
+            validate(mapping),
+            [
+                (2, DiagnosticKind::InvalidR8Header),
+                (5, DiagnosticKind::InvalidLine)
+            ]
+        );
+    }
+
+    #[test]
+    fn mapping_version() {
+        let mapping = "\
+# {\"id\":\"com.android.tools.r8.mapping\",\"version\":\"2.2\"}
+# {\"id\":\"com.android.tools.r8.mapping\",\"version\":\"3.0\"}
+# {\"id\":\"com.android.tools.r8.mapping\",\"version\":\"experimental\"}
+# {\"id\":\"com.android.tools.r8.mapping\",\"version\":\"2\"}
+";
+        assert_eq!(
+            validate(mapping),
+            [
+                (2, DiagnosticKind::UnsupportedMappingVersion),
+                (3, DiagnosticKind::ExperimentalMappingVersion),
+                (4, DiagnosticKind::InvalidMappingVersion)
+            ]
+        );
+    }
+
+    #[test]
+    fn member_before_class() {
+        let mapping = "    int field -> a\n    void foo() -> a\na -> b:\n    void bar() -> a\n";
+        assert_eq!(
+            validate(mapping),
+            [
+                (1, DiagnosticKind::MemberBeforeClass),
+                (2, DiagnosticKind::MemberBeforeClass)
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_class() {
+        let mapping = "\
+com.example.Foo -> a:
+com.example.Bar -> b:
+com.example.Baz -> a:
+";
+        assert_eq!(
+            validate(mapping),
+            [(3, DiagnosticKind::DuplicateClass { first_line: 1 })]
+        );
+    }
+
+    #[test]
+    fn ranges() {
+        let mapping = "\
+com.example.Foo -> a:
+    1:1:void inlined():10:10 -> a
+    1:1:void foo():20 -> a
+    2:5:void foo():21:24 -> a
+    4:6:void foo():30:32 -> a
+    1:1:void bar():40 -> a
+    9:7:void baz():40:42 -> b
+    1:1:void baz():52:50 -> c
+    3:4:void other():1:2 -> d
+";
+        assert_eq!(
+            validate(mapping),
+            [
+                (5, DiagnosticKind::OverlappingRange { other_line: 4 }),
+                (7, DiagnosticKind::InvertedRange),
+                (8, DiagnosticKind::InvertedRange),
+            ]
+        );
+    }
+}
@@ -0,0 +1,68 @@
+//! Functionality for writing ProGuard mapping files.
+
+use std::io::{self, Write};
+
+use crate::ProguardRecord;
+
+/// A writer for ProGuard mapping files.
+///
+/// Records are written one per line in the format R8 produces, so parsing the
+/// written mapping yields the same [`ProguardRecord`]s again. R8 headers
+/// following a field or method are indented like R8 indents them.
+///
+/// # Examples
+///
+/// ```
+/// use proguard::{MappingWriter, ProguardMapping};
+///
+/// let source = br#"android.arch.core.executor.ArchTaskExecutor -> a.a.a.a.c:
+///     android.arch.core.executor.TaskExecutor mDelegate -> d
+///     1:1:void executeOnDiskIO(java.lang.Runnable):96:96 -> a
+///       ## {"id":"com.android.tools.r8.synthesized"}
+/// "#;
+/// let mapping = ProguardMapping::new(source);
+///
+/// let mut writer = MappingWriter::new(Vec::new());
+/// for record in mapping.iter() {
+///     writer.write_record(&record.unwrap()).unwrap();
+/// }
+/// assert_eq!(writer.into_inner(), source);
+/// ```
+#[derive(Debug)]
+pub struct MappingWriter<W> {
+    writer: W,
+    after_member: bool,
+}
+
+impl<W: Write> MappingWriter<W> {
+    /// Creates a new `MappingWriter` writing into `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            after_member: false,
+        }
+    }
+
+    /// Writes a single record, followed by a newline.
+    pub fn write_record(&mut self, record: &ProguardRecord<'_>) -> io::Result<()> {
+        match record {
+            ProguardRecord::R8Header(_) if self.after_member => {
+                writeln!(self.writer, "      {record}")
+            }
+            ProguardRecord::R8Header(_) => writeln!(self.writer, "{record}"),
+            ProguardRecord::Field { .. } | ProguardRecord::Method { .. } => {
+                self.after_member = true;
+                writeln!(self.writer, "{record}")
+            }
+            ProguardRecord::Header { .. } | ProguardRecord::Class { .. } => {
+                self.after_member = false;
+                writeln!(self.writer, "{record}")
+            }
+        }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
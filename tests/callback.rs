@@ -1,4 +1,6 @@
-use proguard::{ProguardCache, ProguardMapper, ProguardMapping, StackFrame};
+use proguard::{
+    ProguardCache, ProguardMapper, ProguardMapping, RemapOptions, StackFrame, StackTrace,
+};
 
 static MAPPING_CALLBACK: &[u8] = include_bytes!("res/mapping-callback.txt");
 static MAPPING_CALLBACK_EXTRA_CLASS: &[u8] = include_bytes!("res/mapping-callback-extra-class.txt");
@@ -281,3 +283,97 @@ fn test_method_matches_callback_inner_class_cache() {
     );
     assert_eq!(mapped.next(), None);
 }
+
+#[test]
+fn test_prefer_non_synthesized() {
+    let input = "\
+java.lang.RuntimeException: boom
+    at io.sentry.samples.instrumentation.ui.g.onMenuItemClick(SourceFile:28)
+";
+    let expected = "\
+java.lang.RuntimeException: boom
+    at io.sentry.samples.instrumentation.ui.EditActivity.onCreate$lambda$1(EditActivity.java:37)
+";
+    let options = RemapOptions::new().prefer_non_synthesized(true);
+    let trace = StackTrace::try_parse(input.as_bytes()).unwrap();
+    let frame = StackFrame::with_file(
+        "io.sentry.samples.instrumentation.ui.EditActivity",
+        "onCreate$lambda$1",
+        37,
+        "EditActivity.java",
+    );
+
+    let mapper = ProguardMapper::new(ProguardMapping::new(MAPPING_CALLBACK));
+    let actual = mapper
+        .remap_stacktrace_with_options(input, &options)
+        .unwrap();
+    assert_eq!(actual.trim(), expected.trim());
+    let actual = mapper.remap_stacktrace_typed_with_options(&trace, &options);
+    assert_eq!(actual.frames(), std::slice::from_ref(&frame));
+    // Without the option, the synthesized lambda frame is kept.
+    assert_eq!(mapper.remap_stacktrace_typed(&trace).frames().len(), 2);
+
+    let mapping = ProguardMapping::new(MAPPING_CALLBACK);
+    let mut cache = Vec::new();
+    ProguardCache::write(&mapping, &mut cache).unwrap();
+    let cache = ProguardCache::parse(&cache).unwrap();
+    cache.test();
+
+    let actual = cache
+        .remap_stacktrace_with_options(input, &options)
+        .unwrap();
+    assert_eq!(actual.trim(), expected.trim());
+    let actual = cache.remap_stacktrace_typed_with_options(&trace, &options);
+    assert_eq!(actual.frames(), [frame]);
+    assert_eq!(cache.remap_stacktrace_typed(&trace).frames().len(), 2);
+}
+
+#[test]
+fn test_remap_frame_prefer_non_synthesized() {
+    let options = RemapOptions::new().prefer_non_synthesized(true);
+    let input = StackFrame::new(
+        "io.sentry.samples.instrumentation.ui.g",
+        "onMenuItemClick",
+        28,
+    );
+    let frame = StackFrame::with_file(
+        "io.sentry.samples.instrumentation.ui.EditActivity",
+        "onCreate$lambda$1",
+        37,
+        "EditActivity.java",
+    );
+
+    let mapper = ProguardMapper::new(ProguardMapping::new(MAPPING_CALLBACK));
+    assert_eq!(
+        mapper.remap_frame_with_options(&input, &options),
+        std::slice::from_ref(&frame)
+    );
+    assert_eq!(
+        mapper.remap_frame_alternatives_with_options(&input, &options),
+        [vec![frame.clone()]]
+    );
+    // Without the option, the synthesized lambda frame is kept.
+    assert_eq!(mapper.remap_frame(&input).count(), 2);
+    assert_eq!(
+        mapper
+            .remap_frame_with_options(&input, &RemapOptions::default())
+            .len(),
+        2
+    );
+
+    let mapping = ProguardMapping::new(MAPPING_CALLBACK);
+    let mut cache = Vec::new();
+    ProguardCache::write(&mapping, &mut cache).unwrap();
+    let cache = ProguardCache::parse(&cache).unwrap();
+    cache.test();
+
+    assert_eq!(
+        cache.remap_frame_with_options(&input, &options),
+        std::slice::from_ref(&frame)
+    );
+    assert_eq!(
+        cache.remap_frame_alternatives_with_options(&input, &options),
+        [vec![frame]]
+    );
+    assert_eq!(cache.remap_frame(&input).count(), 2);
+}
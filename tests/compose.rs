@@ -0,0 +1,295 @@
+#![allow(clippy::unwrap_used)]
+
+use proguard::{ComposeError, ProguardCache, ProguardMapper, ProguardMapping, ProguardRecord};
+
+const INNER: &str = r#"# compiler: R8
+com.example.Foo -> a.a:
+# {"id":"sourceFile","fileName":"Foo.kt"}
+    com.example.Baz baz -> a
+    int count -> b
+    1:3:void bar(com.example.Baz):10:12 -> a
+    4:4:void inlined():20:20 -> b
+    4:4:void baz():30 -> b
+    5:5:java.lang.Object thrower():40:40 -> b
+    5:5:void baz():31 -> b
+      # {"id":"com.android.tools.r8.rewriteFrame","conditions":["throws(Ljava/lang/NullPointerException;)"],"actions":["removeInnerFrames(1)"]}
+    void unused() -> c
+com.example.Baz -> a.b:
+    void <init>() -> <init>
+com.example.Kept -> a.c:
+"#;
+
+const OUTER: &str = r#"# compiler: R8
+# compiler_version: 8.0
+a.a -> x:
+    a.b a -> c
+    5:7:void a(a.b):1:3 -> c
+    10:11:void b():4:5 -> d
+    10:11:void a(a.b):2 -> d
+      # {"id":"com.android.tools.r8.rewriteFrame","conditions":["throws(Ljava/lang/IllegalStateException;)"],"actions":["removeInnerFrames(1)"]}
+a.b -> y:
+"#;
+
+fn compose(inner: &str, outer: &str) -> String {
+    let mut composed = Vec::new();
+    ProguardMapping::new(inner.as_bytes())
+        .compose(&ProguardMapping::new(outer.as_bytes()), &mut composed)
+        .unwrap();
+    String::from_utf8(composed).unwrap()
+}
+
+fn assert_remap_stacktrace(mapping: &str, input: &str, expected: &str) {
+    let mapper = ProguardMapper::from(mapping);
+    let actual = mapper.remap_stacktrace(input).unwrap();
+    assert_eq!(actual.trim_end(), expected.trim_end());
+
+    let mapping = ProguardMapping::new(mapping.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+
+    let actual = cache.remap_stacktrace(input).unwrap();
+    assert_eq!(actual.trim_end(), expected.trim_end());
+}
+
+#[test]
+fn test_compose() {
+    let composed = compose(INNER, OUTER);
+
+    assert_eq!(
+        composed,
+        r#"# compiler: R8
+# compiler_version: 8.0
+com.example.Foo -> x:
+# {"id":"sourceFile","fileName":"Foo.kt"}
+    com.example.Baz baz -> c
+    int count -> b
+    5:7:void bar(com.example.Baz):10:12 -> c
+    10:10:void inlined():20 -> d
+      # {"id":"com.android.tools.r8.rewriteFrame","conditions":["throws(Ljava/lang/IllegalStateException;)"],"actions":["removeInnerFrames(2)"]}
+    10:10:void baz():30 -> d
+    10:10:void bar(com.example.Baz):11 -> d
+    11:11:java.lang.Object thrower():40 -> d
+      # {"id":"com.android.tools.r8.rewriteFrame","conditions":["throws(Ljava/lang/IllegalStateException;)"],"actions":["removeInnerFrames(2)"]}
+    11:11:void baz():31 -> d
+      # {"id":"com.android.tools.r8.rewriteFrame","conditions":["throws(Ljava/lang/NullPointerException;)"],"actions":["removeInnerFrames(1)"]}
+    11:11:void bar(com.example.Baz):11 -> d
+com.example.Baz -> y:
+    void <init>() -> <init>
+com.example.Kept -> a.c:
+"#
+    );
+}
+
+#[test]
+fn test_compose_remap() {
+    let composed = compose(INNER, OUTER);
+
+    assert_remap_stacktrace(
+        &composed,
+        "\
+java.lang.RuntimeException: boom
+    at x.c(SourceFile:6)
+    at x.d(SourceFile:10)
+    at x.d(SourceFile:11)",
+        "\
+java.lang.RuntimeException: boom
+    at com.example.Foo.bar(Foo.kt:11)
+    at com.example.Foo.inlined(Foo.kt:20)
+    at com.example.Foo.baz(Foo.kt:30)
+    at com.example.Foo.bar(Foo.kt:11)
+    at com.example.Foo.thrower(Foo.kt:40)
+    at com.example.Foo.baz(Foo.kt:31)
+    at com.example.Foo.bar(Foo.kt:11)",
+    );
+
+    // Both the inner and the outer rewrite rules still apply.
+    assert_remap_stacktrace(
+        &composed,
+        "\
+java.lang.NullPointerException: boom
+    at x.d(SourceFile:11)",
+        "\
+java.lang.NullPointerException: boom
+    at com.example.Foo.baz(Foo.kt:31)
+    at com.example.Foo.bar(Foo.kt:11)",
+    );
+    assert_remap_stacktrace(
+        &composed,
+        "\
+java.lang.IllegalStateException: boom
+    at x.d(SourceFile:10)",
+        "\
+java.lang.IllegalStateException: boom
+    at com.example.Foo.bar(Foo.kt:11)",
+    );
+}
+
+#[test]
+fn test_compose_outline() {
+    let inner = r#"com.example.Outline -> a.o:
+    1:2:int outline():0:0 -> a
+      # {"id":"com.android.tools.r8.outline"}
+com.example.Main -> a.m:
+    4:5:int caller(int):42:43 -> b
+    27:27:int caller(int):0:0 -> b
+      # {"id":"com.android.tools.r8.outlineCallsite","positions":{"1":4,"2":5},"outline":"La/o;a()I"}
+"#;
+    let outer = r#"a.o -> b.o:
+    11:12:int a():1:2 -> z
+a.m -> b.m:
+    104:105:int b(int):4:5 -> y
+    127:127:int b(int):27 -> y
+"#;
+    let composed = compose(inner, outer);
+
+    assert_eq!(
+        composed,
+        r#"com.example.Outline -> b.o:
+    11:12:int outline():0 -> z
+      # {"id":"com.android.tools.r8.outline"}
+com.example.Main -> b.m:
+    104:105:int caller(int):42:43 -> y
+    127:127:int caller(int):0 -> y
+      # {"id":"com.android.tools.r8.outlineCallsite","positions":{"11":104,"12":105},"outline":"Lb/o;z()I"}
+"#
+    );
+
+    assert_remap_stacktrace(
+        &composed,
+        "\
+java.lang.IllegalStateException: boom
+    at b.o.z(SourceFile:12)
+    at b.m.y(SourceFile:127)",
+        "\
+java.lang.IllegalStateException: boom
+    at com.example.Main.caller(Main.java:43)",
+    );
+}
+
+#[test]
+fn test_compose_mapping_version() {
+    // Outline callsites only name the outline from version 2.2 on.
+    let inner = r#"# {"id":"com.android.tools.r8.mapping","version":"2.1"}
+com.example.Main -> a.m:
+    4:5:int caller(int):42:43 -> b
+    27:27:int caller(int):0:0 -> b
+      # {"id":"com.android.tools.r8.outlineCallsite","positions":{"1":4,"2":5},"outline":"La/o;a()I"}
+"#;
+    let outer = r#"# {"id":"com.android.tools.r8.mapping","version":"2.0"}
+a.m -> b.m:
+    104:105:int b(int):4:5 -> y
+    127:127:int b(int):27 -> y
+"#;
+
+    assert_eq!(
+        compose(inner, outer),
+        r#"# {"id":"com.android.tools.r8.mapping","version":"2.1"}
+com.example.Main -> b.m:
+    104:105:int caller(int):42:43 -> y
+    127:127:int caller(int):0 -> y
+      # {"id":"com.android.tools.r8.outlineCallsite","positions":{"1":104,"2":105}}
+"#
+    );
+}
+
+#[test]
+fn test_compose_residual_signature() {
+    // Only the residual signature of the outer mapping describes the final method.
+    let inner = r#"com.example.Main -> a.m:
+    1:1:void foo(int):10:10 -> b
+      # {"id":"com.android.tools.r8.residualsignature","signature":"(I)V"}
+"#;
+    let outer = r#"a.m -> b.m:
+    5:5:void b(int):1:1 -> c
+      # {"id":"com.android.tools.r8.residualsignature","signature":"()V"}
+"#;
+
+    assert_eq!(
+        compose(inner, outer),
+        r#"com.example.Main -> b.m:
+    5:5:void foo(int):10 -> c
+      # {"id":"com.android.tools.r8.residualsignature","signature":"()V"}
+"#
+    );
+}
+
+fn records(mapping: &str) -> Vec<ProguardRecord<'_>> {
+    ProguardMapping::new(mapping.as_bytes())
+        .iter()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+#[test]
+fn test_compose_unmentioned() {
+    // An outer mapping that doesn't mention anything keeps the inner mapping as is.
+    let inner = include_str!("res/mapping-inlines.txt");
+    let composed = compose(inner, "");
+
+    assert_eq!(records(&composed), records(inner));
+}
+
+#[test]
+fn test_compose_headers() {
+    let inner = r#"# compiler: R8
+# min_api: 21
+# pg_map_id: 1111111
+# pg_map_hash: SHA-256 0000
+# {"id":"com.example.file","value":1}
+com.example.Foo -> a:
+# {"id":"com.example.class","value":2}
+    int count -> a
+      # {"id":"com.example.field","value":3}
+    1:1:void bar():10:10 -> a
+      # {"id":"com.example.method","value":4}
+"#;
+    let outer = r#"# compiler: R8
+# compiler_version: 8.0
+# pg_map_id: 2222222
+# pg_map_hash: SHA-256 0000
+a -> b:
+# {"id":"sourceFile","fileName":"Intermediate.kt"}
+# {"id":"com.example.class","value":5}
+    int a -> c
+    5:5:void a():1:1 -> d
+      # {"id":"com.example.method","value":6}
+"#;
+
+    assert_eq!(
+        compose(inner, outer),
+        r#"# compiler: R8
+# compiler_version: 8.0
+# pg_map_id: 2222222
+# min_api: 21
+# {"id":"com.example.file","value":1}
+com.example.Foo -> b:
+# {"id":"com.example.class","value":2}
+# {"id":"com.example.class","value":5}
+    int count -> c
+      # {"id":"com.example.field","value":3}
+    5:5:void bar():10 -> d
+      # {"id":"com.example.method","value":4}
+      # {"id":"com.example.method","value":6}
+"#
+    );
+}
+
+#[test]
+fn test_compose_invalid() {
+    let valid = ProguardMapping::new(b"a -> b:\n");
+    let invalid = ProguardMapping::new(b"a -> b:\n    garbage\n");
+    let mut composed = Vec::new();
+
+    let err = invalid.compose(&valid, &mut composed).unwrap_err();
+    assert!(matches!(err, ComposeError::InvalidInner(_)));
+    assert_eq!(
+        err.to_string(),
+        "invalid line 2 in inner mapping: line is not a valid proguard record"
+    );
+
+    let err = valid.compose(&invalid, &mut composed).unwrap_err();
+    assert!(matches!(err, ComposeError::InvalidOuter(_)));
+    assert!(composed.is_empty());
+}
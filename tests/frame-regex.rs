@@ -0,0 +1,116 @@
+//! Tests for remapping stack traces in custom formats.
+#![cfg(feature = "regex")]
+#![allow(clippy::unwrap_used)]
+
+use proguard::{
+    FrameRegex, FrameRegexError, ProguardCache, ProguardMapper, ProguardMapping, RemapOptions,
+};
+
+const MAPPING: &str = "\
+some.Class -> a:
+    some.Other field -> b
+    7:8:void method2(int):87:88 -> a
+    7:8:void method1(java.lang.String):95 -> a
+some.Other -> b:
+";
+
+fn assert_remapped(regex: &str, input: &str, expected: &str) {
+    let options = RemapOptions::new().frame_regex(FrameRegex::new(regex).unwrap());
+
+    let mapper = ProguardMapper::from(MAPPING);
+    let actual = mapper
+        .remap_stacktrace_with_options(input, &options)
+        .unwrap();
+    assert_eq!(actual, expected);
+
+    let mapping = ProguardMapping::new(MAPPING.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+
+    let actual = cache
+        .remap_stacktrace_with_options(input, &options)
+        .unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_custom_frame_format() {
+    // Only the captured parts are rewritten, one line per remapped frame.
+    assert_remapped(
+        r"%c\.%m:%l \(%s\)$",
+        "\
+12:00:01 D/Timber: a.a:8 (SourceFile)
+12:00:02 D/Timber: done
+",
+        "\
+12:00:01 D/Timber: some.Class.method2:88 (Class.java)
+12:00:01 D/Timber: some.Class.method1:95 (Class.java)
+12:00:02 D/Timber: done
+",
+    );
+}
+
+#[test]
+fn test_fallback_to_standard_format() {
+    assert_remapped(
+        r"^frame %c#%m$",
+        "\
+b: boom
+    at a.a(SourceFile:7)
+frame a#a
+",
+        "\
+some.Other: boom
+    at some.Class.method2(Class.java:87)
+    at some.Class.method1(Class.java:95)
+frame some.Class#method2
+frame some.Class#method1
+",
+    );
+}
+
+#[test]
+fn test_class_field_and_type() {
+    assert_remapped(
+        r"^%t %c\.%f$",
+        "b[] a.b\nb a.unknown\n",
+        "some.Other[] some.Class.field\nsome.Other some.Class.unknown\n",
+    );
+    assert_remapped(
+        r"^class %c$",
+        "class b\nclass c\n",
+        "class some.Other\nclass c\n",
+    );
+}
+
+#[test]
+fn test_invalid_regex() {
+    assert!(matches!(
+        FrameRegex::new("%c.%m(%l"),
+        Err(FrameRegexError::Regex(_))
+    ));
+    assert!(matches!(
+        FrameRegex::new("%m %m"),
+        Err(FrameRegexError::DuplicatePlaceholder('m'))
+    ));
+}
+
+#[test]
+fn test_logcat_prefix() {
+    // The regex is matched against the message, and the prefix is kept on
+    // every remapped line.
+    assert_remapped(
+        r"^%c\.%m:%l$",
+        "\
+10-16 12:00:01.123  1234  1234 D Timber: a.a:8
+10-16 12:00:01.124  1234  1234 D Timber: done
+",
+        "\
+10-16 12:00:01.123  1234  1234 D Timber: some.Class.method2:88
+10-16 12:00:01.123  1234  1234 D Timber: some.Class.method1:95
+10-16 12:00:01.124  1234  1234 D Timber: done
+",
+    );
+}
@@ -0,0 +1,129 @@
+//! Tests for remapping stack traces in `adb logcat` output.
+#![allow(clippy::unwrap_used)]
+
+use proguard::{ProguardCache, ProguardMapper, ProguardMapping};
+
+const MAPPING: &str = r#"# {"id":"com.android.tools.r8.mapping","version":"2.0"}
+outline.Class -> a:
+    1:2:int outline():0 -> a
+# {"id":"com.android.tools.r8.outline"}
+some.Class -> b:
+    1:1:void inlinee():10:10 -> a
+    1:1:void caller():30 -> a
+    4:5:int outlineCaller(int):98:99 -> s
+    27:27:int outlineCaller(int):0:0 -> s
+# {"id":"com.android.tools.r8.outlineCallsite","positions":{"1":4,"2":5}}
+some.CrashException -> c:
+"#;
+
+fn assert_remapped(input: &str, expected: &str) {
+    let mapper = ProguardMapper::from(MAPPING);
+    assert_eq!(mapper.remap_stacktrace(input).unwrap(), expected);
+
+    let mapping = ProguardMapping::new(MAPPING.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+    assert_eq!(cache.remap_stacktrace(input).unwrap(), expected);
+}
+
+#[test]
+fn test_threadtime() {
+    assert_remapped(
+        "\
+10-16 12:00:01.123  1234  1234 E AndroidRuntime: FATAL EXCEPTION: main
+10-16 12:00:01.123  1234  1234 E AndroidRuntime: Process: com.example, PID: 1234
+10-16 12:00:01.123  1234  1234 E AndroidRuntime: c: boom
+10-16 12:00:01.123  1234  1234 E AndroidRuntime: \tat b.a(SourceFile:1)
+10-16 12:00:01.123  1234  1234 E AndroidRuntime: Caused by: c: inner
+10-16 12:00:01.123  1234  1234 E AndroidRuntime: \tat b.a(SourceFile:1)
+10-16 12:00:01.123  1234  1234 E AndroidRuntime: \t... 1 more
+",
+        "\
+10-16 12:00:01.123  1234  1234 E AndroidRuntime: FATAL EXCEPTION: main
+10-16 12:00:01.123  1234  1234 E AndroidRuntime: Process: com.example, PID: 1234
+10-16 12:00:01.123  1234  1234 E AndroidRuntime: some.CrashException: boom
+10-16 12:00:01.123  1234  1234 E AndroidRuntime:     at some.Class.inlinee(Class.java:10)
+10-16 12:00:01.123  1234  1234 E AndroidRuntime:     at some.Class.caller(Class.java:30)
+10-16 12:00:01.123  1234  1234 E AndroidRuntime: Caused by: some.CrashException: inner
+10-16 12:00:01.123  1234  1234 E AndroidRuntime:     at some.Class.inlinee(Class.java:10)
+10-16 12:00:01.123  1234  1234 E AndroidRuntime:     at some.Class.caller(Class.java:30)
+10-16 12:00:01.123  1234  1234 E AndroidRuntime: \t... 1 more
+",
+    );
+}
+
+#[test]
+fn test_brief_and_tag() {
+    assert_remapped(
+        "\
+E/AndroidRuntime( 1234): c: boom
+E/AndroidRuntime( 1234): \tat b.a(SourceFile:1)
+W/System.err: c
+W/System.err: \tat b.a(SourceFile:1)
+",
+        "\
+E/AndroidRuntime( 1234): some.CrashException: boom
+E/AndroidRuntime( 1234):     at some.Class.inlinee(Class.java:10)
+E/AndroidRuntime( 1234):     at some.Class.caller(Class.java:30)
+W/System.err: some.CrashException
+W/System.err:     at some.Class.inlinee(Class.java:10)
+W/System.err:     at some.Class.caller(Class.java:30)
+",
+    );
+}
+
+#[test]
+fn test_interleaved_processes() {
+    // The outline frame of one process applies to its next frame, not to the
+    // frame another process logs in between.
+    assert_remapped(
+        "\
+10-16 12:00:01.123  1234  1234 E AndroidRuntime: \tat a.a(SourceFile:2)
+10-16 12:00:01.124  5678  5679 W System.err: \tat b.a(SourceFile:1)
+10-16 12:00:01.125  1234  1234 E AndroidRuntime: \tat b.s(SourceFile:27)
+",
+        "\
+10-16 12:00:01.124  5678  5679 W System.err:     at some.Class.inlinee(Class.java:10)
+10-16 12:00:01.124  5678  5679 W System.err:     at some.Class.caller(Class.java:30)
+10-16 12:00:01.125  1234  1234 E AndroidRuntime:     at some.Class.outlineCaller(Class.java:99)
+",
+    );
+}
+
+#[test]
+fn test_long_format() {
+    // Headers and blank lines don't interrupt a trace, and the outline frame
+    // of one thread applies to its own next frame.
+    assert_remapped(
+        "\
+[ 10-16 12:00:01.123  1234: 1234 E/AndroidRuntime ]
+c: boom
+
+[ 10-16 12:00:01.123  1234: 1234 E/AndroidRuntime ]
+\tat a.a(SourceFile:2)
+
+[ 10-16 12:00:01.124  5678: 5679 W/System.err ]
+\tat b.a(SourceFile:1)
+
+[ 10-16 12:00:01.125  1234: 1234 E/AndroidRuntime ]
+\tat b.s(SourceFile:27)
+
+",
+        "\
+[ 10-16 12:00:01.123  1234: 1234 E/AndroidRuntime ]
+some.CrashException: boom
+
+[ 10-16 12:00:01.123  1234: 1234 E/AndroidRuntime ]
+
+[ 10-16 12:00:01.124  5678: 5679 W/System.err ]
+    at some.Class.inlinee(Class.java:10)
+    at some.Class.caller(Class.java:30)
+
+[ 10-16 12:00:01.125  1234: 1234 E/AndroidRuntime ]
+    at some.Class.outlineCaller(Class.java:99)
+
+",
+    );
+}
@@ -0,0 +1,90 @@
+#![allow(clippy::unwrap_used)]
+
+use proguard::{MergeError, ProguardCache, ProguardMapper, ProguardMapping, StackFrame};
+
+const BASE: &str = r#"# compiler: R8
+com.example.app.MainActivity -> a.a:
+# {"id":"sourceFile","fileName":"MainActivity.kt"}
+    1:1:void onCreate(android.os.Bundle):12:12 -> onCreate
+    2:2:void launchFeature():20:20 -> a
+com.example.app.Util -> a.b:
+    1:1:void log(java.lang.String):5:5 -> a
+"#;
+
+const FEATURE: &str = r#"# compiler: R8
+com.example.feature.FeatureActivity -> b.a:
+# {"id":"sourceFile","fileName":"FeatureActivity.kt"}
+    1:1:void crash():30:30 -> a
+    2:2:void helper():40:40 -> a
+    2:2:void start():35 -> a
+com.example.app.Util -> a.b:
+    1:1:void log(java.lang.String):5:5 -> a
+"#;
+
+const STACKTRACE: &str = "\
+java.lang.IllegalStateException: boom
+    at b.a.a(SourceFile:2)
+    at a.b.a(SourceFile:1)
+    at a.a.a(SourceFile:2)";
+
+const EXPECTED: &str = "\
+java.lang.IllegalStateException: boom
+    at com.example.feature.FeatureActivity.helper(FeatureActivity.kt:40)
+    at com.example.feature.FeatureActivity.start(FeatureActivity.kt:35)
+    at com.example.app.Util.log(Util.java:5)
+    at com.example.app.MainActivity.launchFeature(MainActivity.kt:20)
+";
+
+fn mappings() -> [ProguardMapping<'static>; 2] {
+    [
+        ProguardMapping::new(BASE.as_bytes()),
+        ProguardMapping::new(FEATURE.as_bytes()),
+    ]
+}
+
+#[test]
+fn test_merged_mapper() {
+    let mapper = ProguardMapper::new_merged(&mappings(), false).unwrap();
+    assert_eq!(mapper.remap_stacktrace(STACKTRACE).unwrap(), EXPECTED);
+
+    // Classes contained in several mappings don't produce duplicate frames.
+    let frames: Vec<_> = mapper
+        .remap_frame(&StackFrame::new("a.b", "a", 1))
+        .collect();
+    assert_eq!(frames.len(), 1);
+}
+
+#[test]
+fn test_merged_cache() {
+    let mut buf = Vec::new();
+    ProguardCache::write_merged(&mappings(), &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+
+    assert_eq!(cache.remap_stacktrace(STACKTRACE).unwrap(), EXPECTED);
+
+    let frames: Vec<_> = cache.remap_frame(&StackFrame::new("a.b", "a", 1)).collect();
+    assert_eq!(frames.len(), 1);
+}
+
+#[test]
+fn test_merge_conflict() {
+    let conflicting = ProguardMapping::new(b"com.example.feature.Other -> a.a:");
+
+    let err = ProguardMapper::new_merged(&[mappings()[0], conflicting], false).unwrap_err();
+    assert!(matches!(
+        err,
+        MergeError::ConflictingClass {
+            obfuscated: "a.a",
+            first: "com.example.app.MainActivity",
+            second: "com.example.feature.Other",
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "obfuscated class `a.a` is mapped to both `com.example.app.MainActivity` and `com.example.feature.Other`"
+    );
+
+    let mut buf = Vec::new();
+    assert!(ProguardCache::write_merged(&[mappings()[0], conflicting], &mut buf).is_err());
+}
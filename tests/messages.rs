@@ -0,0 +1,84 @@
+//! Tests for remapping obfuscated names in exception messages.
+#![allow(clippy::unwrap_used)]
+
+use proguard::{ProguardCache, ProguardMapper, ProguardMapping, RemapOptions, StackTrace};
+
+const MAPPING: &str = "\
+com.example.Main -> a.a:
+    com.example.Listener listener -> a
+    1:1:void start():10:10 -> a
+    2:2:void stop():20:20 -> b
+com.example.Listener -> a.b:
+com.example.Crash -> a.c:
+";
+
+const INPUT: &str = "\
+java.lang.ClassCastException: a.b cannot be cast to a.a
+    at a.a.a(SourceFile:1)
+Caused by: java.lang.NullPointerException: Attempt to read from field 'a.b a.a.a' on a null object reference
+    at a.a.b(SourceFile:2)
+Caused by: a.c: No virtual method b()V in class La/a; or its super classes
+";
+
+const EXPECTED: &str = "\
+java.lang.ClassCastException: com.example.Listener cannot be cast to com.example.Main
+    at com.example.Main.start(Main.java:10)
+Caused by: java.lang.NullPointerException: Attempt to read from field 'com.example.Listener com.example.Main.listener' on a null object reference
+    at com.example.Main.stop(Main.java:20)
+Caused by: com.example.Crash: No virtual method stop()V in class Lcom/example/Main; or its super classes
+";
+
+#[test]
+fn test_remap_messages() {
+    let mapping = ProguardMapping::new(MAPPING.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+    let mapper = ProguardMapper::from(MAPPING);
+
+    let options = RemapOptions::new().remap_messages(true);
+    let remapped = mapper
+        .remap_stacktrace_with_options(INPUT, &options)
+        .unwrap();
+    assert_eq!(remapped, EXPECTED);
+    let remapped = cache
+        .remap_stacktrace_with_options(INPUT, &options)
+        .unwrap();
+    assert_eq!(remapped, EXPECTED);
+
+    // Messages are kept as they are by default.
+    let remapped = mapper.remap_stacktrace(INPUT).unwrap();
+    assert!(remapped.starts_with("java.lang.ClassCastException: a.b cannot be cast to a.a\n"));
+    let remapped = cache.remap_stacktrace(INPUT).unwrap();
+    assert!(remapped.starts_with("java.lang.ClassCastException: a.b cannot be cast to a.a\n"));
+}
+
+#[test]
+fn test_remap_messages_typed() {
+    let mapping = ProguardMapping::new(MAPPING.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    let mapper = ProguardMapper::from(MAPPING);
+
+    let options = RemapOptions::new().remap_messages(true);
+    let trace = StackTrace::try_parse(INPUT.as_bytes()).unwrap();
+    for remapped in [
+        mapper.remap_stacktrace_typed_with_options(&trace, &options),
+        cache.remap_stacktrace_typed_with_options(&trace, &options),
+    ] {
+        let exception = remapped.exception().unwrap();
+        assert_eq!(exception.class(), "java.lang.ClassCastException");
+        assert_eq!(
+            exception.message(),
+            Some("com.example.Listener cannot be cast to com.example.Main")
+        );
+
+        let cause = remapped.cause().unwrap().exception().unwrap();
+        assert_eq!(
+            cause.message(),
+            Some("Attempt to read from field 'com.example.Listener com.example.Main.listener' on a null object reference")
+        );
+    }
+}
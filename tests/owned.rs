@@ -0,0 +1,65 @@
+//! Tests for the self-owning `OwnedProguardMapper`.
+#![allow(clippy::unwrap_used)]
+
+use std::sync::Arc;
+
+use proguard::{OwnedProguardMapper, ProguardMapper, StackFrame, StackTrace};
+
+const MAPPING: &str = r#"some.Main -> b:
+    1:1:void main(java.lang.String[]):5:5 -> a
+some.MainException -> d:"#;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_send_sync() {
+    assert_send_sync::<ProguardMapper<'static>>();
+    assert_send_sync::<OwnedProguardMapper>();
+}
+
+#[test]
+fn test_owned_mapper() {
+    let mapper = OwnedProguardMapper::from(MAPPING.to_owned());
+    assert_eq!(mapper.data(), MAPPING.as_bytes());
+    assert_eq!(mapper.get().remap_class("d"), Some("some.MainException"));
+
+    let frames: Vec<_> = mapper
+        .get()
+        .remap_frame(&StackFrame::new("b", "a", 1))
+        .collect();
+    assert_eq!(
+        frames,
+        [StackFrame::with_file("some.Main", "main", 5, "Main.java")]
+    );
+}
+
+#[test]
+fn test_owned_mapper_threads() {
+    let mapper = Arc::new(OwnedProguardMapper::new(MAPPING.as_bytes().to_vec()));
+    let expected = "\
+some.MainException: Main
+    at some.Main.main(Main.java:5)
+";
+
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let mapper = Arc::clone(&mapper);
+            std::thread::spawn(move || {
+                let input = String::from("d: Main\n\tat b.a(SourceFile:1)\n");
+                let trace = StackTrace::try_parse(input.as_bytes()).unwrap();
+                let remapped = mapper.get().remap_stacktrace(&input).unwrap();
+                let typed = mapper
+                    .get()
+                    .remap_stacktrace_typed(&trace.into_owned())
+                    .into_owned();
+                (remapped, typed)
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        let (remapped, typed) = worker.join().unwrap();
+        assert_eq!(remapped, expected);
+        assert_eq!(typed.to_string(), expected);
+    }
+}
@@ -0,0 +1,178 @@
+//! Tests for the provenance of remapped frames.
+#![allow(clippy::unwrap_used)]
+
+use proguard::{
+    FileSource, FrameProvenance, ProguardCache, ProguardMapper, ProguardMapping, StackFrame,
+    StackTrace,
+};
+
+const MAPPING: &str = r#"# {"id":"com.android.tools.r8.mapping","version":"2.0"}
+outline.Class -> a:
+    1:2:int outline():0 -> a
+# {"id":"com.android.tools.r8.outline"}
+some.Class -> b:
+# {"id":"sourceFile","fileName":"SomeClass.kt"}
+    1:1:void inlinee():10:10 -> a
+    1:1:void middle():20 -> a
+    1:1:void caller():30 -> a
+    2:2:void caller():31:31 -> a
+    4:5:int outlineCaller(int):98:99 -> s
+    27:27:int outlineCaller(int):0:0 -> s
+# {"id":"com.android.tools.r8.outlineCallsite","positions":{"1":4,"2":5}}
+other.Class -> c:
+    1:1:void inlinee():10:10 -> a
+    1:1:void other(int):11:11 -> a
+"#;
+
+fn cache() -> Vec<u8> {
+    let mapping = ProguardMapping::new(MAPPING.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    ProguardCache::parse(&buf).unwrap().test();
+    buf
+}
+
+fn provenance<'a>(frame: &'a StackFrame) -> &'a FrameProvenance<'a> {
+    frame.provenance().unwrap()
+}
+
+/// Summarizes the method, inline depth and file source of remapped frames.
+fn inline_summary(frames: &[StackFrame]) -> Vec<(String, usize, Option<FileSource>)> {
+    frames
+        .iter()
+        .map(|frame| {
+            let provenance = provenance(frame);
+            assert!(!provenance.is_class_only());
+            assert!(!provenance.is_outline_callsite());
+            (
+                frame.method().to_owned(),
+                provenance.inline_depth(),
+                provenance.file_source(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_inline_provenance() {
+    let buf = cache();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    let mapper = ProguardMapper::from(MAPPING);
+
+    let frame = StackFrame::new("b", "a", 1);
+    let expected = [
+        ("inlinee".to_owned(), 2, Some(FileSource::Mapping)),
+        ("middle".to_owned(), 1, Some(FileSource::Mapping)),
+        ("caller".to_owned(), 0, Some(FileSource::Mapping)),
+    ];
+    let remapped: Vec<_> = mapper.remap_frame(&frame).collect();
+    assert_eq!(inline_summary(&remapped), expected);
+    let remapped = mapper.remap_frame_alternatives(&frame).concat();
+    assert_eq!(inline_summary(&remapped), expected);
+    let remapped: Vec<_> = cache.remap_frame(&frame).collect();
+    assert_eq!(inline_summary(&remapped), expected);
+
+    for frame in &remapped {
+        let provenance = provenance(frame);
+        assert_eq!(provenance.minified_class(), "b");
+        assert_eq!(provenance.minified_method(), "a");
+        assert_eq!(provenance.minified_line(), Some(1));
+        assert_eq!(provenance.is_inlined(), frame.method() != "caller");
+    }
+
+    let frame = StackFrame::new("c", "a", 1);
+    let expected = [
+        ("inlinee".to_owned(), 1, Some(FileSource::Synthesized)),
+        ("other".to_owned(), 0, Some(FileSource::Synthesized)),
+    ];
+    let remapped: Vec<_> = mapper.remap_frame(&frame).collect();
+    assert_eq!(inline_summary(&remapped), expected);
+    let remapped: Vec<_> = cache.remap_frame(&frame).collect();
+    assert_eq!(inline_summary(&remapped), expected);
+}
+
+#[test]
+fn test_inline_provenance_without_line() {
+    let buf = cache();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    let mapper = ProguardMapper::from(MAPPING);
+
+    let frame = StackFrame::try_parse(b"\tat b.a(Unknown Source)").unwrap();
+    let expected = [
+        ("inlinee".to_owned(), 2, Some(FileSource::Synthesized)),
+        ("middle".to_owned(), 1, Some(FileSource::Synthesized)),
+        ("caller".to_owned(), 0, Some(FileSource::Synthesized)),
+    ];
+    let remapped = mapper.remap_frame_alternatives(&frame).concat();
+    assert_eq!(inline_summary(&remapped), expected);
+    let remapped: Vec<_> = cache.remap_frame(&frame).collect();
+    assert_eq!(inline_summary(&remapped), expected);
+    assert!(remapped
+        .iter()
+        .all(|frame| provenance(frame).minified_line().is_none()));
+}
+
+#[test]
+fn test_provenance_is_not_compared() {
+    let mapper = ProguardMapper::from(MAPPING);
+    let remapped: Vec<_> = mapper.remap_frame(&StackFrame::new("b", "a", 2)).collect();
+    assert_eq!(
+        remapped,
+        [StackFrame::with_file(
+            "some.Class",
+            "caller",
+            31,
+            "SomeClass.kt"
+        )]
+    );
+    assert!(StackFrame::new("b", "a", 2).provenance().is_none());
+}
+
+#[test]
+fn test_class_only_provenance() {
+    let buf = cache();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    let mapper = ProguardMapper::from(MAPPING);
+
+    let trace = StackTrace::try_parse(b"\tat b.a(SourceFile:3)").unwrap();
+    for remapped in [
+        mapper.remap_stacktrace_typed(&trace),
+        cache.remap_stacktrace_typed(&trace),
+    ] {
+        let [frame] = remapped.frames() else {
+            panic!("expected a single frame");
+        };
+        assert_eq!(frame.method(), "a");
+        let provenance = provenance(frame);
+        assert!(provenance.is_class_only());
+        assert_eq!(provenance.minified_line(), Some(3));
+        assert_eq!(provenance.inline_depth(), 0);
+        assert_eq!(provenance.file_source(), Some(FileSource::Synthesized));
+    }
+}
+
+#[test]
+fn test_outline_callsite_provenance() {
+    let buf = cache();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    let mapper = ProguardMapper::from(MAPPING);
+
+    let trace =
+        StackTrace::try_parse(b"java.io.IOException: INVALID_SENDER\n\tat a.a(:2)\n\tat b.s(:27)")
+            .unwrap();
+    for remapped in [
+        mapper.remap_stacktrace_typed(&trace),
+        cache.remap_stacktrace_typed(&trace),
+    ] {
+        let [frame] = remapped.frames() else {
+            panic!("expected a single frame");
+        };
+        assert_eq!(frame.method(), "outlineCaller");
+        assert_eq!(frame.line(), Some(99));
+        let provenance = provenance(frame);
+        assert!(provenance.is_outline_callsite());
+        assert_eq!(provenance.minified_method(), "s");
+        assert_eq!(provenance.minified_line(), Some(27));
+        assert_eq!(provenance.file_source(), Some(FileSource::Mapping));
+    }
+}
@@ -2,8 +2,9 @@
 //!
 //! These tests are based on the R8 retrace test suite from:
 //! src/test/java/com/android/tools/r8/retrace/stacktraces/
+#![allow(clippy::unwrap_used)]
 
-use proguard::{ProguardCache, ProguardMapper, ProguardMapping};
+use proguard::{ProguardCache, ProguardMapper, ProguardMapping, RemapOptions, StackFrame};
 
 // =============================================================================
 // AmbiguousStackTrace
@@ -342,3 +343,183 @@ Exception in thread \"main\" java.lang.NullPointerException
     let actual = cache.remap_stacktrace(input).unwrap();
     assert_eq!(actual.trim(), expected.trim());
 }
+
+// =============================================================================
+// Alternative inline chains
+// =============================================================================
+
+fn assert_alternatives(mapping: &str, frame: StackFrame, expected: &[&[(&str, usize)]]) {
+    let chains = |chains: Vec<Vec<StackFrame>>| -> Vec<Vec<(String, usize)>> {
+        chains
+            .iter()
+            .map(|chain| {
+                chain
+                    .iter()
+                    .map(|f| (f.full_method(), f.line().unwrap_or(0)))
+                    .collect()
+            })
+            .collect()
+    };
+    let expected: Vec<Vec<(String, usize)>> = expected
+        .iter()
+        .map(|chain| chain.iter().map(|(m, l)| (m.to_string(), *l)).collect())
+        .collect();
+
+    let mapper = ProguardMapper::from(mapping);
+    assert_eq!(chains(mapper.remap_frame_alternatives(&frame)), expected);
+
+    let mapping = ProguardMapping::new(mapping.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+    assert_eq!(chains(cache.remap_frame_alternatives(&frame)), expected);
+}
+
+#[test]
+fn test_alternatives() {
+    assert_alternatives(
+        AMBIGUOUS_STACKTRACE_MAPPING,
+        StackFrame::with_file("a.a", "a", 0, "Unknown Source"),
+        &[
+            &[("com.android.tools.r8.R8.foo", 0)],
+            &[("com.android.tools.r8.R8.bar", 0)],
+        ],
+    );
+
+    // The signature of the minified method tells the overloads apart.
+    assert_alternatives(
+        AMBIGUOUS_STACKTRACE_MAPPING,
+        StackFrame::with_file("a.a", "a", 0, "Unknown Source").with_signature("(I)V"),
+        &[&[("com.android.tools.r8.R8.foo", 0)]],
+    );
+    assert_alternatives(
+        AMBIGUOUS_STACKTRACE_MAPPING,
+        StackFrame::with_file("a.a", "a", 0, "Unknown Source").with_signature("(II)V"),
+        &[&[("com.android.tools.r8.R8.bar", 0)]],
+    );
+
+    assert_alternatives(
+        AMBIGUOUS_INLINE_FRAMES_MAPPING,
+        StackFrame::new("a.a", "a", 1),
+        &[&[
+            ("com.android.tools.r8.R8.foo", 42),
+            ("com.android.tools.r8.R8.bar", 32),
+            ("com.android.tools.r8.R8.baz", 10),
+        ]],
+    );
+
+    assert_alternatives(
+        AMBIGUOUS_MULTIPLE_INLINE_MAPPING,
+        StackFrame::new("com.android.tools.r8.Internal", "zza", 10),
+        &[
+            &[
+                ("some.inlinee1", 10),
+                ("com.android.tools.r8.Internal.foo", 10),
+            ],
+            &[
+                ("some.inlinee2", 20),
+                ("com.android.tools.r8.Internal.foo", 42),
+            ],
+        ],
+    );
+}
+
+// =============================================================================
+// R8 retrace `<OR>` output
+// =============================================================================
+
+fn assert_marked_alternatives(mapping: &str, input: &str, expected: &str) {
+    let options = RemapOptions::new().mark_alternatives(true);
+
+    let mapper = ProguardMapper::from(mapping);
+    let actual = mapper
+        .remap_stacktrace_with_options(input, &options)
+        .unwrap();
+    assert_eq!(actual.trim(), expected.trim());
+
+    let mapping = ProguardMapping::new(mapping.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+
+    let actual = cache
+        .remap_stacktrace_with_options(input, &options)
+        .unwrap();
+    assert_eq!(actual.trim(), expected.trim());
+}
+
+#[test]
+fn test_mark_alternatives() {
+    assert_marked_alternatives(
+        AMBIGUOUS_STACKTRACE_MAPPING,
+        "\
+com.android.tools.r8.CompilationException: foo[parens](Source:3)
+\tat a.a.a(Unknown Source)
+\tat com.android.tools.r8.R8.main(Unknown Source)
+",
+        "\
+com.android.tools.r8.CompilationException: foo[parens](Source:3)
+\tat com.android.tools.r8.R8.bar(R8.java)
+\t<OR> at com.android.tools.r8.R8.foo(R8.java)
+\tat com.android.tools.r8.R8.main(Unknown Source)
+",
+    );
+
+    // The output of R8's retrace for `AmbiguousStackTrace`.
+    assert_marked_alternatives(
+        AMBIGUOUS_STACKTRACE_MAPPING,
+        "\
+com.android.tools.r8.CompilationException: foo[parens](Source:3)
+\tat a.a.a(Unknown Source)
+\tat a.a.a(Unknown Source)
+\tat com.android.tools.r8.R8.main(Unknown Source)
+Caused by: com.android.tools.r8.CompilationException: foo[parens](Source:3)
+\tat a.a.a(Unknown Source)
+\t... 42 more
+",
+        "\
+com.android.tools.r8.CompilationException: foo[parens](Source:3)
+\tat com.android.tools.r8.R8.bar(R8.java)
+\t<OR> at com.android.tools.r8.R8.foo(R8.java)
+\tat com.android.tools.r8.R8.bar(R8.java)
+\t<OR> at com.android.tools.r8.R8.foo(R8.java)
+\tat com.android.tools.r8.R8.main(Unknown Source)
+Caused by: com.android.tools.r8.CompilationException: foo[parens](Source:3)
+\tat com.android.tools.r8.R8.bar(R8.java)
+\t<OR> at com.android.tools.r8.R8.foo(R8.java)
+\t... 42 more
+",
+    );
+
+    assert_marked_alternatives(
+        AMBIGUOUS_MULTIPLE_INLINE_MAPPING,
+        "\
+java.lang.IndexOutOfBoundsException
+    at com.android.tools.r8.Internal.zza(SourceFile:10)
+",
+        "\
+java.lang.IndexOutOfBoundsException
+    at some.inlinee1(some.java:10)
+    at com.android.tools.r8.Internal.foo(Internal.java:10)
+    <OR> at some.inlinee2(some.java:20)
+    at com.android.tools.r8.Internal.foo(Internal.java:42)
+",
+    );
+
+    // A single inline chain is printed without markers.
+    assert_marked_alternatives(
+        AMBIGUOUS_INLINE_FRAMES_MAPPING,
+        "\
+java.lang.Exception
+  at a.a.a(Unknown Source:1)
+",
+        "\
+java.lang.Exception
+  at com.android.tools.r8.R8.foo(R8.java:42)
+  at com.android.tools.r8.R8.bar(R8.java:32)
+  at com.android.tools.r8.R8.baz(R8.java:10)
+",
+    );
+}
@@ -3,6 +3,8 @@
 //! These tests are based on the R8 retrace test suite from:
 //! src/test/java/com/android/tools/r8/retrace/stacktraces/
 
+use std::io::Cursor;
+
 use proguard::{ProguardCache, ProguardMapper, ProguardMapping, StackFrame};
 
 // =============================================================================
@@ -355,6 +357,40 @@ java.lang.NullPointerException
     assert_eq!(actual.trim(), expected.trim());
 }
 
+#[test]
+fn test_npe_inline_retrace_old_mapping_version() {
+    // Rewrite frame rules were only introduced in version 2.0, so they are ignored in older files.
+    let mapping = NPE_INLINE_RETRACE_MAPPING.replace("\"version\":\"2.0\"", "\"version\":\"1.0\"");
+
+    let input = "\
+java.lang.NullPointerException
+	at a.a(:4)
+";
+
+    let expected = "\
+java.lang.NullPointerException
+    at other.Class(other.java:23)
+    at some.Class.caller(Class.java:7)
+";
+
+    let mapper = ProguardMapper::from(mapping.as_str());
+    let actual = mapper.remap_stacktrace(input).unwrap();
+    assert_eq!(actual.trim(), expected.trim());
+
+    let mut buf = Vec::new();
+    ProguardCache::write(&ProguardMapping::new(mapping.as_bytes()), &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    let actual = cache.remap_stacktrace(input).unwrap();
+    assert_eq!(actual.trim(), expected.trim());
+
+    let mut buf = Cursor::new(Vec::new());
+    let mut scratch = Cursor::new(Vec::new());
+    ProguardCache::write_from_reader(mapping.as_bytes(), &mut buf, &mut scratch).unwrap();
+    let cache = ProguardCache::parse(buf.get_ref()).unwrap();
+    let actual = cache.remap_stacktrace(input).unwrap();
+    assert_eq!(actual.trim(), expected.trim());
+}
+
 // =============================================================================
 // InlineRemoveFrameJava17StackTrace - Java 17 NPE with rewriteFrame
 // =============================================================================
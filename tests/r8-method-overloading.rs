@@ -124,3 +124,124 @@ fn test_retrace_mapping_with_overloads_api_includes_sync_with_line() {
     let remapped: Vec<_> = cache.remap_frame(&frame).collect();
     assert!(remapped.iter().any(|f| f.method() == "sync"));
 }
+
+// =============================================================================
+// Residual signatures
+// =============================================================================
+
+const RESIDUAL_SIGNATURE_MAPPING: &str = r#"# {"id":"com.android.tools.r8.mapping","version":"2.2"}
+com.example.Main -> a:
+    1:1:void foo(int):10:10 -> a
+    # {"id":"com.android.tools.r8.residualsignature","signature":"(I)V"}
+    2:2:void foo(int):11:11 -> a
+    1:1:void bar(int,int):20:20 -> a
+    # {"id":"com.android.tools.r8.residualsignature","signature":"(II)V"}
+    3:3:void inlined():30:30 -> a
+    3:3:void baz(long):40:40 -> a
+    # {"id":"com.android.tools.r8.residualsignature","signature":"(J)V"}
+"#;
+
+#[test]
+fn test_residual_signature() {
+    let mapper = ProguardMapper::from(RESIDUAL_SIGNATURE_MAPPING);
+    let mapping = ProguardMapping::new(RESIDUAL_SIGNATURE_MAPPING.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+
+    let foo = StackFrame::with_file("com.example.Main", "foo", 10, "Main.java");
+    let bar = StackFrame::with_file("com.example.Main", "bar", 20, "Main.java");
+    let cases = [
+        (StackFrame::new("a", "a", 1), vec![foo.clone(), bar.clone()]),
+        (
+            StackFrame::new("a", "a", 1).with_signature("(I)V"),
+            vec![foo],
+        ),
+        (
+            StackFrame::new("a", "a", 1).with_signature("(II)V"),
+            vec![bar],
+        ),
+        // The signature also applies to later ranges of the same method.
+        (
+            StackFrame::new("a", "a", 2).with_signature("(I)V"),
+            vec![StackFrame::with_file(
+                "com.example.Main",
+                "foo",
+                11,
+                "Main.java",
+            )],
+        ),
+        // It also applies to the methods inlined into it.
+        (
+            StackFrame::new("a", "a", 3).with_signature("(J)V"),
+            vec![
+                StackFrame::with_file("com.example.Main", "inlined", 30, "Main.java"),
+                StackFrame::with_file("com.example.Main", "baz", 40, "Main.java"),
+            ],
+        ),
+        // Unknown signatures don't narrow the candidates.
+        (
+            StackFrame::new("a", "a", 1).with_signature("(Z)V"),
+            vec![
+                StackFrame::with_file("com.example.Main", "foo", 10, "Main.java"),
+                StackFrame::with_file("com.example.Main", "bar", 20, "Main.java"),
+            ],
+        ),
+    ];
+
+    for (frame, expected) in cases {
+        let mapped: Vec<_> = mapper.remap_frame(&frame).collect();
+        assert_eq!(mapped, expected);
+        let mapped: Vec<_> = cache.remap_frame(&frame).collect();
+        assert_eq!(mapped, expected);
+    }
+}
+
+const DERIVED_SIGNATURE_MAPPING: &str = r#"# {"id":"com.android.tools.r8.mapping","version":"2.2"}
+com.example.Main -> a:
+    void foo(int) -> a
+    void bar(com.example.Other) -> a
+    void baz(long) -> a
+    # {"id":"com.android.tools.r8.residualsignature","signature":"(I)V"}
+com.example.Other -> b:
+"#;
+
+#[test]
+fn test_derived_signature() {
+    let mapper = ProguardMapper::from(DERIVED_SIGNATURE_MAPPING);
+    let mapping = ProguardMapping::new(DERIVED_SIGNATURE_MAPPING.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+
+    let foo = StackFrame::with_file("com.example.Main", "foo", 0, "Main.java");
+    let bar = StackFrame::with_file("com.example.Main", "bar", 0, "Main.java");
+    let baz = StackFrame::with_file("com.example.Main", "baz", 0, "Main.java");
+    let cases = [
+        // Without a residual signature, the signature is derived from the
+        // original types, with class names obfuscated.
+        (
+            StackFrame::new("a", "a", 0).with_signature("(Lb;)V"),
+            vec![bar.clone()],
+        ),
+        // A residual signature takes precedence over the original types, and
+        // the methods in between are left out.
+        (
+            StackFrame::new("a", "a", 0).with_signature("(I)V"),
+            vec![foo.clone(), baz.clone()],
+        ),
+        (
+            StackFrame::new("a", "a", 0).with_signature("(J)V"),
+            vec![foo, bar, baz],
+        ),
+    ];
+
+    for (frame, expected) in cases {
+        let mapped: Vec<_> = mapper.remap_frame(&frame).collect();
+        assert_eq!(mapped, expected);
+        let mapped: Vec<_> = cache.remap_frame(&frame).collect();
+        assert_eq!(mapped, expected);
+    }
+}
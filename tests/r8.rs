@@ -1,3 +1,4 @@
+#[cfg(feature = "uuid")]
 use std::sync::LazyLock;
 
 use proguard::{ProguardCache, ProguardMapper, ProguardMapping, StackFrame, StackTrace, Throwable};
@@ -14,6 +15,7 @@ static MAPPING_REWRITE_COMPLEX: &str = include_str!("res/mapping-rewrite-complex
 static MAPPING_ZERO_LINE_INFO: &[u8] = include_bytes!("res/mapping-zero-line-info.txt");
 static MAPPING_INLINE_NO_BASE: &str = include_str!("res/mapping-inline-no-base.txt");
 
+#[cfg(feature = "uuid")]
 static MAPPING_WIN_R8: LazyLock<Vec<u8>> = LazyLock::new(|| {
     MAPPING_R8
         .iter()
@@ -88,6 +90,277 @@ fn test_method_matches() {
     assert_eq!(mapped.next(), None);
 }
 
+#[test]
+fn test_remap_field() {
+    let mapper = ProguardMapper::new(ProguardMapping::new(MAPPING_R8));
+
+    assert_eq!(
+        mapper.remap_field("a.a.a.a.c", "d"),
+        Some((
+            "android.arch.core.executor.ArchTaskExecutor",
+            "mDelegate",
+            "android.arch.core.executor.TaskExecutor"
+        ))
+    );
+    assert_eq!(
+        mapper.remap_field("a.a.a.a.d", "a"),
+        Some((
+            "android.arch.core.executor.DefaultTaskExecutor",
+            "mLock",
+            "java.lang.Object"
+        ))
+    );
+    assert_eq!(mapper.remap_field("a.a.a.a.c", "z"), None);
+    assert_eq!(mapper.remap_field("a.a.a.a.z", "a"), None);
+}
+
+#[test]
+fn test_remap_field_cache() {
+    let mapping = ProguardMapping::new(MAPPING_R8);
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+
+    assert_eq!(
+        cache.remap_field("a.a.a.a.c", "d"),
+        Some((
+            "android.arch.core.executor.ArchTaskExecutor",
+            "mDelegate",
+            "android.arch.core.executor.TaskExecutor"
+        ))
+    );
+    assert_eq!(
+        cache.remap_field("a.a.a.a.d", "a"),
+        Some((
+            "android.arch.core.executor.DefaultTaskExecutor",
+            "mLock",
+            "java.lang.Object"
+        ))
+    );
+    assert_eq!(cache.remap_field("a.a.a.a.c", "z"), None);
+    assert_eq!(cache.remap_field("a.a.a.a.z", "a"), None);
+}
+
+#[test]
+fn test_remap_field_ambiguous() {
+    let mapping = "\
+some.Class -> a:
+    int count -> a
+    java.lang.String name -> a
+    long total -> b
+    long total -> b
+";
+    let mapper = ProguardMapper::from(mapping);
+    assert_eq!(mapper.remap_field("a", "a"), None);
+    assert_eq!(
+        mapper.remap_field("a", "b"),
+        Some(("some.Class", "total", "long"))
+    );
+
+    let mapping = ProguardMapping::new(mapping.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+
+    assert_eq!(cache.remap_field("a", "a"), None);
+    assert_eq!(
+        cache.remap_field("a", "b"),
+        Some(("some.Class", "total", "long"))
+    );
+}
+
+#[test]
+fn test_obfuscate() {
+    let mapper = ProguardMapper::new(ProguardMapping::new(MAPPING_R8));
+
+    assert_eq!(
+        mapper.obfuscate_class("android.arch.core.executor.ArchTaskExecutor"),
+        Some("a.a.a.a.c")
+    );
+    assert_eq!(mapper.obfuscate_class("a.a.a.a.c"), None);
+
+    assert_eq!(
+        mapper.obfuscate_method(
+            "android.arch.core.executor.ArchTaskExecutor",
+            "postToMainThread",
+            "java.lang.Runnable"
+        ),
+        Some(("a.a.a.a.c", "b"))
+    );
+    assert_eq!(
+        mapper.obfuscate_method(
+            "android.arch.core.executor.ArchTaskExecutor",
+            "postToMainThread",
+            ""
+        ),
+        None
+    );
+
+    assert_eq!(
+        mapper.obfuscate_frame(&StackFrame::new(
+            "android.arch.core.executor.ArchTaskExecutor",
+            "getInstance",
+            73
+        )),
+        vec![StackFrame::new("a.a.a.a.c", "b", 4)]
+    );
+    assert_eq!(
+        mapper.obfuscate_frame(&StackFrame::new(
+            "android.arch.core.executor.ArchTaskExecutor",
+            "getInstance",
+            76
+        )),
+        vec![
+            StackFrame::new("a.a.a.a.c", "b", 6),
+            StackFrame::new("a.a.a.a.c", "b", 8)
+        ]
+    );
+    assert!(mapper
+        .obfuscate_frame(&StackFrame::new(
+            "android.arch.core.executor.ArchTaskExecutor",
+            "getInstance",
+            71
+        ))
+        .is_empty());
+}
+
+#[test]
+fn test_obfuscate_cache() {
+    let mapping = ProguardMapping::new(MAPPING_R8);
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+
+    assert_eq!(
+        cache.obfuscate_class("android.arch.core.executor.ArchTaskExecutor"),
+        Some("a.a.a.a.c")
+    );
+    assert_eq!(cache.obfuscate_class("a.a.a.a.c"), None);
+
+    assert_eq!(
+        cache.obfuscate_method(
+            "android.arch.core.executor.ArchTaskExecutor",
+            "postToMainThread",
+            "java.lang.Runnable"
+        ),
+        Some(("a.a.a.a.c", "b"))
+    );
+    assert_eq!(
+        cache.obfuscate_method(
+            "android.arch.core.executor.ArchTaskExecutor",
+            "postToMainThread",
+            ""
+        ),
+        None
+    );
+
+    assert_eq!(
+        cache.obfuscate_frame(&StackFrame::new(
+            "android.arch.core.executor.ArchTaskExecutor",
+            "getInstance",
+            73
+        )),
+        vec![StackFrame::new("a.a.a.a.c", "b", 4)]
+    );
+    assert_eq!(
+        cache.obfuscate_frame(&StackFrame::new(
+            "android.arch.core.executor.ArchTaskExecutor",
+            "getInstance",
+            76
+        )),
+        vec![
+            StackFrame::new("a.a.a.a.c", "b", 6),
+            StackFrame::new("a.a.a.a.c", "b", 8)
+        ]
+    );
+    assert!(cache
+        .obfuscate_frame(&StackFrame::new(
+            "android.arch.core.executor.ArchTaskExecutor",
+            "getInstance",
+            71
+        ))
+        .is_empty());
+}
+
+#[test]
+fn test_obfuscate_method_skips_inlined() {
+    let mapping = "\
+com.example.MainFragment$onActivityCreated$4 -> com.example.MainFragment$g:
+    1:1:void startEngines():90:90 -> onClick
+    1:1:void onClick(android.view.View):65 -> onClick
+    3:3:void startEngines():90:90 -> a
+";
+    let mapper = ProguardMapper::from(mapping);
+    assert_eq!(
+        mapper.obfuscate_method(
+            "com.example.MainFragment$onActivityCreated$4",
+            "startEngines",
+            ""
+        ),
+        Some(("com.example.MainFragment$g", "a"))
+    );
+
+    // Inlined positions are still valid obfuscated frames.
+    let frame = StackFrame::new(
+        "com.example.MainFragment$onActivityCreated$4",
+        "startEngines",
+        90,
+    );
+    let obfuscated = mapper.obfuscate_frame(&frame);
+    assert_eq!(
+        obfuscated,
+        vec![
+            StackFrame::new("com.example.MainFragment$g", "a", 3),
+            StackFrame::new("com.example.MainFragment$g", "onClick", 1),
+        ]
+    );
+    for f in &obfuscated {
+        let remapped = mapper.remap_frame(f).next().unwrap();
+        assert_eq!(
+            (remapped.class(), remapped.method()),
+            (frame.class(), frame.method())
+        );
+        assert_eq!(remapped.line(), frame.line());
+    }
+
+    let mapping = ProguardMapping::new(mapping.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+
+    assert_eq!(
+        cache.obfuscate_method(
+            "com.example.MainFragment$onActivityCreated$4",
+            "startEngines",
+            ""
+        ),
+        Some(("com.example.MainFragment$g", "a"))
+    );
+    assert_eq!(cache.obfuscate_frame(&frame), obfuscated);
+}
+
+#[test]
+fn test_obfuscate_class_duplicate_original() {
+    let mapping = "\
+com.example.Main -> c:
+com.example.Main -> b:
+com.example.Main -> d:
+";
+    let mapper = ProguardMapper::from(mapping);
+    assert_eq!(mapper.obfuscate_class("com.example.Main"), Some("b"));
+
+    let mapping = ProguardMapping::new(mapping.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+    assert_eq!(cache.obfuscate_class("com.example.Main"), Some("b"));
+}
+
 #[test]
 fn test_summary() {
     let mapping = ProguardMapping::new(MAPPING_R8);
@@ -95,9 +368,14 @@ fn test_summary() {
     let summary = mapping.summary();
     assert_eq!(summary.compiler(), Some("R8"));
     assert_eq!(summary.compiler_version(), Some("1.3.49"));
+    assert_eq!(summary.mapping_version(), None);
     assert_eq!(summary.min_api(), Some(15));
     assert_eq!(summary.class_count(), 1167);
     assert_eq!(summary.method_count(), 24076);
+    assert_eq!(summary.field_count(), 4128);
+    assert_eq!(summary.no_line_info_count(), 795);
+    assert_eq!(summary.inlined_count(), 0);
+    assert_eq!(summary.max_inline_depth(), 0);
 }
 
 #[cfg(feature = "uuid")]
@@ -0,0 +1,142 @@
+//! Tests for routing remapping through a `CacheRegistry`.
+#![allow(clippy::unwrap_used)]
+
+use std::sync::Arc;
+
+use proguard::{
+    CacheRegistry, MappingKey, ProguardCache, ProguardMapping, RegistryError, StackTrace,
+};
+
+const MAPPING_A: &[u8] = b"# pg_map_id: aaaaaaa
+some.Main -> b:
+    1:1:void main(java.lang.String[]):5:5 -> a
+";
+
+const MAPPING_B: &[u8] = b"# pg_map_id: bbbbbbb
+other.Main -> b:
+    1:1:void run():7:7 -> a
+";
+
+const INPUT: &str = "    at b.a(SourceFile:1)";
+
+fn cache(mapping: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    ProguardCache::write(&ProguardMapping::new(mapping), &mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn test_route_by_map_id() {
+    let mut registry = CacheRegistry::new(4);
+    registry
+        .insert_mapping(&ProguardMapping::new(MAPPING_A))
+        .unwrap();
+    registry
+        .insert(MappingKey::map_id("bbbbbbb"), cache(MAPPING_B))
+        .unwrap();
+
+    let a = MappingKey::map_id("aaaaaaa");
+    let b = MappingKey::map_id("bbbbbbb");
+    assert!(registry.contains(&a));
+    assert_eq!(
+        registry.remap_stacktrace(&a, INPUT).unwrap(),
+        "    at some.Main.main(Main.java:5)\n"
+    );
+    assert_eq!(
+        registry.remap_stacktrace(&b, INPUT).unwrap(),
+        "    at other.Main.run(Main.java:7)\n"
+    );
+
+    let trace = StackTrace::try_parse(INPUT.as_bytes()).unwrap();
+    let remapped = registry.remap_stacktrace_typed(&b, &trace).unwrap();
+    assert_eq!(remapped.frames()[0].class(), "other.Main");
+
+    let unknown = MappingKey::map_id("ccccccc");
+    assert!(!registry.contains(&unknown));
+    assert!(matches!(
+        registry.remap_stacktrace(&unknown, INPUT),
+        Err(RegistryError::UnknownKey(key)) if key == unknown
+    ));
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn test_route_by_uuid() {
+    let mapping = ProguardMapping::new(MAPPING_A);
+    let mut registry = CacheRegistry::new(4);
+    registry.insert_mapping(&mapping).unwrap();
+
+    assert_eq!(
+        registry
+            .remap_stacktrace(&mapping.uuid().into(), INPUT)
+            .unwrap(),
+        "    at some.Main.main(Main.java:5)\n"
+    );
+}
+
+#[test]
+fn test_invalid_cache() {
+    let mut registry = CacheRegistry::new(4);
+    assert!(matches!(
+        registry.insert(MappingKey::map_id("aaaaaaa"), b"not a cache".to_vec()),
+        Err(RegistryError::InvalidCache(_))
+    ));
+}
+
+#[test]
+fn test_lru_eviction() {
+    let dir = std::env::temp_dir().join(format!("proguard-registry-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path_a = dir.join("a.prgcache");
+    let path_b = dir.join("b.prgcache");
+    std::fs::write(&path_a, cache(MAPPING_A)).unwrap();
+    std::fs::write(&path_b, cache(MAPPING_B)).unwrap();
+
+    let a = MappingKey::map_id("aaaaaaa");
+    let b = MappingKey::map_id("bbbbbbb");
+    let mut registry = CacheRegistry::new(1);
+    registry.insert_file(a.clone(), &path_a);
+    registry.insert_file(b.clone(), &path_b);
+    let registry = Arc::new(registry);
+
+    // The cache of `a` stays loaded after its file is gone.
+    registry.remap_stacktrace(&a, INPUT).unwrap();
+    std::fs::remove_file(&path_a).unwrap();
+    let worker = {
+        let registry = Arc::clone(&registry);
+        let a = a.clone();
+        std::thread::spawn(move || registry.remap_stacktrace(&a, INPUT).unwrap())
+    };
+    assert_eq!(
+        worker.join().unwrap(),
+        "    at some.Main.main(Main.java:5)\n"
+    );
+
+    // Loading `b` evicts `a`, which then has to be read again.
+    registry.remap_stacktrace(&b, INPUT).unwrap();
+    assert!(matches!(
+        registry.remap_stacktrace(&a, INPUT),
+        Err(RegistryError::Io(_))
+    ));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_replace_and_remove() {
+    let key = MappingKey::map_id("aaaaaaa");
+    let mut registry = CacheRegistry::new(4);
+    registry.insert(key.clone(), cache(MAPPING_A)).unwrap();
+    registry.insert(key.clone(), cache(MAPPING_B)).unwrap();
+    assert_eq!(
+        registry.remap_stacktrace(&key, INPUT).unwrap(),
+        "    at other.Main.run(Main.java:7)\n"
+    );
+
+    assert!(registry.remove(&key));
+    assert!(!registry.contains(&key));
+    assert!(matches!(
+        registry.remap_stacktrace(&key, INPUT),
+        Err(RegistryError::UnknownKey(_))
+    ));
+}
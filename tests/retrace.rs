@@ -1,4 +1,4 @@
-use proguard::{ProguardCache, ProguardMapper, ProguardMapping, StackFrame};
+use proguard::{ProguardCache, ProguardMapper, ProguardMapping, StackFrame, StackTrace};
 
 #[test]
 fn test_remap() {
@@ -183,3 +183,116 @@ fn test_remap_compose_stacktrace_group_keys_cache() {
             .trim()
     );
 }
+
+#[test]
+fn test_remap_suppressed_typed() {
+    let mapping = r#"some.Resource -> a:
+    1:1:void close():10:10 -> a
+some.Main -> b:
+    1:1:void main(java.lang.String[]):5:5 -> a
+some.ResourceException -> c:
+some.MainException -> d:"#;
+    let stacktrace = "\
+d: Main
+\tat b.a(SourceFile:1)
+\tSuppressed: c: Close
+\t\tat a.a(SourceFile:1)
+\t\t... 1 more
+";
+    let expected = "\
+some.MainException: Main
+    at some.Main.main(Main.java:5)
+    Suppressed: some.ResourceException: Close
+        at some.Resource.close(Resource.java:10)
+        ... 1 more
+";
+
+    let trace = StackTrace::try_parse(stacktrace.as_bytes()).unwrap();
+
+    let mapper = ProguardMapper::from(mapping);
+    let mapped = mapper.remap_stacktrace_typed(&trace);
+    assert_eq!(mapped.to_string(), expected);
+
+    let mapping = ProguardMapping::new(mapping.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    let mapped = cache.remap_stacktrace_typed(&trace);
+    assert_eq!(mapped.to_string(), expected);
+}
+
+#[test]
+fn test_remap_suppressed_text() {
+    let mapping = r#"some.Resource -> a:
+    1:1:void close():10:10 -> a
+some.Main -> b:
+    1:1:void main(java.lang.String[]):5:5 -> a
+some.ResourceException -> c:
+some.MainException -> d:
+some.CloseCause -> e:"#;
+    // As printed by the JDK for a try-with-resources statement.
+    let stacktrace = "\
+d: Main
+\tat b.a(SourceFile:1)
+\tSuppressed: c: Close
+\t\tat a.a(SourceFile:1)
+\t\t... 1 more
+\tCaused by: e: io
+\t\t... 2 more
+";
+    let expected = "\
+some.MainException: Main
+    at some.Main.main(Main.java:5)
+\tSuppressed: some.ResourceException: Close
+    at some.Resource.close(Resource.java:10)
+\t\t... 1 more
+\tCaused by: some.CloseCause: io
+\t\t... 2 more
+";
+
+    let mapper = ProguardMapper::from(mapping);
+    assert_eq!(mapper.remap_stacktrace(stacktrace).unwrap(), expected);
+
+    let mapping = ProguardMapping::new(mapping.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    assert_eq!(cache.remap_stacktrace(stacktrace).unwrap(), expected);
+}
+
+#[test]
+fn test_remap_owned_typed() {
+    let mapping = r#"some.Main -> b:
+    1:1:void main(java.lang.String[]):5:5 -> a
+some.MainException -> d:"#;
+    let expected = "\
+some.MainException: Main
+    at some.Main.main(Main.java:5)
+";
+
+    // The input is dropped before remapping, and the results outlive the mapper.
+    let trace = {
+        let stacktrace = String::from("d: Main\n\tat b.a(SourceFile:1)\n");
+        StackTrace::try_parse(stacktrace.as_bytes())
+            .unwrap()
+            .into_owned()
+    };
+
+    let mapped = {
+        let mapper = ProguardMapper::from(mapping);
+        mapper.remap_stacktrace_typed(&trace).into_owned()
+    };
+    let mapped = std::thread::spawn(move || mapped.to_string())
+        .join()
+        .unwrap();
+    assert_eq!(mapped, expected);
+
+    let mapped = {
+        let mapping = ProguardMapping::new(mapping.as_bytes());
+        let mut buf = Vec::new();
+        ProguardCache::write(&mapping, &mut buf).unwrap();
+        let cache = ProguardCache::parse(&buf).unwrap();
+        cache.remap_stacktrace_typed(&trace).into_owned()
+    };
+    assert_eq!(mapped.to_string(), expected);
+}
@@ -0,0 +1,53 @@
+//! Tests for serializing typed stack traces.
+#![cfg(feature = "serde")]
+#![allow(clippy::unwrap_used)]
+
+use proguard::{ProguardMapper, StackTrace};
+
+#[test]
+fn test_serialize_remapped() {
+    let mapper = ProguardMapper::from(
+        "\
+com.example.Crash -> a:
+com.example.Main -> b:
+    1:1:void run():10:10 -> a",
+    );
+    let trace =
+        StackTrace::try_parse(b"a: boom\n    at b.a(SourceFile:1)\n    ... 2 more").unwrap();
+    let remapped = mapper.remap_stacktrace_typed(&trace);
+
+    let json = serde_json::to_value(&remapped).unwrap();
+    let frame = &json["frames"][0];
+    assert_eq!(json["exception"]["class"], "com.example.Crash");
+    assert_eq!(json["exception"]["message"], "boom");
+    assert_eq!(json["elided_frames"], 2);
+    assert_eq!(frame["class"], "com.example.Main");
+    assert_eq!(frame["method"], "run");
+    assert_eq!(frame["line"], 10);
+    assert_eq!(frame["file"], "Main.java");
+    assert_eq!(frame["provenance"]["minified_class"], "b");
+    assert_eq!(frame["provenance"]["file_source"], "synthesized");
+}
+
+#[test]
+fn test_roundtrip() {
+    let input = "\
+a: boom
+    at b.a(SourceFile:1)
+    Suppressed: c
+        at b.b(SourceFile:2)
+Caused by: d: inner
+    at b.c(Unknown Source)
+";
+    let trace = StackTrace::try_parse(input.as_bytes()).unwrap();
+
+    let json = serde_json::to_string(&trace).unwrap();
+    let parsed: StackTrace = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, trace);
+    assert_eq!(parsed.to_string(), input);
+
+    // Fields may be left out.
+    let parsed: StackTrace =
+        serde_json::from_str(r#"{"frames": [{"class": "b", "method": "a"}]}"#).unwrap();
+    assert_eq!(parsed.to_string(), "    at b.a(<unknown>)\n");
+}
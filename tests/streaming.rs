@@ -0,0 +1,142 @@
+#![allow(clippy::unwrap_used)]
+
+use std::io::{BufReader, Cursor};
+
+use proguard::{ProguardCache, ProguardMapping, StackFrame};
+
+const MAPPINGS: &[&[u8]] = &[
+    include_bytes!("res/mapping.txt"),
+    include_bytes!("res/mapping-r8.txt"),
+    include_bytes!("res/mapping-inlines.txt"),
+    include_bytes!("res/mapping-outline.txt"),
+    include_bytes!("res/mapping-outline-complex.txt"),
+    include_bytes!("res/mapping-rewrite-complex.txt"),
+    include_bytes!("res/mapping-zero-line-info.txt"),
+];
+
+fn write(mapping: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    ProguardCache::write(&ProguardMapping::new(mapping), &mut buf).unwrap();
+    buf
+}
+
+fn write_from_reader(mapping: &[u8]) -> Vec<u8> {
+    // A tiny buffer makes sure that lines are read in several pieces.
+    let reader = BufReader::with_capacity(7, mapping);
+    let mut buf = Cursor::new(Vec::new());
+    let mut scratch = Cursor::new(Vec::new());
+    ProguardCache::write_from_reader(reader, &mut buf, &mut scratch).unwrap();
+    buf.into_inner()
+}
+
+fn dump(cache: &ProguardCache) -> String {
+    format!(
+        "{:#?}\n{:#?}\n{:#?}\n{}",
+        cache.debug_classes().collect::<Vec<_>>(),
+        cache.debug_members().collect::<Vec<_>>(),
+        cache.debug_members_by_params().collect::<Vec<_>>(),
+        cache.display(),
+    )
+}
+
+#[test]
+fn test_same_as_write() {
+    for mapping in MAPPINGS {
+        let expected = write(mapping);
+        let expected = ProguardCache::parse(&expected).unwrap();
+
+        let streamed = write_from_reader(mapping);
+        let streamed = ProguardCache::parse(&streamed).unwrap();
+        streamed.test();
+
+        assert_eq!(dump(&streamed), dump(&expected));
+    }
+}
+
+#[test]
+fn test_remap() {
+    let streamed = write_from_reader(include_bytes!("res/mapping-rewrite-complex.txt"));
+    let streamed = ProguardCache::parse(&streamed).unwrap();
+    let expected = write(include_bytes!("res/mapping-rewrite-complex.txt"));
+    let expected = ProguardCache::parse(&expected).unwrap();
+
+    let trace = "\
+java.lang.NullPointerException: boom
+    at a.a(SourceFile:4)
+    at a.b(SourceFile:1)";
+    assert_eq!(
+        streamed.remap_stacktrace(trace).unwrap(),
+        expected.remap_stacktrace(trace).unwrap()
+    );
+}
+
+#[test]
+fn test_inlined_from_later_class() {
+    // `Main` has a method inlined from `Other`, which only appears after it.
+    let mapping = br#"# compiler: R8
+com.example.Main -> a:
+# {"id":"sourceFile","fileName":"Main.kt"}
+    1:1:void com.example.Other.helper():20:20 -> a
+    1:1:void run():10:10 -> a
+com.example.Other -> b:
+# {"id":"sourceFile","fileName":"Other.kt"}
+    1:1:void helper():20:20 -> a
+# {"id":"com.android.tools.r8.synthesized"}
+"#;
+
+    let streamed = write_from_reader(mapping);
+    let streamed = ProguardCache::parse(&streamed).unwrap();
+    streamed.test();
+
+    let frames: Vec<_> = streamed
+        .remap_frame(&StackFrame::new("a", "a", 1))
+        .collect();
+    assert_eq!(
+        frames,
+        [
+            StackFrame::with_file("com.example.Other", "helper", 20, "Other.kt")
+                .with_method_synthesized(true),
+            StackFrame::with_file("com.example.Main", "run", 10, "Main.kt"),
+        ]
+    );
+
+    let expected = write(mapping);
+    let expected = ProguardCache::parse(&expected).unwrap();
+    assert_eq!(dump(&streamed), dump(&expected));
+}
+
+#[test]
+fn test_duplicate_class() {
+    let mapping = b"\
+com.example.Foo -> a:
+    1:1:void foo():10:10 -> b
+    int field -> b
+com.example.Bar -> c:
+    1:1:void bar():30:30 -> a
+com.example.Foo -> a:
+    1:1:void baz():20:20 -> a
+    2:2:void foo():11:11 -> b
+";
+
+    let streamed = write_from_reader(mapping);
+    let streamed = ProguardCache::parse(&streamed).unwrap();
+    streamed.test();
+
+    let expected = write(mapping);
+    let expected = ProguardCache::parse(&expected).unwrap();
+    assert_eq!(dump(&streamed), dump(&expected));
+
+    assert_eq!(
+        streamed.remap_method("a", "a"),
+        Some(("com.example.Foo", "baz"))
+    );
+    assert_eq!(
+        streamed.remap_frame(&StackFrame::new("a", "b", 2)).next(),
+        Some(StackFrame::with_file(
+            "com.example.Foo",
+            "foo",
+            11,
+            "Foo.java"
+        ))
+    );
+}
@@ -0,0 +1,62 @@
+//! Tests for remapping thread dumps.
+#![allow(clippy::unwrap_used)]
+
+use proguard::{ProguardCache, ProguardMapper, ProguardMapping, ThreadDump};
+
+const MAPPING: &str = "\
+com.example.Worker -> a.a:
+    1:1:void process():20:20 -> a
+    1:1:void run():10 -> a
+com.example.Lock -> a.b:
+";
+
+const DUMP: &str = r#"----- pid 1234 at 2024-10-16 12:00:00 -----
+
+"main" prio=5 tid=1 Blocked
+  | group="main" sCount=1 dsCount=0 flags=1 obj=0x72f3a000 self=0xb400007
+  at a.a.a(SourceFile:1)
+  - waiting to lock <0x0abc> (a a.b) held by thread 12
+  at android.os.Looper.loop(Looper.java:288)
+  - locked <0x0def> (a java.lang.Class<a.a>)
+
+"Thread-2" prio=5 tid=12 Sleeping
+  at java.lang.Thread.sleep(Native method)
+  - sleeping on <0x0123> (a java.lang.Object)
+----- end 1234 -----
+"#;
+
+const EXPECTED: &str = r#""main" prio=5 tid=1 Blocked
+  | group="main" sCount=1 dsCount=0 flags=1 obj=0x72f3a000 self=0xb400007
+    at com.example.Worker.process(Worker.java:20)
+    at com.example.Worker.run(Worker.java:10)
+    - waiting to lock <0x0abc> (a com.example.Lock) held by thread 12
+    at android.os.Looper.loop(Looper.java:288)
+    - locked <0x0def> (a java.lang.Class<com.example.Worker>)
+
+"Thread-2" prio=5 tid=12 Sleeping
+    at java.lang.Thread.sleep(Native method)
+    - sleeping on <0x0123> (a java.lang.Object)
+"#;
+
+#[test]
+fn test_remap_thread_dump() {
+    let dump = ThreadDump::try_parse(DUMP.as_bytes()).unwrap();
+
+    let mapper = ProguardMapper::from(MAPPING);
+    let remapped = mapper.remap_thread_dump(&dump);
+    assert_eq!(remapped.to_string(), EXPECTED);
+
+    let mapping = ProguardMapping::new(MAPPING.as_bytes());
+    let mut buf = Vec::new();
+    ProguardCache::write(&mapping, &mut buf).unwrap();
+    let cache = ProguardCache::parse(&buf).unwrap();
+    cache.test();
+    let remapped = cache.remap_thread_dump(&dump);
+    assert_eq!(remapped.to_string(), EXPECTED);
+
+    let [main, _] = remapped.threads() else {
+        panic!("expected two threads");
+    };
+    let methods: Vec<_> = main.frames().map(|frame| frame.method()).collect();
+    assert_eq!(methods, ["process", "run", "loop"]);
+}
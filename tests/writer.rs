@@ -0,0 +1,79 @@
+#![allow(clippy::unwrap_used)]
+
+use proguard::{MappingWriter, ProguardMapping, ProguardRecord};
+
+fn write_mapping(records: &[ProguardRecord<'_>]) -> Vec<u8> {
+    let mut writer = MappingWriter::new(Vec::new());
+    for record in records {
+        writer.write_record(record).unwrap();
+    }
+    writer.into_inner()
+}
+
+fn assert_roundtrip(source: &[u8]) {
+    let mapping = ProguardMapping::new(source);
+    let records: Vec<_> = mapping.iter().collect::<Result<_, _>>().unwrap();
+
+    let written = write_mapping(&records);
+    let reparsed: Vec<_> = ProguardMapping::new(&written)
+        .iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(records, reparsed);
+    // Writing is stable once the mapping is in canonical form.
+    assert_eq!(write_mapping(&reparsed), written);
+}
+
+#[test]
+fn test_roundtrip_fixtures() {
+    for source in [
+        &include_bytes!("res/mapping.txt")[..],
+        include_bytes!("res/mapping-r8.txt"),
+        include_bytes!("res/mapping-r8-symbolicated_file_names.txt"),
+        include_bytes!("res/mapping-inlines.txt"),
+        include_bytes!("res/mapping-inline-no-base.txt"),
+        include_bytes!("res/mapping-outline.txt"),
+        include_bytes!("res/mapping-outline-complex.txt"),
+        include_bytes!("res/mapping-rewrite-complex.txt"),
+        include_bytes!("res/mapping-zero-line-info.txt"),
+        include_bytes!("res/mapping-callback.txt"),
+        include_bytes!("res/mapping-callback-extra-class.txt"),
+        include_bytes!("res/mapping-callback-inner-class.txt"),
+    ] {
+        assert_roundtrip(source);
+    }
+}
+
+#[test]
+fn test_write_identical() {
+    let source = include_bytes!("res/mapping-inlines.txt");
+    let records: Vec<_> = ProguardMapping::new(source)
+        .iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(write_mapping(&records)).unwrap(),
+        std::str::from_utf8(source).unwrap()
+    );
+}
+
+#[test]
+fn test_write_unknown_headers() {
+    let source = br#"# {"id":"com.android.tools.r8.unknown","payload":{"b":[1,2],"a":null}}
+some.Class -> a:
+# {"id":"com.example.custom","value":"x"}
+    1:1:void run():10:10 -> a
+      # {"id":"com.example.member","flags":["a","b"]}
+"#;
+    let records: Vec<_> = ProguardMapping::new(source)
+        .iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(write_mapping(&records)).unwrap(),
+        std::str::from_utf8(source).unwrap()
+    );
+}